@@ -0,0 +1,417 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::ops::Range;
+use std::path::Path;
+use tauri::WebviewWindow;
+use super::comment_storage;
+use super::comments::{self, CommentsFile};
+use super::{suggestions, watcher, workspace_file};
+
+/// Characters of surrounding text kept on each side of an anchor
+const CONTEXT_CHARS: usize = 32;
+/// Below this many characters a fuzzy match says nothing; only exact ones count
+const MIN_FUZZY_CHARS: usize = 4;
+const MIN_CONFIDENCE: f64 = 0.5;
+const MAX_CANDIDATES: usize = 3;
+/// Windows scored with the cheap bigram measure before the exact one
+const SHORTLIST: usize = 12;
+
+/// What a thread was anchored to when the sidecar was last saved, so it can
+/// be found again if its marker is lost.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct AnchorFingerprint {
+    pub text: String,
+    #[serde(default)]
+    pub prefix: String,
+    #[serde(default)]
+    pub suffix: String,
+}
+
+/// A place a lost thread could be re-attached to. Offsets are byte offsets
+/// into the note body, except across the command boundary, where they are
+/// UTF-16 offsets as the editor counts them (see `into_utf16`).
+#[derive(Serialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct AnchorCandidate {
+    pub thread_id: String,
+    pub start: usize,
+    pub end: usize,
+    pub text: String,
+    /// 0.0–1.0; 1.0 is the same text in the same surroundings
+    pub confidence: f64,
+}
+
+/// UTF-16 offset of byte offset `at` in `text`.
+fn utf16_offset(text: &str, at: usize) -> usize {
+    text[..at].encode_utf16().count()
+}
+
+/// Byte offset of UTF-16 offset `at` in `text`, if it falls on a character.
+fn byte_offset(text: &str, at: usize) -> Option<usize> {
+    let mut units = 0;
+    for (i, ch) in text.char_indices() {
+        if units == at {
+            return Some(i);
+        }
+        if units > at {
+            return None;
+        }
+        units += ch.len_utf16();
+    }
+    (units == at).then_some(text.len())
+}
+
+impl AnchorCandidate {
+    /// The candidate with UTF-16 offsets into `markdown`, for the editor.
+    fn into_utf16(mut self, markdown: &str) -> Self {
+        self.start = utf16_offset(markdown, self.start);
+        self.end = utf16_offset(markdown, self.end);
+        self
+    }
+}
+
+fn context_before(markdown: &str, at: usize) -> String {
+    let before: Vec<char> = markdown[..at].chars().rev().take(CONTEXT_CHARS).collect();
+    before.into_iter().rev().collect()
+}
+
+fn context_after(markdown: &str, at: usize) -> String {
+    markdown[at..].chars().take(CONTEXT_CHARS).collect()
+}
+
+/// Record the anchored text and its surroundings for every thread with a
+/// marker in `markdown`. Threads without one keep their last fingerprint.
+pub fn update_fingerprints(file: &mut CommentsFile, markdown: &str) {
+    for anchor in comments::extract_anchors(markdown) {
+        if let Some(thread) = file.comments.get_mut(&anchor.id) {
+            thread.fingerprint = Some(AnchorFingerprint {
+                text: anchor.text,
+                prefix: context_before(markdown, anchor.start),
+                suffix: context_after(markdown, anchor.end),
+            });
+        }
+    }
+}
+
+fn levenshtein(a: &[char], b: &[char]) -> usize {
+    let mut row: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.iter().enumerate() {
+        let mut diag = row[0];
+        row[0] = i + 1;
+        for (j, cb) in b.iter().enumerate() {
+            let above = row[j + 1];
+            row[j + 1] = if ca == cb {
+                diag
+            } else {
+                1 + diag.min(above).min(row[j])
+            };
+            diag = above;
+        }
+    }
+    row[b.len()]
+}
+
+/// Edit-distance similarity, 1.0 for identical strings.
+fn similarity(a: &[char], b: &[char]) -> f64 {
+    let longest = a.len().max(b.len());
+    if longest == 0 {
+        return 1.0;
+    }
+    1.0 - levenshtein(a, b) as f64 / longest as f64
+}
+
+fn bigrams(chars: &[char]) -> HashMap<(char, char), usize> {
+    let mut counts = HashMap::new();
+    for pair in chars.windows(2) {
+        *counts.entry((pair[0], pair[1])).or_insert(0) += 1;
+    }
+    counts
+}
+
+/// Sørensen–Dice coefficient over character bigrams: a cheap first pass.
+fn dice(a: &HashMap<(char, char), usize>, a_len: usize, b: &[char]) -> f64 {
+    if a_len < 2 || b.len() < 2 {
+        return 0.0;
+    }
+    let mut remaining = a.clone();
+    let mut shared = 0;
+    for pair in b.windows(2) {
+        if let Some(n) = remaining.get_mut(&(pair[0], pair[1])) {
+            if *n > 0 {
+                *n -= 1;
+                shared += 1;
+            }
+        }
+    }
+    2.0 * shared as f64 / ((a_len - 1) + (b.len() - 1)) as f64
+}
+
+/// How well the text around `range` matches the fingerprint's context.
+/// `None` when the fingerprint recorded no context at all.
+fn context_score(fp: &AnchorFingerprint, markdown: &str, range: &Range<usize>) -> Option<f64> {
+    let mut scores = Vec::new();
+    if !fp.prefix.is_empty() {
+        let prefix: Vec<char> = fp.prefix.chars().collect();
+        let found: Vec<char> = context_before(markdown, range.start).chars().collect();
+        scores.push(similarity(&prefix, &found));
+    }
+    if !fp.suffix.is_empty() {
+        let suffix: Vec<char> = fp.suffix.chars().collect();
+        let found: Vec<char> = context_after(markdown, range.end).chars().collect();
+        scores.push(similarity(&suffix, &found));
+    }
+    if scores.is_empty() {
+        None
+    } else {
+        Some(scores.iter().sum::<f64>() / scores.len() as f64)
+    }
+}
+
+/// Byte ranges that must not be wrapped again: existing comment and
+/// suggestion markers.
+fn occupied(markdown: &str) -> Vec<Range<usize>> {
+    let mut ranges: Vec<Range<usize>> = comments::extract_anchors(markdown)
+        .into_iter()
+        .map(|a| a.start..a.end)
+        .collect();
+    ranges.extend(
+        suggestions::extract_suggestions(markdown)
+            .into_iter()
+            .map(|s| s.start..s.end),
+    );
+    ranges
+}
+
+fn overlaps(a: &Range<usize>, b: &Range<usize>) -> bool {
+    a.start < b.end && b.start < a.end
+}
+
+/// Windows of roughly the anchor's length that start and end on word
+/// boundaries, as byte ranges.
+fn fuzzy_windows(markdown: &str, len: usize) -> Vec<Range<usize>> {
+    let chars: Vec<(usize, char)> = markdown.char_indices().collect();
+    let mut starts = Vec::new();
+    let mut ends = Vec::new();
+    for (i, &(byte, ch)) in chars.iter().enumerate() {
+        let prev_space = i == 0 || chars[i - 1].1.is_whitespace();
+        let next_space = chars.get(i + 1).map_or(true, |c| c.1.is_whitespace());
+        if !ch.is_whitespace() {
+            if prev_space {
+                starts.push(i);
+            }
+            if next_space {
+                ends.push((i + 1, byte + ch.len_utf8()));
+            }
+        }
+    }
+    let min = len * 7 / 10;
+    let max = len * 13 / 10 + 1;
+    let mut windows = Vec::new();
+    let mut first_end = 0;
+    for &s in &starts {
+        while first_end < ends.len() && ends[first_end].0 <= s + min {
+            first_end += 1;
+        }
+        for &(e, byte_end) in ends[first_end..].iter().take_while(|(e, _)| *e <= s + max) {
+            if e > s {
+                windows.push(chars[s].0..byte_end);
+            }
+        }
+    }
+    windows
+}
+
+/// Likely new positions for a thread whose marker was lost, best first.
+pub fn find_candidates(
+    thread_id: &str,
+    fp: &AnchorFingerprint,
+    markdown: &str,
+) -> Vec<AnchorCandidate> {
+    let text: Vec<char> = fp.text.chars().collect();
+    if text.is_empty() {
+        return Vec::new();
+    }
+    let taken = occupied(markdown);
+    let free = |r: &Range<usize>| !taken.iter().any(|t| overlaps(t, r));
+
+    // (range, text similarity)
+    let mut scored: Vec<(Range<usize>, f64)> = markdown
+        .match_indices(fp.text.as_str())
+        .map(|(i, m)| (i..i + m.len(), 1.0))
+        .filter(|(r, _)| free(r))
+        .collect();
+
+    if scored.is_empty() && text.len() >= MIN_FUZZY_CHARS {
+        let grams = bigrams(&text);
+        let mut shortlist: Vec<(Range<usize>, f64)> = fuzzy_windows(markdown, text.len())
+            .into_iter()
+            .filter(|r| free(r))
+            .map(|r| {
+                let window: Vec<char> = markdown[r.clone()].chars().collect();
+                let score = dice(&grams, text.len(), &window);
+                (r, score)
+            })
+            .collect();
+        shortlist.sort_by(|a, b| b.1.total_cmp(&a.1));
+        shortlist.truncate(SHORTLIST);
+        scored = shortlist
+            .into_iter()
+            .map(|(r, _)| {
+                let window: Vec<char> = markdown[r.clone()].chars().collect();
+                let score = similarity(&text, &window);
+                (r, score)
+            })
+            .collect();
+    }
+
+    let mut candidates: Vec<AnchorCandidate> = scored
+        .into_iter()
+        .map(|(range, text_score)| {
+            let confidence = match context_score(fp, markdown, &range) {
+                Some(context) => 0.6 * text_score + 0.4 * context,
+                None => text_score,
+            };
+            AnchorCandidate {
+                thread_id: thread_id.to_string(),
+                start: range.start,
+                end: range.end,
+                text: markdown[range].to_string(),
+                confidence: (confidence * 1000.0).round() / 1000.0,
+            }
+        })
+        .filter(|c| c.confidence >= MIN_CONFIDENCE)
+        .collect();
+    candidates.sort_by(|a, b| b.confidence.total_cmp(&a.confidence).then(a.start.cmp(&b.start)));
+
+    // Overlapping windows are the same place found twice; keep the best
+    let mut best: Vec<AnchorCandidate> = Vec::new();
+    for c in candidates {
+        if best.iter().all(|b| !overlaps(&(b.start..b.end), &(c.start..c.end))) {
+            best.push(c);
+        }
+        if best.len() == MAX_CANDIDATES {
+            break;
+        }
+    }
+    best
+}
+
+/// Candidates for every thread that has a fingerprint but no marker.
+pub fn lost_anchor_candidates(file: &CommentsFile, markdown: &str) -> Vec<AnchorCandidate> {
+    let anchored: Vec<String> = comments::extract_anchors(markdown)
+        .into_iter()
+        .map(|a| a.id)
+        .collect();
+    file.comments
+        .iter()
+        .filter(|(id, _)| !anchored.contains(id))
+        .filter_map(|(id, thread)| thread.fingerprint.as_ref().map(|fp| (id, fp)))
+        .flat_map(|(id, fp)| find_candidates(id, fp, markdown))
+        .collect()
+}
+
+/// Suggested positions for threads whose markers disappeared from a note,
+/// e.g. after it was edited outside Gutter.
+#[tauri::command]
+pub fn find_anchor_candidates(
+    window: WebviewWindow,
+    path: String,
+    workspace: Option<String>,
+) -> Result<Vec<AnchorCandidate>, String> {
+    let workspace = workspace_file::owning_root(&window, workspace, &path);
+    let md_path = Path::new(&path);
+    let text = fs::read_to_string(md_path).map_err(|e| format!("Failed to read file: {}", e))?;
+    let markdown = comment_storage::split_embedded(&text).0;
+    let store = comment_storage::store_for(workspace.as_deref());
+    match comments::load_comments_file(store.as_ref(), md_path)? {
+        Some(file) => Ok(lost_anchor_candidates(&file, markdown)
+            .into_iter()
+            .map(|c| c.into_utf16(markdown))
+            .collect()),
+        None => Ok(Vec::new()),
+    }
+}
+
+/// Wrap `start..end` (UTF-16 offsets, as `find_anchor_candidates` gives
+/// them) of the note in a marker for `thread_id` and return the rewritten
+/// note. Leftover pieces of the thread's old marker are removed.
+#[tauri::command]
+pub fn reattach_comment(
+    window: WebviewWindow,
+    path: String,
+    thread_id: String,
+    start: usize,
+    end: usize,
+    workspace: Option<String>,
+) -> Result<String, String> {
+    let workspace = workspace_file::owning_root(&window, workspace, &path);
+    let md_path = Path::new(&path);
+    let text = fs::read_to_string(md_path).map_err(|e| format!("Failed to read file: {}", e))?;
+    let markdown = comment_storage::split_embedded(&text).0;
+    let store = comment_storage::store_for(workspace.as_deref());
+    let mut file = comments::load_comments_file(store.as_ref(), md_path)?
+        .ok_or_else(|| "No comments for this file".to_string())?;
+    if !file.comments.contains_key(&thread_id) {
+        return Err(format!("Comment thread not found: {}", thread_id));
+    }
+    if comments::extract_anchors(markdown).iter().any(|a| a.id == thread_id) {
+        return Err(format!("Comment thread is already anchored: {}", thread_id));
+    }
+    let range = match (byte_offset(markdown, start), byte_offset(markdown, end)) {
+        (Some(start), Some(end)) if start < end => start..end,
+        _ => return Err("Invalid anchor range".to_string()),
+    };
+    if occupied(markdown).iter().any(|t| overlaps(t, &range)) {
+        return Err("Invalid anchor range".to_string());
+    }
+
+    let leftover = format!("<sup>[{}]</sup>", thread_id);
+    let rewritten = format!(
+        "{}<mark>{}</mark><sup>[{}]</sup>{}",
+        markdown[..range.start].replace(&leftover, ""),
+        &markdown[range.clone()],
+        thread_id,
+        markdown[range.end..].replace(&leftover, ""),
+    );
+    update_fingerprints(&mut file, &rewritten);
+
+    // Rewrite the document first: the embedded store appends to it
+    watcher::mark_write(&window, &path);
+    fs::write(md_path, &rewritten).map_err(|e| format!("Failed to write file: {}", e))?;
+    comments::save_comments_file(&window, store.as_ref(), md_path, &file)?;
+    Ok(rewritten)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn candidates_use_utf16_offsets() {
+        let fp = AnchorFingerprint {
+            text: "naïve claim".to_string(),
+            prefix: String::new(),
+            suffix: String::new(),
+        };
+        let markdown = "Émoji 🎉 and a naïve claim here.\n";
+        let found = find_candidates("c1", &fp, markdown);
+        let candidate = found[0].clone().into_utf16(markdown);
+        let utf16: Vec<u16> = markdown.encode_utf16().collect();
+        let text = String::from_utf16(&utf16[candidate.start..candidate.end]).unwrap();
+        assert_eq!(text, "naïve claim");
+        let start = byte_offset(markdown, candidate.start).unwrap();
+        let end = byte_offset(markdown, candidate.end).unwrap();
+        assert_eq!(&markdown[start..end], "naïve claim");
+    }
+
+    #[test]
+    fn offsets_inside_a_character_are_rejected() {
+        // The emoji is two UTF-16 units; 1 falls between them
+        assert_eq!(byte_offset("🎉x", 1), None);
+        assert_eq!(byte_offset("🎉x", 2), Some(4));
+        assert_eq!(byte_offset("🎉x", 3), Some(5));
+        assert_eq!(byte_offset("🎉x", 4), None);
+    }
+}
@@ -0,0 +1,108 @@
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// Environment variable naming a folder to keep all app data in, e.g. to
+/// run an isolated instance in tests.
+pub const HOME_ENV: &str = "GUTTER_HOME";
+
+/// A folder with this name next to the executable turns on portable mode.
+const PORTABLE_DIR: &str = "gutter-data";
+
+/// The single folder all data lives in when running portable:
+/// `$GUTTER_HOME`, else `gutter-data` next to the executable.
+pub fn portable_root() -> Option<PathBuf> {
+    if let Some(dir) = std::env::var_os(HOME_ENV).filter(|v| !v.is_empty()) {
+        return Some(PathBuf::from(dir));
+    }
+    let exe = std::env::current_exe().ok()?;
+    let dir = exe.parent()?.join(PORTABLE_DIR);
+    dir.is_dir().then_some(dir)
+}
+
+/// `~/.gutter`, where everything lived before XDG support. Still the
+/// location on macOS and Windows.
+fn legacy_root() -> PathBuf {
+    dirs::home_dir()
+        .unwrap_or_else(|| PathBuf::from("."))
+        .join(".gutter")
+}
+
+fn resolve(xdg: fn() -> Option<PathBuf>) -> PathBuf {
+    if let Some(root) = portable_root() {
+        return root;
+    }
+    if !cfg!(target_os = "linux") {
+        return legacy_root();
+    }
+    xdg().map(|dir| dir.join("gutter")).unwrap_or_else(legacy_root)
+}
+
+/// Settings: `$XDG_CONFIG_HOME/gutter` on Linux.
+pub fn config_dir() -> PathBuf {
+    resolve(dirs::config_dir)
+}
+
+/// Templates and other user content: `$XDG_DATA_HOME/gutter` on Linux.
+pub fn data_dir() -> PathBuf {
+    resolve(dirs::data_dir)
+}
+
+/// History and other state worth keeping but not worth syncing:
+/// `$XDG_STATE_HOME/gutter` on Linux.
+pub fn state_dir() -> PathBuf {
+    resolve(dirs::state_dir)
+}
+
+fn copy_recursive(from: &Path, to: &Path) -> io::Result<()> {
+    if !from.is_dir() {
+        return fs::copy(from, to).map(|_| ());
+    }
+    fs::create_dir_all(to)?;
+    for entry in fs::read_dir(from)? {
+        let entry = entry?;
+        copy_recursive(&entry.path(), &to.join(entry.file_name()))?;
+    }
+    Ok(())
+}
+
+fn move_path(from: &Path, to: &Path) -> io::Result<()> {
+    if let Some(parent) = to.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    // Renames can't cross filesystems; copy then delete instead
+    if fs::rename(from, to).is_ok() {
+        return Ok(());
+    }
+    copy_recursive(from, to)?;
+    if from.is_dir() {
+        fs::remove_dir_all(from)
+    } else {
+        fs::remove_file(from)
+    }
+}
+
+/// Move data out of `~/.gutter` into the XDG folders, once. Anything that
+/// already exists at its new place wins and the old copy is left behind;
+/// `~/.gutter` itself goes once nothing is left in it.
+pub fn migrate_legacy_dir() -> Result<(), String> {
+    let legacy = legacy_root();
+    if portable_root().is_some() || !legacy.is_dir() {
+        return Ok(());
+    }
+    let moves = [
+        ("config.json", config_dir()),
+        ("config.json.bak", config_dir()),
+        ("templates", data_dir()),
+        ("history", state_dir()),
+    ];
+    for (name, dir) in moves {
+        let (from, to) = (legacy.join(name), dir.join(name));
+        if from == to || !from.exists() || to.exists() {
+            continue;
+        }
+        move_path(&from, &to).map_err(|e| format!("Failed to move {}: {}", from.display(), e))?;
+    }
+    let _ = fs::remove_dir(&legacy);
+    Ok(())
+}
@@ -0,0 +1,266 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use tauri::WebviewWindow;
+use super::comment_storage;
+use super::comments::{self, ThreadStatus};
+use super::{workspace, workspace_file};
+
+#[derive(Deserialize, Clone, Copy)]
+#[serde(rename_all = "lowercase")]
+pub enum ReportFormat {
+    Csv,
+    Json,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReportMessage {
+    pub author: String,
+    pub timestamp: String,
+    pub body: String,
+}
+
+/// One row per thread.
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReportRow {
+    pub file: String,
+    pub anchor_text: String,
+    pub thread_id: String,
+    pub author: String,
+    pub status: ThreadStatus,
+    pub created_at: String,
+    pub resolved_at: Option<String>,
+    pub resolved_by: Option<String>,
+    pub last_activity: String,
+    pub assignees: Vec<String>,
+    pub labels: Vec<String>,
+    pub priority: Option<comments::Priority>,
+    pub due_date: Option<String>,
+    pub messages: Vec<ReportMessage>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CommentExportReport {
+    pub threads: usize,
+    /// Notes whose comments couldn't be read or parsed, left out of the export
+    pub skipped: Vec<String>,
+}
+
+const CSV_HEADER: &[&str] = &[
+    "file",
+    "anchor_text",
+    "thread_id",
+    "author",
+    "status",
+    "created_at",
+    "resolved_at",
+    "resolved_by",
+    "last_activity",
+    "assignees",
+    "labels",
+    "priority",
+    "due_date",
+    "message_count",
+    "messages",
+];
+
+fn status_label(status: ThreadStatus) -> &'static str {
+    match status {
+        ThreadStatus::Open => "open",
+        ThreadStatus::InProgress => "in progress",
+        ThreadStatus::WontFix => "won't fix",
+        ThreadStatus::Resolved => "resolved",
+    }
+}
+
+fn priority_label(priority: comments::Priority) -> &'static str {
+    match priority {
+        comments::Priority::Low => "low",
+        comments::Priority::Normal => "normal",
+        comments::Priority::High => "high",
+        comments::Priority::Urgent => "urgent",
+    }
+}
+
+/// Quote a CSV field when needed. Leading characters that spreadsheets treat
+/// as a formula are neutralized with a `'` prefix.
+fn csv_field(value: &str) -> String {
+    let value = if value.starts_with(['=', '+', '-', '@', '\t', '\r']) {
+        format!("'{}", value)
+    } else {
+        value.to_string()
+    };
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value
+    }
+}
+
+/// Collapse the anchor's whitespace so it reads well in a single cell.
+fn clean_anchor(text: &str) -> String {
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// Rows for every thread under `scope`, and the notes whose comments
+/// couldn't be loaded. One broken sidecar doesn't stop the rest.
+pub fn collect_rows(workspace: Option<&str>, scope: &Path) -> (Vec<ReportRow>, Vec<String>) {
+    let store = comment_storage::store_for(workspace);
+    let (files, base): (Vec<PathBuf>, PathBuf) = if scope.is_dir() {
+        let files = match workspace.map(Path::new).filter(|ws| scope.starts_with(ws)) {
+            Some(ws) => workspace::collect_markdown_files_in(ws, scope),
+            None => workspace::collect_markdown_files(scope),
+        };
+        (files, scope.to_path_buf())
+    } else {
+        let parent = scope.parent().unwrap_or(Path::new(".")).to_path_buf();
+        (vec![scope.to_path_buf()], parent)
+    };
+    // Report paths relative to the workspace when the scope is inside it
+    let base = workspace
+        .map(PathBuf::from)
+        .filter(|ws| scope.starts_with(ws))
+        .unwrap_or(base);
+
+    let mut rows = Vec::new();
+    let mut skipped = Vec::new();
+    for md_path in files {
+        let rel = md_path
+            .strip_prefix(&base)
+            .unwrap_or(&md_path)
+            .to_string_lossy()
+            .replace('\\', "/");
+        let file = match comments::load_comments_file(store.as_ref(), &md_path) {
+            Ok(Some(f)) => f,
+            Ok(None) => continue,
+            Err(_) => {
+                skipped.push(rel);
+                continue;
+            }
+        };
+        let markdown = fs::read_to_string(&md_path).unwrap_or_default();
+        let anchors = comments::extract_anchors(&markdown);
+        let anchor_text: HashMap<&str, &str> = anchors
+            .iter()
+            .map(|a| (a.id.as_str(), a.text.as_str()))
+            .collect();
+        // Document order first, then threads whose marker is gone
+        let mut ids: Vec<&String> = Vec::new();
+        for a in &anchors {
+            if let Some((id, _)) = file.comments.get_key_value(&a.id) {
+                if !ids.contains(&id) {
+                    ids.push(id);
+                }
+            }
+        }
+        for id in file.comments.keys() {
+            if !ids.contains(&id) {
+                ids.push(id);
+            }
+        }
+
+        for id in ids {
+            let thread = &file.comments[id];
+            let last_activity = thread
+                .thread
+                .iter()
+                .map(|m| m.timestamp.as_str())
+                .chain(thread.resolved_at.as_deref())
+                .max()
+                .unwrap_or(&thread.created_at)
+                .to_string();
+            rows.push(ReportRow {
+                file: rel.clone(),
+                anchor_text: anchor_text
+                    .get(id.as_str())
+                    .map(|t| clean_anchor(t))
+                    .unwrap_or_default(),
+                thread_id: id.clone(),
+                author: thread.thread.first().map(|m| m.author.clone()).unwrap_or_default(),
+                status: thread.effective_status(),
+                created_at: thread.created_at.clone(),
+                resolved_at: thread.resolved_at.clone(),
+                resolved_by: thread.resolved_by.clone(),
+                last_activity,
+                assignees: thread.assignees.clone(),
+                labels: thread.labels.clone(),
+                priority: thread.priority,
+                due_date: thread.due_date.clone(),
+                messages: thread
+                    .thread
+                    .iter()
+                    .map(|m| ReportMessage {
+                        author: m.author.clone(),
+                        timestamp: m.timestamp.clone(),
+                        body: m.body.clone(),
+                    })
+                    .collect(),
+            });
+        }
+    }
+    (rows, skipped)
+}
+
+pub fn rows_to_csv(rows: &[ReportRow]) -> String {
+    // BOM so Excel opens the file as UTF-8
+    let mut out = String::from("\u{feff}");
+    out.push_str(&CSV_HEADER.join(","));
+    out.push_str("\r\n");
+    for row in rows {
+        let messages = row
+            .messages
+            .iter()
+            .map(|m| format!("{} ({}): {}", m.author, m.timestamp, m.body))
+            .collect::<Vec<_>>()
+            .join("\n");
+        let fields = [
+            row.file.clone(),
+            row.anchor_text.clone(),
+            row.thread_id.clone(),
+            row.author.clone(),
+            status_label(row.status).to_string(),
+            row.created_at.clone(),
+            row.resolved_at.clone().unwrap_or_default(),
+            row.resolved_by.clone().unwrap_or_default(),
+            row.last_activity.clone(),
+            row.assignees.join("; "),
+            row.labels.join("; "),
+            row.priority.map(priority_label).unwrap_or_default().to_string(),
+            row.due_date.clone().unwrap_or_default(),
+            row.messages.len().to_string(),
+            messages,
+        ];
+        let line: Vec<String> = fields.iter().map(|f| csv_field(f)).collect();
+        out.push_str(&line.join(","));
+        out.push_str("\r\n");
+    }
+    out
+}
+
+/// Export every thread under `scope` (a note or a folder) to `output` as CSV
+/// or a JSON array of rows.
+#[tauri::command]
+pub fn export_comments_report(
+    window: WebviewWindow,
+    scope: String,
+    format: ReportFormat,
+    output: String,
+    workspace: Option<String>,
+) -> Result<CommentExportReport, String> {
+    let workspace = workspace_file::owning_root(&window, workspace, &scope);
+    let (rows, skipped) = collect_rows(workspace.as_deref(), Path::new(&scope));
+    let content = match format {
+        ReportFormat::Csv => rows_to_csv(&rows),
+        ReportFormat::Json => serde_json::to_string_pretty(&rows)
+            .map_err(|e| format!("Failed to serialize report: {}", e))?,
+    };
+    fs::write(&output, content).map_err(|e| format!("Failed to write report: {}", e))?;
+    Ok(CommentExportReport {
+        threads: rows.len(),
+        skipped,
+    })
+}
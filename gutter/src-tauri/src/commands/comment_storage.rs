@@ -0,0 +1,325 @@
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Info string of the fenced block used by the embedded store.
+const EMBED_FENCE: &str = "```gutter-comments";
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Debug, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum StorageMode {
+    /// `note.comments.json` next to `note.md` (the original layout)
+    #[default]
+    Sidecar,
+    /// `<workspace>/.gutter/comments/<relative-path>.json`
+    Central,
+    /// Fenced `gutter-comments` block at the end of the markdown file
+    Embedded,
+}
+
+/// Where a document's comment threads live. `content` is always the raw
+/// `CommentsFile` JSON the frontend reads and writes.
+pub trait CommentStore {
+    fn read(&self, md_path: &Path) -> Result<Option<String>, String>;
+    fn write(&self, md_path: &Path, content: &str) -> Result<(), String>;
+    fn delete(&self, md_path: &Path) -> Result<(), String>;
+    /// File that changes when the comments are written (for watcher suppression)
+    fn location(&self, md_path: &Path) -> PathBuf;
+    /// Path of the generated `.comments.md` companion, if this mode keeps one
+    fn companion_location(&self, md_path: &Path) -> Option<PathBuf>;
+}
+
+pub struct SidecarStore;
+
+pub struct CentralStore {
+    workspace: PathBuf,
+}
+
+pub struct EmbeddedStore;
+
+impl CommentStore for SidecarStore {
+    fn read(&self, md_path: &Path) -> Result<Option<String>, String> {
+        read_if_exists(&self.location(md_path))
+    }
+
+    fn write(&self, md_path: &Path, content: &str) -> Result<(), String> {
+        fs::write(self.location(md_path), content)
+            .map_err(|e| format!("Failed to write comments: {}", e))
+    }
+
+    fn delete(&self, md_path: &Path) -> Result<(), String> {
+        remove_if_exists(&self.location(md_path))
+    }
+
+    fn location(&self, md_path: &Path) -> PathBuf {
+        PathBuf::from(comments_json_path(&md_path.to_string_lossy()))
+    }
+
+    fn companion_location(&self, md_path: &Path) -> Option<PathBuf> {
+        Some(PathBuf::from(comments_md_path(&md_path.to_string_lossy())))
+    }
+}
+
+impl CentralStore {
+    pub fn new(workspace: &Path) -> Self {
+        CentralStore {
+            workspace: workspace.to_path_buf(),
+        }
+    }
+
+    /// Path of the note relative to the workspace, or None if it lives outside
+    fn relative(&self, md_path: &Path) -> Option<PathBuf> {
+        md_path
+            .strip_prefix(&self.workspace)
+            .ok()
+            .map(|p| p.to_path_buf())
+    }
+
+    fn store_path(&self, md_path: &Path, suffix: &str) -> Option<PathBuf> {
+        let rel = self.relative(md_path)?;
+        let mut name = rel.file_name()?.to_os_string();
+        name.push(suffix);
+        Some(
+            self.workspace
+                .join(".gutter")
+                .join("comments")
+                .join(rel.with_file_name(name)),
+        )
+    }
+}
+
+impl CommentStore for CentralStore {
+    fn read(&self, md_path: &Path) -> Result<Option<String>, String> {
+        read_if_exists(&self.location(md_path))
+    }
+
+    fn write(&self, md_path: &Path, content: &str) -> Result<(), String> {
+        let path = self.location(md_path);
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)
+                .map_err(|e| format!("Failed to create comments directory: {}", e))?;
+        }
+        fs::write(&path, content).map_err(|e| format!("Failed to write comments: {}", e))
+    }
+
+    fn delete(&self, md_path: &Path) -> Result<(), String> {
+        remove_if_exists(&self.location(md_path))
+    }
+
+    fn location(&self, md_path: &Path) -> PathBuf {
+        // Notes outside the workspace fall back to the sidecar layout
+        self.store_path(md_path, ".json")
+            .unwrap_or_else(|| SidecarStore.location(md_path))
+    }
+
+    fn companion_location(&self, md_path: &Path) -> Option<PathBuf> {
+        self.store_path(md_path, ".comments.md")
+            .or_else(|| SidecarStore.companion_location(md_path))
+    }
+}
+
+impl CommentStore for EmbeddedStore {
+    fn read(&self, md_path: &Path) -> Result<Option<String>, String> {
+        let Some(text) = read_if_exists(md_path)? else {
+            return Ok(None);
+        };
+        Ok(split_embedded(&text).1.map(|s| s.to_string()))
+    }
+
+    fn write(&self, md_path: &Path, content: &str) -> Result<(), String> {
+        let text = read_if_exists(md_path)?.unwrap_or_default();
+        let (body, _) = split_embedded(&text);
+        fs::write(md_path, append_embedded(body, content))
+            .map_err(|e| format!("Failed to write comments: {}", e))
+    }
+
+    fn delete(&self, md_path: &Path) -> Result<(), String> {
+        let Some(text) = read_if_exists(md_path)? else {
+            return Ok(());
+        };
+        let (body, block) = split_embedded(&text);
+        if block.is_none() {
+            return Ok(());
+        }
+        fs::write(md_path, body).map_err(|e| format!("Failed to delete comments: {}", e))
+    }
+
+    fn location(&self, md_path: &Path) -> PathBuf {
+        md_path.to_path_buf()
+    }
+
+    fn companion_location(&self, _md_path: &Path) -> Option<PathBuf> {
+        // The whole point of this mode is to keep a single file per note
+        None
+    }
+}
+
+/// Split a markdown document into its body and the JSON of a trailing
+/// `gutter-comments` fenced block, if it has one.
+pub fn split_embedded(text: &str) -> (&str, Option<&str>) {
+    let trimmed = text.trim_end();
+    let Some(inner) = trimmed.strip_suffix("```") else {
+        return (text, None);
+    };
+    // The opening fence must start its own line; pick the last one that does
+    let Some(start) = inner
+        .rmatch_indices(EMBED_FENCE)
+        .map(|(i, _)| i)
+        .find(|&i| i == 0 || inner[..i].ends_with('\n'))
+    else {
+        return (text, None);
+    };
+    let Some(json) = inner[start + EMBED_FENCE.len()..].strip_prefix('\n') else {
+        return (text, None);
+    };
+    if !json.is_empty() && !json.ends_with('\n') {
+        return (text, None);
+    }
+    // Drop only the blank line `append_embedded` put before the block, so
+    // the body comes back as it was written
+    let body = &inner[..start];
+    let body = body.strip_suffix("\n\n").or_else(|| body.strip_suffix('\n')).unwrap_or(body);
+    (body, Some(json.trim_end()))
+}
+
+/// Append comments JSON to a document body as a `gutter-comments` block,
+/// after a blank line. The body is kept exactly, trailing whitespace and
+/// all, so `split_embedded` gives it back unchanged.
+pub fn append_embedded(body: &str, json: &str) -> String {
+    let sep = if body.is_empty() { "" } else { "\n\n" };
+    format!("{}{}{}\n{}\n```\n", body, sep, EMBED_FENCE, json.trim_end())
+}
+
+pub fn comments_json_path(md_path: &str) -> String {
+    let p = Path::new(md_path);
+    let stem = p.file_stem().unwrap_or_default().to_string_lossy();
+    let parent = p.parent().unwrap_or(Path::new("."));
+    parent.join(format!("{}.comments.json", stem)).to_string_lossy().to_string()
+}
+
+pub fn comments_md_path(md_path: &str) -> String {
+    let p = Path::new(md_path);
+    let stem = p.file_stem().unwrap_or_default().to_string_lossy();
+    let parent = p.parent().unwrap_or(Path::new("."));
+    parent.join(format!("{}.comments.md", stem)).to_string_lossy().to_string()
+}
+
+fn workspace_config_path(workspace: &Path) -> PathBuf {
+    workspace.join(".gutter").join("config.json")
+}
+
+/// Storage mode configured for a workspace (`commentStorage` in
+/// `<workspace>/.gutter/config.json`), defaulting to sidecar files.
+pub fn workspace_mode(workspace: &Path) -> StorageMode {
+    fs::read_to_string(workspace_config_path(workspace))
+        .ok()
+        .and_then(|s| serde_json::from_str::<serde_json::Value>(&s).ok())
+        .and_then(|v| v.get("commentStorage").cloned())
+        .and_then(|v| serde_json::from_value(v).ok())
+        .unwrap_or_default()
+}
+
+pub fn set_workspace_mode(workspace: &Path, mode: StorageMode) -> Result<(), String> {
+    let path = workspace_config_path(workspace);
+    let mut config: serde_json::Map<String, serde_json::Value> = fs::read_to_string(&path)
+        .ok()
+        .and_then(|s| serde_json::from_str(&s).ok())
+        .unwrap_or_default();
+    config.insert(
+        "commentStorage".to_string(),
+        serde_json::to_value(mode).map_err(|e| format!("Failed to serialize: {}", e))?,
+    );
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)
+            .map_err(|e| format!("Failed to create workspace config directory: {}", e))?;
+    }
+    let json = serde_json::to_string_pretty(&config)
+        .map_err(|e| format!("Failed to serialize: {}", e))?;
+    fs::write(&path, json).map_err(|e| format!("Failed to write workspace config: {}", e))
+}
+
+pub fn store_for_mode(mode: StorageMode, workspace: Option<&Path>) -> Box<dyn CommentStore> {
+    match (mode, workspace) {
+        (StorageMode::Central, Some(ws)) => Box::new(CentralStore::new(ws)),
+        (StorageMode::Embedded, _) => Box::new(EmbeddedStore),
+        _ => Box::new(SidecarStore),
+    }
+}
+
+/// The store a workspace is configured to use. Without a workspace (a single
+/// file opened on its own) comments always go to the sidecar.
+pub fn store_for(workspace: Option<&str>) -> Box<dyn CommentStore> {
+    match workspace {
+        Some(ws) => {
+            let ws = Path::new(ws);
+            store_for_mode(workspace_mode(ws), Some(ws))
+        }
+        None => Box::new(SidecarStore),
+    }
+}
+
+fn read_if_exists(path: &Path) -> Result<Option<String>, String> {
+    if !path.exists() {
+        return Ok(None);
+    }
+    fs::read_to_string(path)
+        .map(Some)
+        .map_err(|e| format!("Failed to read comments: {}", e))
+}
+
+fn remove_if_exists(path: &Path) -> Result<(), String> {
+    if path.exists() {
+        fs::remove_file(path).map_err(|e| format!("Failed to delete comments: {}", e))?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const JSON: &str = "{\n  \"version\": 1,\n  \"comments\": {}\n}";
+
+    #[test]
+    fn split_gives_back_what_append_wrote() {
+        for body in ["", "Text", "Text\n", "# Title\n\nBody.\n\n\n", "trailing spaces  \n", "\n"] {
+            let text = append_embedded(body, JSON);
+            assert_eq!(split_embedded(&text), (body, Some(JSON)), "{:?}", body);
+        }
+    }
+
+    #[test]
+    fn split_ignores_other_fences() {
+        for text in [
+            "No block here.\n",
+            "```gutter-comments\n{}\n```\nthen more text\n",
+            "inline ```gutter-comments\n{}\n```\n",
+            "```rust\nfn main() {}\n```\n",
+        ] {
+            assert_eq!(split_embedded(text), (text, None), "{:?}", text);
+        }
+        // A block written by hand without the blank line before it
+        assert_eq!(split_embedded("Body.\n```gutter-comments\n{}\n```"), ("Body.", Some("{}")));
+    }
+
+    #[test]
+    fn embedded_store_round_trips_the_note() {
+        let dir = std::env::temp_dir().join(format!("gutter-embedded-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let md_path = dir.join("note.md");
+        let note = "# Note\n\nBody with a trailing blank line.\n\n";
+        fs::write(&md_path, note).unwrap();
+
+        let store = EmbeddedStore;
+        store.write(&md_path, JSON).unwrap();
+        assert_eq!(store.read(&md_path).unwrap().as_deref(), Some(JSON));
+        // Writing again replaces the block rather than stacking another
+        store.write(&md_path, JSON).unwrap();
+        assert_eq!(split_embedded(&fs::read_to_string(&md_path).unwrap()).0, note);
+
+        store.delete(&md_path).unwrap();
+        assert_eq!(fs::read_to_string(&md_path).unwrap(), note);
+        assert_eq!(store.read(&md_path).unwrap(), None);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -1,72 +1,910 @@
+use serde::{Deserialize, Serialize};
+use std::collections::hash_map::RandomState;
+use std::collections::{BTreeMap, HashSet};
+use std::hash::{BuildHasher, Hasher};
 use std::fs;
 use std::path::Path;
-use tauri::AppHandle;
-use super::watcher;
+use std::time::{SystemTime, UNIX_EPOCH};
+use tauri::WebviewWindow;
+use super::anchoring::{self, AnchorFingerprint};
+use super::comment_storage::{self, CommentStore, StorageMode};
+use super::{settings, suggestions, watcher, workspace, workspace_file};
 
-#[tauri::command]
-pub fn read_comments(path: String) -> Result<String, String> {
-    let comments_path = comments_json_path(&path);
-    if Path::new(&comments_path).exists() {
-        fs::read_to_string(&comments_path)
-            .map_err(|e| format!("Failed to read comments: {}", e))
+/// Mirror of `CommentsFile` in `src/types/comments.ts`. Fields the backend
+/// doesn't know about are kept in `extra` so a round-trip never drops them.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct CommentsFile {
+    pub version: u32,
+    #[serde(default)]
+    pub comments: BTreeMap<String, CommentThread>,
+    /// Tracked changes, keyed by the `sN` id in their markdown marker
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub suggestions: BTreeMap<String, Suggestion>,
+    #[serde(flatten)]
+    pub extra: serde_json::Map<String, serde_json::Value>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Debug)]
+#[serde(rename_all = "lowercase")]
+pub enum SuggestionKind {
+    Insert,
+    Delete,
+    Replace,
+}
+
+/// A proposed edit. The text itself lives in the markdown as
+/// `<del>old</del><ins>new</ins><sup>[sN]</sup>` (either part optional);
+/// the copies here are for listings and the companion file.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct Suggestion {
+    pub kind: SuggestionKind,
+    pub author: String,
+    pub timestamp: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub deleted_text: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub inserted_text: Option<String>,
+    #[serde(flatten)]
+    pub extra: serde_json::Map<String, serde_json::Value>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct CommentMessage {
+    pub id: String,
+    pub author: String,
+    pub timestamp: String,
+    pub body: String,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Debug)]
+#[serde(rename_all = "camelCase")]
+pub enum ThreadStatus {
+    Open,
+    InProgress,
+    WontFix,
+    Resolved,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Debug)]
+#[serde(rename_all = "lowercase")]
+pub enum Priority {
+    Low,
+    Normal,
+    High,
+    Urgent,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct StatusChange {
+    pub from: ThreadStatus,
+    pub to: ThreadStatus,
+    pub by: String,
+    pub at: String,
+}
+
+/// A comment thread. Everything after `created_at` is review workflow state
+/// that older files don't have, so it all defaults and is omitted when empty.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct CommentThread {
+    pub thread: Vec<CommentMessage>,
+    #[serde(default)]
+    pub resolved: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub resolved_by: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub resolved_at: Option<String>,
+    #[serde(default)]
+    pub created_at: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub status: Option<ThreadStatus>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub assignees: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub priority: Option<Priority>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub labels: Vec<String>,
+    /// ISO 8601 date (`YYYY-MM-DD`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub due_date: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub status_history: Vec<StatusChange>,
+    /// Bumped by each workflow update, so a save from an editor that loaded
+    /// an older copy can't undo it
+    #[serde(default, skip_serializing_if = "is_zero")]
+    pub workflow_revision: u32,
+    /// Anchored text as of the last save, for re-anchoring a lost marker
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fingerprint: Option<AnchorFingerprint>,
+    #[serde(flatten)]
+    pub extra: serde_json::Map<String, serde_json::Value>,
+}
+
+fn is_zero(n: &u32) -> bool {
+    *n == 0
+}
+
+impl Default for CommentsFile {
+    fn default() -> Self {
+        CommentsFile {
+            version: 1,
+            comments: BTreeMap::new(),
+            suggestions: BTreeMap::new(),
+            extra: serde_json::Map::new(),
+        }
+    }
+}
+
+impl CommentThread {
+    /// An open thread with no messages yet.
+    pub fn new(created_at: String) -> Self {
+        CommentThread {
+            thread: Vec::new(),
+            resolved: false,
+            resolved_by: None,
+            resolved_at: None,
+            created_at,
+            status: None,
+            assignees: Vec::new(),
+            priority: None,
+            labels: Vec::new(),
+            due_date: None,
+            status_history: Vec::new(),
+            workflow_revision: 0,
+            fingerprint: None,
+            extra: serde_json::Map::new(),
+        }
+    }
+
+    /// Effective workflow status. The editor only toggles `resolved`, so that
+    /// flag wins whenever it disagrees with the stored status.
+    pub fn effective_status(&self) -> ThreadStatus {
+        match (self.resolved, self.status) {
+            (true, Some(ThreadStatus::WontFix)) => ThreadStatus::WontFix,
+            (true, _) => ThreadStatus::Resolved,
+            (false, Some(ThreadStatus::WontFix | ThreadStatus::Resolved)) => ThreadStatus::Open,
+            (false, Some(status)) => status,
+            (false, None) => ThreadStatus::Open,
+        }
+    }
+
+    /// Move the thread to `status`, keeping `resolved` in sync and recording
+    /// the transition. No-op if the status doesn't change.
+    pub fn set_status(&mut self, status: ThreadStatus, by: &str, at: &str) {
+        let from = self.effective_status();
+        if from == status {
+            return;
+        }
+        let closed = matches!(status, ThreadStatus::Resolved | ThreadStatus::WontFix);
+        self.resolved = closed;
+        if closed {
+            self.resolved_by = Some(by.to_string());
+            self.resolved_at = Some(at.to_string());
+        } else {
+            self.resolved_by = None;
+            self.resolved_at = None;
+        }
+        self.status = Some(status);
+        self.status_history.push(StatusChange {
+            from,
+            to: status,
+            by: by.to_string(),
+            at: at.to_string(),
+        });
+    }
+}
+
+/// Partial update of a thread's workflow fields. Omitted fields are left
+/// alone; `"priority": null` and an empty `dueDate` clear those fields.
+#[derive(Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct WorkflowUpdate {
+    pub status: Option<ThreadStatus>,
+    pub assignees: Option<Vec<String>>,
+    #[serde(default, deserialize_with = "present")]
+    pub priority: Option<Option<Priority>>,
+    pub labels: Option<Vec<String>>,
+    pub due_date: Option<String>,
+}
+
+/// Distinguish an explicit `null` (Some(None)) from an omitted field (None).
+fn present<'de, D, T>(deserializer: D) -> Result<Option<Option<T>>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+    T: Deserialize<'de>,
+{
+    Option::<T>::deserialize(deserializer).map(Some)
+}
+
+#[derive(Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct CommentQuery {
+    /// Restrict to a single note or a folder
+    pub path: Option<String>,
+    /// Any of these statuses (all when empty)
+    #[serde(default)]
+    pub status: Vec<ThreadStatus>,
+    pub assignee: Option<String>,
+    pub label: Option<String>,
+    pub min_priority: Option<Priority>,
+    /// Due on or before this date
+    pub due_before: Option<String>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ThreadMatch {
+    pub path: String,
+    pub thread_id: String,
+    pub status: ThreadStatus,
+    pub thread: CommentThread,
+}
+
+impl CommentQuery {
+    fn matches(&self, thread: &CommentThread) -> bool {
+        if !self.status.is_empty() && !self.status.contains(&thread.effective_status()) {
+            return false;
+        }
+        if let Some(assignee) = &self.assignee {
+            if !thread.assignees.iter().any(|a| a.eq_ignore_ascii_case(assignee)) {
+                return false;
+            }
+        }
+        if let Some(label) = &self.label {
+            if !thread.labels.iter().any(|l| l.eq_ignore_ascii_case(label)) {
+                return false;
+            }
+        }
+        if let Some(min) = self.min_priority {
+            if thread.priority.map_or(true, |p| p < min) {
+                return false;
+            }
+        }
+        if let Some(before) = &self.due_before {
+            // ISO dates compare correctly as strings
+            match &thread.due_date {
+                Some(due) if due.as_str() <= before.as_str() => {}
+                _ => return false,
+            }
+        }
+        true
+    }
+}
+
+/// Current time as an ISO 8601 UTC string, matching `Date.toISOString()`.
+pub fn iso_now() -> String {
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default();
+    let secs = now.as_secs();
+    let (y, m, d) = civil_from_days((secs / 86_400) as i64);
+    let rem = secs % 86_400;
+    format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}.{:03}Z",
+        y,
+        m,
+        d,
+        rem / 3600,
+        (rem % 3600) / 60,
+        rem % 60,
+        now.subsec_millis()
+    )
+}
+
+/// Days since the Unix epoch to a (year, month, day) civil date.
+fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let d = (doy - (153 * mp + 2) / 5 + 1) as u32;
+    let m = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+    let y = yoe + era * 400 + if m <= 2 { 1 } else { 0 };
+    (y, m, d)
+}
+
+/// Whether `date` is a real calendar date written `YYYY-MM-DD`, the form
+/// the due-date filters compare as text.
+fn is_iso_date(date: &str) -> bool {
+    let b = date.as_bytes();
+    if b.len() != 10 || b[4] != b'-' || b[7] != b'-' {
+        return false;
+    }
+    let digits = |r: std::ops::Range<usize>| -> Option<u32> {
+        if b[r.clone()].iter().all(u8::is_ascii_digit) {
+            date[r].parse().ok()
+        } else {
+            None
+        }
+    };
+    let (Some(y), Some(m), Some(d)) = (digits(0..4), digits(5..7), digits(8..10)) else {
+        return false;
+    };
+    let leap = y % 4 == 0 && (y % 100 != 0 || y % 400 == 0);
+    let days = match m {
+        2 if leap => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        1..=12 => 31,
+        _ => return false,
+    };
+    (1..=days).contains(&d)
+}
+
+/// A `<mark>text</mark><sup>[cN]</sup>` comment marker in a markdown document.
+#[derive(Clone, Debug)]
+pub struct Anchor {
+    pub id: String,
+    pub text: String,
+    /// Byte range of the whole marker, tags included
+    pub start: usize,
+    pub end: usize,
+}
+
+const MARK_OPEN: &str = "<mark>";
+const MARK_CLOSE: &str = "</mark><sup>[c";
+
+/// Comment markers in document order. Mirrors `MARKER_RE` in `useComments.ts`.
+pub fn extract_anchors(markdown: &str) -> Vec<Anchor> {
+    let mut anchors = Vec::new();
+    let mut pos = 0;
+    while let Some(rel) = markdown[pos..].find(MARK_OPEN) {
+        let start = pos + rel;
+        let text_start = start + MARK_OPEN.len();
+        let mut search = text_start;
+        let mut found = None;
+        while let Some(close_rel) = markdown[search..].find(MARK_CLOSE) {
+            let close = search + close_rel;
+            let digits_start = close + MARK_CLOSE.len();
+            let digits = markdown[digits_start..]
+                .bytes()
+                .take_while(|b| b.is_ascii_digit())
+                .count();
+            let tail = &markdown[digits_start + digits..];
+            if digits > 0 && tail.starts_with("]</sup>") {
+                found = Some((close, digits_start, digits));
+                break;
+            }
+            search = close + MARK_CLOSE.len();
+        }
+        match found {
+            Some((close, digits_start, digits)) => {
+                let end = digits_start + digits + "]</sup>".len();
+                anchors.push(Anchor {
+                    id: format!("c{}", &markdown[digits_start..digits_start + digits]),
+                    text: markdown[text_start..close].to_string(),
+                    start,
+                    end,
+                });
+                pos = end;
+            }
+            None => pos = text_start,
+        }
+    }
+    anchors
+}
+
+/// Fresh message id in the same `m_xxxxxxxx` shape the editor generates.
+pub fn new_message_id() -> String {
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_nanos();
+    // RandomState is seeded per instance, which is all the randomness we need
+    let mut hasher = RandomState::new().build_hasher();
+    hasher.write_u128(nanos);
+    format!("m_{:08x}", hasher.finish() as u32)
+}
+
+pub fn parse_comments_file(content: &str) -> Result<CommentsFile, String> {
+    serde_json::from_str(content).map_err(|e| format!("Invalid comments file: {}", e))
+}
+
+pub fn load_comments_file(
+    store: &dyn CommentStore,
+    md_path: &Path,
+) -> Result<Option<CommentsFile>, String> {
+    match store.read(md_path)? {
+        Some(content) if !content.trim().is_empty() => parse_comments_file(&content).map(Some),
+        _ => Ok(None),
+    }
+}
+
+pub fn save_comments_file(
+    window: &WebviewWindow,
+    store: &dyn CommentStore,
+    md_path: &Path,
+    file: &CommentsFile,
+) -> Result<(), String> {
+    let json = serde_json::to_string_pretty(file)
+        .map_err(|e| format!("Failed to serialize comments: {}", e))?;
+    watcher::mark_write(window, &store.location(md_path).to_string_lossy());
+    store.write(md_path, &json)
+}
+
+/// Carry backend-owned state (workflow history, suggestions) over from the
+/// stored copy and record resolve/reopen toggles the editor made since.
+/// `suggestions_sent` says whether the editor's copy has a `suggestions`
+/// key at all; `markdown` is the note as just saved.
+fn merge_backend_state(
+    incoming: &mut CommentsFile,
+    stored: &CommentsFile,
+    suggestions_sent: bool,
+    markdown: &str,
+    workspace: Option<&Path>,
+) {
+    if suggestions_sent {
+        // A stored suggestion the editor left out was accepted, rejected or
+        // deleted there, unless its marker is still in the note (then it was
+        // added after the editor loaded)
+        let markers: HashSet<String> = suggestions::extract_suggestions(markdown)
+            .into_iter()
+            .map(|m| m.id)
+            .collect();
+        for (id, previous) in &stored.suggestions {
+            match incoming.suggestions.get_mut(id) {
+                Some(suggestion) => {
+                    if suggestion.deleted_text.is_none() {
+                        suggestion.deleted_text = previous.deleted_text.clone();
+                    }
+                    if suggestion.inserted_text.is_none() {
+                        suggestion.inserted_text = previous.inserted_text.clone();
+                    }
+                    for (key, value) in &previous.extra {
+                        suggestion.extra.entry(key.clone()).or_insert_with(|| value.clone());
+                    }
+                }
+                None if markers.contains(id) => {
+                    incoming.suggestions.insert(id.clone(), previous.clone());
+                }
+                None => {}
+            }
+        }
     } else {
-        Ok(String::new())
+        // An editor that doesn't know about suggestions must not drop them
+        incoming.suggestions = stored.suggestions.clone();
+    }
+
+    let author = settings::configured_author(workspace).unwrap_or_else(|| "Unknown".to_string());
+    for (id, thread) in incoming.comments.iter_mut() {
+        let Some(previous) = stored.comments.get(id) else {
+            continue;
+        };
+        // The status the editor loaded is the last change in the history it
+        // was sent; threads start open
+        let loaded = thread
+            .status_history
+            .last()
+            .map_or(ThreadStatus::Open, |change| change.to);
+        if previous.status_history.len() > thread.status_history.len() {
+            thread.status_history = previous.status_history.clone();
+        }
+        // The editor loaded this thread before a workflow update, so its
+        // workflow fields are out of date. Its status is too, unless the
+        // user resolved or reopened the thread there since
+        if thread.workflow_revision < previous.workflow_revision {
+            thread.workflow_revision = previous.workflow_revision;
+            thread.assignees = previous.assignees.clone();
+            thread.priority = previous.priority;
+            thread.labels = previous.labels.clone();
+            thread.due_date = previous.due_date.clone();
+            if thread.effective_status() == loaded {
+                thread.status = previous.status;
+                thread.resolved = previous.resolved;
+                thread.resolved_by = previous.resolved_by.clone();
+                thread.resolved_at = previous.resolved_at.clone();
+            }
+        }
+        if thread.fingerprint.is_none() {
+            thread.fingerprint = previous.fingerprint.clone();
+        }
+        let from = previous.effective_status();
+        let to = thread.effective_status();
+        if from != to {
+            let by = if thread.resolved {
+                thread.resolved_by.clone().unwrap_or_else(|| author.clone())
+            } else {
+                author.clone()
+            };
+            let at = thread.resolved_at.clone().filter(|_| thread.resolved).unwrap_or_else(iso_now);
+            thread.status = Some(to);
+            thread.status_history.push(StatusChange { from, to, by, at });
+        }
     }
 }
 
+#[derive(Serialize)]
+pub struct MigrationReport {
+    pub mode: StorageMode,
+    pub migrated: usize,
+    pub failed: Vec<String>,
+}
+
+#[tauri::command]
+pub fn read_comments(window: WebviewWindow, path: String, workspace: Option<String>) -> Result<String, String> {
+    let workspace = workspace_file::owning_root(&window, workspace, &path);
+    let store = comment_storage::store_for(workspace.as_deref());
+    Ok(store.read(Path::new(&path))?.unwrap_or_default())
+}
+
+#[tauri::command]
+pub fn write_comments(
+    window: WebviewWindow,
+    path: String,
+    content: String,
+    workspace: Option<String>,
+) -> Result<(), String> {
+    let workspace = workspace_file::owning_root(&window, workspace, &path);
+    let store = comment_storage::store_for(workspace.as_deref());
+    let md_path = Path::new(&path);
+
+    // Content the backend can't parse is still written as-is
+    let parsed = serde_json::from_str::<serde_json::Value>(&content).ok().and_then(|value| {
+        let suggestions_sent = value.get("suggestions").is_some();
+        serde_json::from_value::<CommentsFile>(value)
+            .ok()
+            .map(|file| (file, suggestions_sent))
+    });
+    if let Some((mut incoming, suggestions_sent)) = parsed {
+        // The note is saved before its comments, so markers are current
+        let text = fs::read_to_string(md_path).ok();
+        let markdown = text.as_deref().map_or("", |t| comment_storage::split_embedded(t).0);
+        if let Ok(Some(stored)) = load_comments_file(store.as_ref(), md_path) {
+            merge_backend_state(
+                &mut incoming,
+                &stored,
+                suggestions_sent,
+                markdown,
+                workspace.as_deref().map(Path::new),
+            );
+        }
+        if text.is_some() {
+            anchoring::update_fingerprints(&mut incoming, markdown);
+        }
+        return save_comments_file(&window, store.as_ref(), md_path, &incoming);
+    }
+
+    watcher::mark_write(&window, &store.location(md_path).to_string_lossy());
+    store.write(md_path, &content)
+}
+
+/// Change a thread's workflow fields and return the updated thread.
+#[tauri::command]
+pub fn update_thread_workflow(
+    window: WebviewWindow,
+    path: String,
+    thread_id: String,
+    update: WorkflowUpdate,
+    author: Option<String>,
+    workspace: Option<String>,
+) -> Result<CommentThread, String> {
+    if let Some(due) = update.due_date.as_deref().filter(|d| !d.is_empty() && !is_iso_date(d)) {
+        return Err(format!("Invalid due date: {} (expected YYYY-MM-DD)", due));
+    }
+    let workspace = workspace_file::owning_root(&window, workspace, &path);
+    let store = comment_storage::store_for(workspace.as_deref());
+    let md_path = Path::new(&path);
+    let mut file = load_comments_file(store.as_ref(), md_path)?
+        .ok_or_else(|| "No comments for this file".to_string())?;
+    let thread = file
+        .comments
+        .get_mut(&thread_id)
+        .ok_or_else(|| format!("Thread not found: {}", thread_id))?;
+
+    if let Some(status) = update.status {
+        let by = author
+            .or_else(|| settings::configured_author(workspace.as_deref().map(Path::new)))
+            .unwrap_or_else(|| "Unknown".to_string());
+        thread.set_status(status, &by, &iso_now());
+    }
+    if let Some(assignees) = update.assignees {
+        thread.assignees = assignees;
+    }
+    if let Some(priority) = update.priority {
+        thread.priority = priority;
+    }
+    if let Some(labels) = update.labels {
+        thread.labels = labels;
+    }
+    if let Some(due) = update.due_date {
+        thread.due_date = if due.is_empty() { None } else { Some(due) };
+    }
+    thread.workflow_revision += 1;
+
+    let updated = thread.clone();
+    save_comments_file(&window, store.as_ref(), md_path, &file)?;
+    Ok(updated)
+}
+
+/// Find threads across the workspace matching workflow criteria.
 #[tauri::command]
-pub fn write_comments(app: AppHandle, path: String, content: String) -> Result<(), String> {
-    let comments_path = comments_json_path(&path);
-    watcher::mark_write(&app, &comments_path);
-    fs::write(&comments_path, &content)
-        .map_err(|e| format!("Failed to write comments: {}", e))
+pub fn query_comments(workspace: String, query: CommentQuery) -> Result<Vec<ThreadMatch>, String> {
+    let ws = Path::new(&workspace);
+    let store = comment_storage::store_for(Some(&workspace));
+    let files = match &query.path {
+        Some(p) if Path::new(p).is_file() => vec![Path::new(p).to_path_buf()],
+        Some(p) if Path::new(p).starts_with(ws) => workspace::collect_markdown_files_in(ws, Path::new(p)),
+        Some(p) => workspace::collect_markdown_files(Path::new(p)),
+        None => workspace::collect_markdown_files(ws),
+    };
+
+    let mut matches = Vec::new();
+    for md_path in files {
+        // A broken sidecar shouldn't hide every other file's threads
+        let Ok(Some(file)) = load_comments_file(store.as_ref(), &md_path) else {
+            continue;
+        };
+        for (id, thread) in file.comments {
+            if query.matches(&thread) {
+                matches.push(ThreadMatch {
+                    path: md_path.to_string_lossy().to_string(),
+                    thread_id: id,
+                    status: thread.effective_status(),
+                    thread,
+                });
+            }
+        }
+    }
+    Ok(matches)
 }
 
 #[tauri::command]
-pub fn delete_comments(path: String) -> Result<(), String> {
-    let comments_path = comments_json_path(&path);
-    if Path::new(&comments_path).exists() {
-        fs::remove_file(&comments_path)
-            .map_err(|e| format!("Failed to delete comments: {}", e))?;
+pub fn delete_comments(
+    window: WebviewWindow,
+    path: String,
+    workspace: Option<String>,
+) -> Result<(), String> {
+    let workspace = workspace_file::owning_root(&window, workspace, &path);
+    let store = comment_storage::store_for(workspace.as_deref());
+    let md_path = Path::new(&path);
+
+    // Pending suggestions outlive the last comment thread
+    if let Ok(Some(mut stored)) = load_comments_file(store.as_ref(), md_path) {
+        if !stored.suggestions.is_empty() {
+            stored.comments.clear();
+            return save_comments_file(&window, store.as_ref(), md_path, &stored);
+        }
     }
 
-    let companion_path = comments_md_path(&path);
-    if Path::new(&companion_path).exists() {
-        fs::remove_file(&companion_path)
-            .map_err(|e| format!("Failed to delete companion: {}", e))?;
+    store.delete(md_path)?;
+
+    if let Some(companion_path) = store.companion_location(Path::new(&path)) {
+        if companion_path.exists() {
+            fs::remove_file(&companion_path)
+                .map_err(|e| format!("Failed to delete companion: {}", e))?;
+        }
     }
 
     Ok(())
 }
 
 #[tauri::command]
-pub fn write_companion(app: AppHandle, path: String, content: String) -> Result<(), String> {
-    let companion_path = comments_md_path(&path);
-    watcher::mark_write(&app, &companion_path);
+pub fn write_companion(
+    window: WebviewWindow,
+    path: String,
+    content: String,
+    workspace: Option<String>,
+) -> Result<(), String> {
+    let workspace = workspace_file::owning_root(&window, workspace, &path);
+    let store = comment_storage::store_for(workspace.as_deref());
+    let Some(companion_path) = store.companion_location(Path::new(&path)) else {
+        return Ok(());
+    };
+    if let Some(parent) = companion_path.parent() {
+        fs::create_dir_all(parent)
+            .map_err(|e| format!("Failed to create companion directory: {}", e))?;
+    }
+    watcher::mark_write(&window, &companion_path.to_string_lossy());
     fs::write(&companion_path, &content)
         .map_err(|e| format!("Failed to write companion: {}", e))
 }
 
 #[tauri::command]
-pub fn delete_companion(path: String) -> Result<(), String> {
-    let companion_path = comments_md_path(&path);
-    if Path::new(&companion_path).exists() {
-        fs::remove_file(&companion_path)
-            .map_err(|e| format!("Failed to delete companion: {}", e))?;
+pub fn delete_companion(window: WebviewWindow, path: String, workspace: Option<String>) -> Result<(), String> {
+    let workspace = workspace_file::owning_root(&window, workspace, &path);
+    let store = comment_storage::store_for(workspace.as_deref());
+    if let Some(companion_path) = store.companion_location(Path::new(&path)) {
+        if companion_path.exists() {
+            fs::remove_file(&companion_path)
+                .map_err(|e| format!("Failed to delete companion: {}", e))?;
+        }
     }
     Ok(())
 }
 
-fn comments_json_path(md_path: &str) -> String {
-    let p = Path::new(md_path);
-    let stem = p.file_stem().unwrap_or_default().to_string_lossy();
-    let parent = p.parent().unwrap_or(Path::new("."));
-    parent.join(format!("{}.comments.json", stem)).to_string_lossy().to_string()
+#[tauri::command]
+pub fn get_comment_storage(workspace: String) -> StorageMode {
+    comment_storage::workspace_mode(Path::new(&workspace))
 }
 
-fn comments_md_path(md_path: &str) -> String {
-    let p = Path::new(md_path);
-    let stem = p.file_stem().unwrap_or_default().to_string_lossy();
-    let parent = p.parent().unwrap_or(Path::new("."));
-    parent.join(format!("{}.comments.md", stem)).to_string_lossy().to_string()
+/// Move every note's comments from the workspace's current storage mode to
+/// `mode`, then record `mode` in the workspace config.
+#[tauri::command]
+pub fn migrate_comment_storage(
+    window: WebviewWindow,
+    workspace: String,
+    mode: StorageMode,
+) -> Result<MigrationReport, String> {
+    let ws = Path::new(&workspace);
+    let current = comment_storage::workspace_mode(ws);
+    let mut report = MigrationReport {
+        mode,
+        migrated: 0,
+        failed: Vec::new(),
+    };
+    if current == mode {
+        return Ok(report);
+    }
+
+    let from = comment_storage::store_for_mode(current, Some(ws));
+    let to = comment_storage::store_for_mode(mode, Some(ws));
+
+    for md_path in workspace::collect_markdown_files(ws) {
+        let content = match from.read(&md_path) {
+            Ok(Some(c)) => c,
+            Ok(None) => continue,
+            Err(e) => {
+                report.failed.push(format!("{}: {}", md_path.display(), e));
+                continue;
+            }
+        };
+
+        watcher::mark_write(&window, &from.location(&md_path).to_string_lossy());
+        watcher::mark_write(&window, &to.location(&md_path).to_string_lossy());
+        // Write the new copy before removing the old one so a failure never loses threads
+        if let Err(e) = to.write(&md_path, &content).and_then(|_| from.delete(&md_path)) {
+            report.failed.push(format!("{}: {}", md_path.display(), e));
+            continue;
+        }
+
+        if let Err(e) = move_companion(from.as_ref(), to.as_ref(), &md_path) {
+            report.failed.push(format!("{}: {}", md_path.display(), e));
+            continue;
+        }
+
+        report.migrated += 1;
+    }
+
+    comment_storage::set_workspace_mode(ws, mode)?;
+    Ok(report)
+}
+
+/// The companion is regenerated on the next save; just move what exists, or
+/// drop it when the new mode keeps none.
+fn move_companion(from: &dyn CommentStore, to: &dyn CommentStore, md_path: &Path) -> Result<(), String> {
+    let Some(old_companion) = from.companion_location(md_path).filter(|p| p.exists()) else {
+        return Ok(());
+    };
+    match to.companion_location(md_path) {
+        Some(new_companion) => {
+            if let Some(parent) = new_companion.parent() {
+                fs::create_dir_all(parent)
+                    .map_err(|e| format!("Failed to create companion directory: {}", e))?;
+            }
+            fs::rename(&old_companion, &new_companion)
+                .map_err(|e| format!("Failed to move companion: {}", e))
+        }
+        None => fs::remove_file(&old_companion).map_err(|e| format!("Failed to delete companion: {}", e)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn suggestion(deleted: &str) -> Suggestion {
+        Suggestion {
+            kind: SuggestionKind::Delete,
+            author: "Ann".to_string(),
+            timestamp: "2024-05-01T09:00:00Z".to_string(),
+            deleted_text: Some(deleted.to_string()),
+            inserted_text: None,
+            extra: serde_json::Map::new(),
+        }
+    }
+
+    fn with_suggestions(ids: &[&str]) -> CommentsFile {
+        let mut file = CommentsFile::default();
+        for id in ids {
+            file.suggestions.insert(id.to_string(), suggestion("very"));
+        }
+        file
+    }
+
+    #[test]
+    fn deleting_the_last_suggestion_sticks() {
+        let stored = with_suggestions(&["s1"]);
+        let mut incoming = with_suggestions(&[]);
+        merge_backend_state(&mut incoming, &stored, true, "A plain note.\n", None);
+        assert!(incoming.suggestions.is_empty());
+    }
+
+    #[test]
+    fn suggestions_are_kept_when_not_sent() {
+        let stored = with_suggestions(&["s1"]);
+        let mut incoming = with_suggestions(&[]);
+        merge_backend_state(&mut incoming, &stored, false, "A plain note.\n", None);
+        assert!(incoming.suggestions.contains_key("s1"));
+    }
+
+    #[test]
+    fn suggestions_are_merged_per_id() {
+        let stored = with_suggestions(&["s1", "s2", "s3"]);
+        let mut incoming = with_suggestions(&["s1"]);
+        incoming.suggestions.get_mut("s1").unwrap().deleted_text = None;
+        // s2 was resolved in the editor; s3 was added after it loaded
+        let markdown = "Keep <del>very</del><sup>[s1]</sup> and <del>this</del><sup>[s3]</sup>.\n";
+        merge_backend_state(&mut incoming, &stored, true, markdown, None);
+        let ids: Vec<&str> = incoming.suggestions.keys().map(String::as_str).collect();
+        assert_eq!(ids, ["s1", "s3"]);
+        assert_eq!(incoming.suggestions["s1"].deleted_text.as_deref(), Some("very"));
+    }
+
+    #[test]
+    fn stale_save_keeps_workflow_fields() {
+        let mut thread = CommentThread::new("2024-05-01T09:00:00Z".to_string());
+        let mut stored = CommentsFile::default();
+        let mut incoming = CommentsFile::default();
+        incoming.comments.insert("c1".to_string(), thread.clone());
+
+        thread.assignees = vec!["bob".to_string()];
+        thread.priority = Some(Priority::High);
+        thread.set_status(ThreadStatus::InProgress, "Ann", "2024-05-02T09:00:00Z");
+        thread.workflow_revision = 1;
+        stored.comments.insert("c1".to_string(), thread);
+
+        merge_backend_state(&mut incoming, &stored, true, "", None);
+        let merged = &incoming.comments["c1"];
+        assert_eq!(merged.assignees, ["bob"]);
+        assert_eq!(merged.priority, Some(Priority::High));
+        assert_eq!(merged.effective_status(), ThreadStatus::InProgress);
+        assert_eq!(merged.status_history.len(), 1);
+    }
+
+    #[test]
+    fn due_dates_must_be_iso_dates() {
+        for date in ["2024-05-01", "2024-02-29", "2000-02-29", "1999-12-31"] {
+            assert!(is_iso_date(date), "{}", date);
+        }
+        for date in [
+            "2024-5-1", "2023-02-29", "1900-02-29", "2024-13-01", "2024-04-31", "2024-00-10",
+            "tomorrow", "2024-05-01T09:00", "+024-05-01",
+        ] {
+            assert!(!is_iso_date(date), "{}", date);
+        }
+    }
+
+    #[test]
+    fn editor_resolve_survives_a_workflow_update() {
+        let mut thread = CommentThread::new("2024-05-01T09:00:00Z".to_string());
+        let mut stored = CommentsFile::default();
+        let mut incoming = CommentsFile::default();
+
+        // The editor resolved the thread it loaded at revision 0...
+        let mut edited = thread.clone();
+        edited.resolved = true;
+        edited.resolved_by = Some("Ann".to_string());
+        edited.resolved_at = Some("2024-05-03T09:00:00Z".to_string());
+        incoming.comments.insert("c1".to_string(), edited);
+
+        // ...while an assignee was added in the backend
+        thread.assignees = vec!["bob".to_string()];
+        thread.workflow_revision = 1;
+        stored.comments.insert("c1".to_string(), thread);
+
+        merge_backend_state(&mut incoming, &stored, true, "", None);
+        let merged = &incoming.comments["c1"];
+        assert_eq!(merged.assignees, ["bob"]);
+        assert_eq!(merged.workflow_revision, 1);
+        assert!(merged.resolved);
+        assert_eq!(merged.resolved_by.as_deref(), Some("Ann"));
+        assert_eq!(merged.effective_status(), ThreadStatus::Resolved);
+        let change = merged.status_history.last().unwrap();
+        assert_eq!((change.from, change.to), (ThreadStatus::Open, ThreadStatus::Resolved));
+        assert_eq!(change.by, "Ann");
+    }
 }
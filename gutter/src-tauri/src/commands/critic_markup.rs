@@ -0,0 +1,769 @@
+use serde::Serialize;
+use std::collections::HashSet;
+use std::fs;
+use std::path::Path;
+use tauri::WebviewWindow;
+use super::comment_storage;
+use super::comments::{self, CommentMessage, CommentThread, CommentsFile, Suggestion, SuggestionKind};
+use super::suggestions;
+use super::{settings, watcher, workspace_file};
+
+// Thread metadata rides along inside the comments so a document survives a
+// round-trip: the first comment of a thread starts with its id (`[c3] `),
+// every message is written as `Author (timestamp): body`, and a resolved
+// thread ends with a `✓ Resolved by Author (timestamp)` comment. Suggestions
+// are followed by a `[sN] Author (timestamp)` comment the same way.
+const RESOLVED_PREFIX: &str = "✓ Resolved by ";
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CriticImportReport {
+    pub threads: usize,
+    pub messages: usize,
+    pub suggestions: usize,
+    /// CriticMarkup constructs that have no Gutter equivalent and were kept verbatim
+    pub unmapped: usize,
+}
+
+pub struct CriticImport {
+    pub markdown: String,
+    pub comments: CommentsFile,
+    pub report: CriticImportReport,
+}
+
+impl CriticImport {
+    /// Whether the markdown carries markers the comments file must back:
+    /// threads, or suggestions on their own
+    fn has_records(&self) -> bool {
+        !self.comments.comments.is_empty() || !self.comments.suggestions.is_empty()
+    }
+}
+
+struct ParsedMessage {
+    author: Option<String>,
+    timestamp: Option<String>,
+    body: String,
+}
+
+#[derive(Default)]
+struct ParsedThread {
+    id: Option<String>,
+    anchored: bool,
+    messages: Vec<ParsedMessage>,
+    resolved: Option<(String, Option<String>)>,
+}
+
+/// Keep a body from closing its comment early, and from breaking the
+/// paragraph (CriticMarkup doesn't span blank lines). Reversed exactly by
+/// `unescape_body`: backslashes are doubled, `<<}` becomes `<<\}` and a
+/// blank line gets a lone `\`.
+fn escape_body(body: &str) -> String {
+    body.replace('\\', "\\\\")
+        .replace("<<}", "<<\\}")
+        .split('\n')
+        .map(|line| {
+            if line.trim().is_empty() {
+                format!("\\{}", line)
+            } else {
+                line.to_string()
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+fn unescape_body(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            out.push(c);
+            continue;
+        }
+        match chars.peek() {
+            Some('\\') | Some('}') => out.extend(chars.next()),
+            // The marker `escape_body` puts on a blank line
+            Some(next) if next.is_whitespace() => {}
+            None => {}
+            Some(_) => out.push(c),
+        }
+    }
+    out
+}
+
+fn thread_comments(id: &str, thread: &CommentThread) -> String {
+    let mut out = String::new();
+    for (i, msg) in thread.thread.iter().enumerate() {
+        let prefix = if i == 0 { format!("[{}] ", id) } else { String::new() };
+        out.push_str(&format!(
+            "{{>>{}{} ({}): {}<<}}",
+            prefix,
+            msg.author,
+            msg.timestamp,
+            escape_body(&msg.body)
+        ));
+    }
+    if thread.thread.is_empty() {
+        out.push_str(&format!("{{>>[{}] <<}}", id));
+    }
+    if thread.resolved {
+        let by = thread.resolved_by.as_deref().unwrap_or("Unknown");
+        match &thread.resolved_at {
+            Some(at) => out.push_str(&format!("{{>>{}{} ({})<<}}", RESOLVED_PREFIX, by, at)),
+            None => out.push_str(&format!("{{>>{}{}<<}}", RESOLVED_PREFIX, by)),
+        }
+    }
+    out
+}
+
+/// Rewrite suggestion markers as CriticMarkup changes, each followed by a
+/// `[sN] Author (timestamp)` comment.
+fn suggestions_to_critic(markdown: &str, file: &CommentsFile) -> String {
+    let mut out = String::with_capacity(markdown.len());
+    let mut pos = 0;
+    for marker in suggestions::extract_suggestions(markdown) {
+        out.push_str(&markdown[pos..marker.start]);
+        match (&marker.deleted, &marker.inserted) {
+            (Some(d), Some(i)) => out.push_str(&format!("{{~~{}~>{}~~}}", d, i)),
+            (Some(d), None) => out.push_str(&format!("{{--{}--}}", d)),
+            (None, i) => out.push_str(&format!("{{++{}++}}", i.as_deref().unwrap_or(""))),
+        }
+        match file.suggestions.get(&marker.id) {
+            Some(s) => out.push_str(&format!(
+                "{{>>[{}] {} ({})<<}}",
+                marker.id, s.author, s.timestamp
+            )),
+            None => out.push_str(&format!("{{>>[{}]<<}}", marker.id)),
+        }
+        pos = marker.end;
+    }
+    out.push_str(&markdown[pos..]);
+    out
+}
+
+/// Rewrite Gutter comment markers as CriticMarkup highlights and comments and
+/// suggestion markers as CriticMarkup changes. Threads without a marker are
+/// appended as standalone comments at the end.
+pub fn to_critic_markup(markdown: &str, file: &CommentsFile) -> String {
+    let converted = suggestions_to_critic(markdown, file);
+    let markdown = converted.as_str();
+    let mut out = String::with_capacity(markdown.len());
+    let mut pos = 0;
+    let mut anchored: HashSet<&str> = HashSet::new();
+
+    for anchor in comments::extract_anchors(markdown) {
+        out.push_str(&markdown[pos..anchor.start]);
+        match file.comments.get(&anchor.id) {
+            Some(thread) if !anchored.contains(anchor.id.as_str()) => {
+                out.push_str(&format!("{{=={}==}}", anchor.text));
+                out.push_str(&thread_comments(&anchor.id, thread));
+                if let Some((id, _)) = file.comments.get_key_value(&anchor.id) {
+                    anchored.insert(id.as_str());
+                }
+            }
+            // A marker without a thread is just text
+            _ => out.push_str(&anchor.text),
+        }
+        pos = anchor.end;
+    }
+    out.push_str(&markdown[pos..]);
+
+    let orphans: Vec<String> = file
+        .comments
+        .iter()
+        .filter(|(id, _)| !anchored.contains(id.as_str()))
+        .map(|(id, thread)| thread_comments(id, thread))
+        .collect();
+    if !orphans.is_empty() {
+        let trimmed = out.trim_end_matches('\n').len();
+        out.truncate(trimmed);
+        out.push_str("\n\n");
+        out.push_str(&orphans.join("\n\n"));
+        out.push('\n');
+    }
+    out
+}
+
+fn looks_like_timestamp(s: &str) -> bool {
+    let b = s.as_bytes();
+    b.len() >= 10 && b[..4].iter().all(u8::is_ascii_digit) && b[4] == b'-' && b[7] == b'-'
+}
+
+/// Split `Author (timestamp)` into its parts, if it has that shape.
+fn split_author(s: &str) -> Option<(String, Option<String>)> {
+    if s.contains('\n') {
+        return None;
+    }
+    match s.strip_suffix(')').and_then(|rest| rest.rsplit_once(" (")) {
+        Some((author, ts)) if looks_like_timestamp(ts) && !author.trim().is_empty() => {
+            Some((author.trim().to_string(), Some(ts.to_string())))
+        }
+        _ => None,
+    }
+}
+
+fn parse_message(raw: &str) -> (Option<String>, ParsedMessage) {
+    // Only the start is trimmed: a body we wrote runs right up to `<<}`
+    let mut text = raw.trim_start();
+    let mut id = None;
+    if let Some(rest) = text.strip_prefix("[c") {
+        if let Some((num, rest)) = rest.split_once(']') {
+            if !num.is_empty() && num.bytes().all(|b| b.is_ascii_digit()) {
+                id = Some(format!("c{}", num));
+                text = rest.trim_start();
+            }
+        }
+    }
+    let plain = || ParsedMessage { author: None, timestamp: None, body: text.trim_end().to_string() };
+    let message = match text.split_once("): ") {
+        Some((head, body)) => match split_author(&format!("{})", head)) {
+            Some((author, timestamp)) => ParsedMessage {
+                author: Some(author),
+                timestamp,
+                body: unescape_body(body),
+            },
+            None => plain(),
+        },
+        None => plain(),
+    };
+    (id, message)
+}
+
+/// Byte length of the construct at the start of `s` delimited by `open` and
+/// `close`, or None if it never closes.
+fn delimited(s: &str, open: &str, close: &str) -> Option<usize> {
+    let inner = s.strip_prefix(open)?;
+    inner.find(close).map(|i| open.len() + i + close.len())
+}
+
+/// Read a run of adjacent `{>>…<<}` comments starting at `s` into `thread`,
+/// returning the number of bytes consumed.
+fn take_comments(s: &str, thread: &mut ParsedThread) -> usize {
+    let mut consumed = 0;
+    while let Some(len) = delimited(&s[consumed..], "{>>", "<<}") {
+        let raw = &s[consumed + 3..consumed + len - 3];
+        consumed += len;
+        if let Some(rest) = raw.trim().strip_prefix(RESOLVED_PREFIX) {
+            thread.resolved = Some(
+                split_author(rest).unwrap_or_else(|| (rest.trim().to_string(), None)),
+            );
+            continue;
+        }
+        let (id, message) = parse_message(raw);
+        if thread.id.is_none() {
+            thread.id = id;
+        }
+        if !message.body.is_empty() || message.author.is_some() {
+            thread.messages.push(message);
+        }
+    }
+    consumed
+}
+
+/// Placeholders for markers whose ids are assigned once everything is parsed.
+fn thread_placeholder(index: usize) -> String {
+    format!("\u{0}{}\u{0}", index)
+}
+
+fn suggestion_placeholder(index: usize) -> String {
+    format!("\u{1}{}\u{1}", index)
+}
+
+struct ParsedSuggestion {
+    id: Option<String>,
+    deleted: Option<String>,
+    inserted: Option<String>,
+    author: Option<String>,
+    timestamp: Option<String>,
+}
+
+/// `[sN] Author (timestamp)` metadata comment written after a suggestion.
+/// Fills in the suggestion's id, author and timestamp when it parses.
+fn parse_suggestion_meta(raw: &str, suggestion: &mut ParsedSuggestion) -> bool {
+    let Some(rest) = raw.trim().strip_prefix("[s") else {
+        return false;
+    };
+    let Some((num, rest)) = rest.split_once(']') else {
+        return false;
+    };
+    if num.is_empty() || !num.bytes().all(|b| b.is_ascii_digit()) {
+        return false;
+    }
+    suggestion.id = Some(format!("s{}", num));
+    let rest = rest.trim();
+    if !rest.is_empty() {
+        let (author, timestamp) = split_author(rest).unwrap_or_else(|| (rest.to_string(), None));
+        suggestion.author = Some(author);
+        suggestion.timestamp = timestamp;
+    }
+    true
+}
+
+#[derive(Default)]
+struct Importer {
+    out: String,
+    threads: Vec<ParsedThread>,
+    suggestions: Vec<ParsedSuggestion>,
+    unmapped: usize,
+}
+
+impl Importer {
+    fn convert_inline(&mut self, text: &str) {
+        let out = &mut self.out;
+        let mut pos = 0;
+        while pos < text.len() {
+            let rest = &text[pos..];
+
+            // Inline code is copied verbatim
+            if rest.starts_with('`') {
+                let ticks = rest.bytes().take_while(|&b| b == b'`').count();
+                let fence = &rest[..ticks];
+                let len = rest[ticks..]
+                    .find(fence)
+                    .map(|i| ticks + i + ticks)
+                    .unwrap_or(ticks);
+                out.push_str(&rest[..len]);
+                pos += len;
+                continue;
+            }
+
+            if let Some(len) = delimited(rest, "{==", "==}") {
+                let highlighted = &rest[3..len - 3];
+                let mut thread = ParsedThread { anchored: true, ..Default::default() };
+                let consumed = take_comments(&rest[len..], &mut thread);
+                if consumed > 0 {
+                    out.push_str(&format!("<mark>{}</mark>", highlighted));
+                    out.push_str(&thread_placeholder(self.threads.len()));
+                    self.threads.push(thread);
+                } else {
+                    // A bare highlight has no Gutter equivalent
+                    out.push_str(highlighted);
+                    self.unmapped += 1;
+                }
+                pos += len + consumed;
+                continue;
+            }
+
+            if rest.starts_with("{>>") {
+                let mut thread = ParsedThread::default();
+                let consumed = take_comments(rest, &mut thread);
+                if consumed > 0 {
+                    // A comment with no highlight has no anchor: it becomes an
+                    // unanchored thread, and the gap it leaves is closed up
+                    let after = &rest[consumed..];
+                    let skip = match out.chars().next_back() {
+                        None | Some('\n') if after.starts_with('\n') => 1,
+                        Some(' ') if after.starts_with(' ') => 1,
+                        _ => 0,
+                    };
+                    self.threads.push(thread);
+                    pos += consumed + skip;
+                    continue;
+                }
+            }
+
+            let change = if let Some(len) = delimited(rest, "{++", "++}") {
+                Some((len, None, Some(&rest[3..len - 3])))
+            } else if let Some(len) = delimited(rest, "{--", "--}") {
+                Some((len, Some(&rest[3..len - 3]), None))
+            } else if let Some(len) = delimited(rest, "{~~", "~~}") {
+                match rest[3..len - 3].split_once("~>") {
+                    Some((old, new)) => Some((len, Some(old), Some(new))),
+                    None => Some((len, Some(&rest[3..len - 3]), None)),
+                }
+            } else {
+                None
+            };
+            if let Some((len, deleted, inserted)) = change {
+                let mut consumed = len;
+                let mut suggestion = ParsedSuggestion {
+                    id: None,
+                    deleted: deleted.map(str::to_string),
+                    inserted: inserted.map(str::to_string),
+                    author: None,
+                    timestamp: None,
+                };
+                if let Some(meta_len) = delimited(&rest[len..], "{>>", "<<}") {
+                    let raw = &rest[len + 3..len + meta_len - 3];
+                    if parse_suggestion_meta(raw, &mut suggestion) {
+                        consumed += meta_len;
+                    }
+                }
+                if let Some(d) = &suggestion.deleted {
+                    out.push_str(&format!("<del>{}</del>", d));
+                }
+                if let Some(i) = &suggestion.inserted {
+                    out.push_str(&format!("<ins>{}</ins>", i));
+                }
+                out.push_str(&suggestion_placeholder(self.suggestions.len()));
+                self.suggestions.push(suggestion);
+                pos += consumed;
+                continue;
+            }
+
+            let ch = rest.chars().next().unwrap_or_default();
+            out.push(ch);
+            pos += ch.len_utf8();
+        }
+    }
+}
+
+fn is_fence(line: &str) -> bool {
+    let t = line.trim_start();
+    t.starts_with("```") || t.starts_with("~~~")
+}
+
+/// Drop duplicate ids carried in the text and return the next free number
+/// for `prefix` after everything already in use.
+fn claim_ids<'a, 'b>(
+    ids: impl Iterator<Item = &'a mut Option<String>>,
+    in_use: impl Iterator<Item = &'b String>,
+    prefix: char,
+) -> u64 {
+    let mut claimed: HashSet<String> = HashSet::new();
+    for id in ids {
+        if let Some(value) = id {
+            if !claimed.insert(value.clone()) {
+                *id = None;
+            }
+        }
+    }
+    let number = |id: &str| id.strip_prefix(prefix)?.parse::<u64>().ok();
+    let highest_in_use = in_use.filter_map(|id| number(id)).max();
+    let highest_claimed = claimed.iter().filter_map(|id| number(id)).max();
+    highest_in_use.max(highest_claimed).unwrap_or(0) + 1
+}
+
+/// Convert CriticMarkup back to Gutter markers, threads and suggestions.
+/// Threads that already exist in `existing` (matched by id) keep their state
+/// and only gain messages they don't have yet; threads missing from the text
+/// are kept.
+pub fn from_critic_markup(
+    text: &str,
+    existing: Option<&CommentsFile>,
+    default_author: &str,
+) -> CriticImport {
+    let mut importer = Importer {
+        out: String::with_capacity(text.len()),
+        ..Default::default()
+    };
+
+    // Convert paragraphs of prose; copy fenced code blocks untouched
+    let mut chunk = String::new();
+    let mut in_fence = false;
+    for line in text.split_inclusive('\n') {
+        if is_fence(line) {
+            if !in_fence {
+                importer.convert_inline(&chunk);
+                chunk.clear();
+            }
+            in_fence = !in_fence;
+            importer.out.push_str(line);
+        } else if in_fence {
+            importer.out.push_str(line);
+        } else {
+            chunk.push_str(line);
+        }
+    }
+    importer.convert_inline(&chunk);
+
+    let Importer {
+        out: mut markdown,
+        mut threads,
+        mut suggestions,
+        unmapped,
+    } = importer;
+    let mut file = existing.cloned().unwrap_or_default();
+    let now = comments::iso_now();
+
+    // Keep ids carried in the text; number the rest after the highest in use
+    let mut next = claim_ids(threads.iter_mut().map(|t| &mut t.id), file.comments.keys(), 'c');
+    let mut message_count = 0;
+    let mut ids = Vec::with_capacity(threads.len());
+    for t in threads.iter_mut() {
+        let id = t.id.clone().unwrap_or_else(|| {
+            let id = format!("c{}", next);
+            next += 1;
+            id
+        });
+        let thread = file.comments.entry(id.clone()).or_insert_with(|| {
+            CommentThread::new(
+                t.messages
+                    .first()
+                    .and_then(|m| m.timestamp.clone())
+                    .unwrap_or_else(|| now.clone()),
+            )
+        });
+        for m in t.messages.drain(..) {
+            let author = m.author.unwrap_or_else(|| default_author.to_string());
+            let body = m.body;
+            if thread
+                .thread
+                .iter()
+                .any(|e| e.author == author && e.body.trim() == body.trim())
+            {
+                continue;
+            }
+            thread.thread.push(CommentMessage {
+                id: comments::new_message_id(),
+                author,
+                timestamp: m.timestamp.unwrap_or_else(|| now.clone()),
+                body,
+            });
+            message_count += 1;
+        }
+        if let Some((by, at)) = t.resolved.take() {
+            if !thread.resolved {
+                thread.resolved = true;
+                thread.resolved_by = Some(by);
+                thread.resolved_at = Some(at.unwrap_or_else(|| now.clone()));
+            }
+        }
+        ids.push(id);
+    }
+    for (i, id) in ids.iter().enumerate() {
+        if threads[i].anchored {
+            markdown =
+                markdown.replacen(&thread_placeholder(i), &format!("<sup>[{}]</sup>", id), 1);
+        }
+    }
+
+    let mut next = claim_ids(
+        suggestions.iter_mut().map(|s| &mut s.id),
+        file.suggestions.keys(),
+        's',
+    );
+    for (i, s) in suggestions.iter_mut().enumerate() {
+        let id = s.id.clone().unwrap_or_else(|| {
+            let id = format!("s{}", next);
+            next += 1;
+            id
+        });
+        let kind = match (&s.deleted, &s.inserted) {
+            (Some(_), Some(_)) => SuggestionKind::Replace,
+            (Some(_), None) => SuggestionKind::Delete,
+            _ => SuggestionKind::Insert,
+        };
+        let entry = file.suggestions.entry(id.clone()).or_insert_with(|| Suggestion {
+            kind,
+            author: s.author.clone().unwrap_or_else(|| default_author.to_string()),
+            timestamp: s.timestamp.clone().unwrap_or_else(|| now.clone()),
+            deleted_text: None,
+            inserted_text: None,
+            extra: serde_json::Map::new(),
+        });
+        // The text is authoritative for what the change is
+        entry.kind = kind;
+        entry.deleted_text = s.deleted.take();
+        entry.inserted_text = s.inserted.take();
+        markdown =
+            markdown.replacen(&suggestion_placeholder(i), &format!("<sup>[{}]</sup>", id), 1);
+    }
+
+    // Unanchored threads exported at the end leave trailing blank lines
+    if threads.iter().any(|t| !t.anchored) && markdown.ends_with("\n\n") {
+        let trimmed = markdown.trim_end_matches('\n').len();
+        markdown.truncate(trimmed);
+        markdown.push('\n');
+    }
+
+    CriticImport {
+        markdown,
+        comments: file,
+        report: CriticImportReport {
+            threads: ids.len(),
+            messages: message_count,
+            suggestions: suggestions.len(),
+            unmapped,
+        },
+    }
+}
+
+/// Render a note and its threads as CriticMarkup. Writes to `output` when
+/// given and always returns the converted text.
+#[tauri::command]
+pub fn export_critic_markup(
+    window: WebviewWindow,
+    path: String,
+    output: Option<String>,
+    workspace: Option<String>,
+) -> Result<String, String> {
+    let workspace = workspace_file::owning_root(&window, workspace, &path);
+    let markdown = fs::read_to_string(&path).map_err(|e| format!("Failed to read file: {}", e))?;
+    let markdown = comment_storage::split_embedded(&markdown).0;
+    let store = comment_storage::store_for(workspace.as_deref());
+    let file = comments::load_comments_file(store.as_ref(), Path::new(&path))?.unwrap_or_default();
+    let text = to_critic_markup(markdown, &file);
+    if let Some(out) = output {
+        fs::write(&out, &text).map_err(|e| format!("Failed to write CriticMarkup: {}", e))?;
+    }
+    Ok(text)
+}
+
+/// Convert CriticMarkup in `source` (default: the note itself) into Gutter
+/// markers in `path`, merging its threads into the note's comments.
+#[tauri::command]
+pub fn import_critic_markup(
+    window: WebviewWindow,
+    path: String,
+    source: Option<String>,
+    author: Option<String>,
+    workspace: Option<String>,
+) -> Result<CriticImportReport, String> {
+    let workspace = workspace_file::owning_root(&window, workspace, &path);
+    let source = source.unwrap_or_else(|| path.clone());
+    let text = fs::read_to_string(&source).map_err(|e| format!("Failed to read file: {}", e))?;
+    let text = comment_storage::split_embedded(&text).0;
+
+    let store = comment_storage::store_for(workspace.as_deref());
+    let md_path = Path::new(&path);
+    let existing = comments::load_comments_file(store.as_ref(), md_path)?;
+    let author = author
+        .or_else(|| settings::configured_author(workspace.as_deref().map(Path::new)))
+        .unwrap_or_else(|| "Unknown".to_string());
+    let import = from_critic_markup(text, existing.as_ref(), &author);
+
+    // Markdown first: the embedded store appends to whatever is on disk
+    watcher::mark_write(&window, &path);
+    fs::write(md_path, &import.markdown).map_err(|e| format!("Failed to write file: {}", e))?;
+    if import.has_records() {
+        comments::save_comments_file(&window, store.as_ref(), md_path, &import.comments)?;
+    }
+    Ok(import.report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::comment_storage::{CommentStore, SidecarStore};
+
+    fn message(author: &str, timestamp: &str, body: &str) -> CommentMessage {
+        CommentMessage {
+            id: comments::new_message_id(),
+            author: author.to_string(),
+            timestamp: timestamp.to_string(),
+            body: body.to_string(),
+        }
+    }
+
+    fn thread(messages: Vec<CommentMessage>) -> CommentThread {
+        let mut thread = CommentThread::new("2024-05-01T09:00:00Z".to_string());
+        thread.thread = messages;
+        thread
+    }
+
+    #[test]
+    fn escaping_is_reversible() {
+        for body in [
+            "plain",
+            "two\n\nparagraphs",
+            "closes <<} early",
+            "a \\ backslash and \\} and \\\\",
+            "trailing newline\n",
+            "\nleading newline",
+            "blank with spaces\n   \nafter",
+            "ends with <",
+            "",
+        ] {
+            let escaped = escape_body(body);
+            assert!(!escaped.contains("<<}"), "{:?}", escaped);
+            assert!(!escaped.contains("\n\n"), "{:?}", escaped);
+            assert_eq!(unescape_body(&escaped), body);
+        }
+    }
+
+    #[test]
+    fn export_then_import_round_trips() {
+        let markdown = "Intro <mark>key claim</mark><sup>[c1]</sup> and \
+                        <del>old</del><ins>new</ins><sup>[s1]</sup> text.\n";
+        let mut file = CommentsFile::default();
+        let mut first = thread(vec![
+            message("Ann", "2024-05-01T09:00:00Z", "Source?\n\nSee <<} and C:\\notes"),
+            message("Bob", "2024-05-01T10:00:00Z", "Added one.  "),
+        ]);
+        first.resolved = true;
+        first.resolved_by = Some("Ann".to_string());
+        first.resolved_at = Some("2024-05-02T08:00:00Z".to_string());
+        file.comments.insert("c1".to_string(), first);
+        file.comments.insert(
+            "c2".to_string(),
+            thread(vec![message("Cy", "2024-05-03T09:00:00Z", "Lost its marker")]),
+        );
+        file.suggestions.insert(
+            "s1".to_string(),
+            Suggestion {
+                kind: SuggestionKind::Replace,
+                author: "Dee".to_string(),
+                timestamp: "2024-05-04T09:00:00Z".to_string(),
+                deleted_text: Some("old".to_string()),
+                inserted_text: Some("new".to_string()),
+                extra: serde_json::Map::new(),
+            },
+        );
+
+        let critic = to_critic_markup(markdown, &file);
+        let import = from_critic_markup(&critic, None, "Nobody");
+
+        assert_eq!(import.markdown, markdown);
+        assert_eq!(import.report.unmapped, 0);
+        for (id, original) in &file.comments {
+            let imported = &import.comments.comments[id];
+            let pairs: Vec<_> = imported.thread.iter().zip(&original.thread).collect();
+            assert_eq!(pairs.len(), original.thread.len());
+            for (got, want) in pairs {
+                assert_eq!(got.author, want.author);
+                assert_eq!(got.timestamp, want.timestamp);
+                assert_eq!(got.body, want.body);
+            }
+            assert_eq!(imported.resolved, original.resolved);
+            assert_eq!(imported.resolved_by, original.resolved_by);
+            assert_eq!(imported.resolved_at, original.resolved_at);
+        }
+        let suggestion = &import.comments.suggestions["s1"];
+        assert_eq!(suggestion.author, "Dee");
+        assert_eq!(suggestion.timestamp, "2024-05-04T09:00:00Z");
+        assert_eq!(suggestion.kind, SuggestionKind::Replace);
+    }
+
+    #[test]
+    fn suggestions_alone_are_saved() {
+        let text = "Keep {++added++} and {--gone--} and {~~old~>new~~}.\n";
+        let import = from_critic_markup(text, None, "Ann");
+        assert!(import.comments.comments.is_empty());
+        assert!(import.has_records());
+
+        let dir = std::env::temp_dir().join(format!("gutter-critic-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let md_path = dir.join("note.md");
+        fs::write(&md_path, &import.markdown).unwrap();
+        let store = SidecarStore;
+        let json = serde_json::to_string_pretty(&import.comments).unwrap();
+        store.write(&md_path, &json).unwrap();
+
+        let saved = comments::load_comments_file(&store, &md_path).unwrap().unwrap();
+        let markers = suggestions::extract_suggestions(&import.markdown);
+        assert_eq!(saved.suggestions.len(), 3);
+        assert_eq!(markers.len(), 3);
+        for marker in &markers {
+            assert_eq!(saved.suggestions[&marker.id].author, "Ann");
+        }
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn standalone_comment_is_not_anchored() {
+        let import = from_critic_markup("Some text {>>a note<<} here.\n", None, "Ann");
+        assert_eq!(import.markdown, "Some text here.\n");
+        let thread = &import.comments.comments["c1"];
+        assert_eq!(thread.thread[0].author, "Ann");
+        assert_eq!(thread.thread[0].body, "a note");
+    }
+
+    #[test]
+    fn foreign_comments_keep_backslashes() {
+        let text = "A {==phrase==}{>>see C:\\docs\\x<<} here.\n";
+        let import = from_critic_markup(text, None, "Ann");
+        assert_eq!(import.markdown, "A <mark>phrase</mark><sup>[c1]</sup> here.\n");
+        assert_eq!(import.comments.comments["c1"].thread[0].body, "see C:\\docs\\x");
+    }
+}
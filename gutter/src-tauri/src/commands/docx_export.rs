@@ -0,0 +1,1186 @@
+use pulldown_cmark::{Event, HeadingLevel, Options, Parser, Tag, TagEnd};
+use serde::Serialize;
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs::{self, File};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use tauri::WebviewWindow;
+use zip::write::SimpleFileOptions;
+use super::comment_storage;
+use super::comments::{self, CommentThread, CommentsFile, Suggestion};
+use super::{suggestions, workspace_file};
+
+// Markers are swapped for private-use sentinels before parsing so they
+// survive as plain text inside whatever inline formatting surrounds them.
+// Each sentinel is followed by the marker number and `ID_END`.
+const COMMENT_START: char = '\u{E000}';
+const COMMENT_END: char = '\u{E001}';
+const DEL_START: char = '\u{E002}';
+const DEL_END: char = '\u{E003}';
+const INS_START: char = '\u{E004}';
+const INS_END: char = '\u{E005}';
+const ID_END: char = '\u{E00F}';
+
+const NS_W: &str = "http://schemas.openxmlformats.org/wordprocessingml/2006/main";
+const NS_R: &str = "http://schemas.openxmlformats.org/officeDocument/2006/relationships";
+const NS_W14: &str = "http://schemas.microsoft.com/office/word/2010/wordml";
+const NS_W15: &str = "http://schemas.microsoft.com/office/word/2012/wordml";
+const NS_MC: &str = "http://schemas.openxmlformats.org/markup-compatibility/2006";
+const NS_WP: &str = "http://schemas.openxmlformats.org/drawingml/2006/wordprocessingDrawing";
+const NS_A: &str = "http://schemas.openxmlformats.org/drawingml/2006/main";
+const NS_PIC: &str = "http://schemas.openxmlformats.org/drawingml/2006/picture";
+const REL_BASE: &str = "http://schemas.openxmlformats.org/officeDocument/2006/relationships";
+const BULLET_NUM_ID: u32 = 1;
+/// EMUs per pixel at 96 dpi
+const EMU_PER_PX: u64 = 9525;
+/// Text width of a Letter page with 1in margins
+const MAX_IMAGE_WIDTH: u64 = 6 * 914_400;
+
+#[derive(Serialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct DocxExportReport {
+    pub comments: usize,
+    pub replies: usize,
+    pub suggestions: usize,
+    /// Threads with no marker in the document, left out of the export
+    pub unanchored: Vec<String>,
+    pub images: usize,
+    /// Images kept as their alt text and HTML blocks left out
+    pub replaced: Vec<String>,
+}
+
+pub fn xml_escape(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for ch in text.chars() {
+        match ch {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            // Control characters aren't allowed in XML 1.0
+            '\t' | '\n' | '\r' => out.push(ch),
+            c if (c as u32) < 0x20 => {}
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+/// Word wants `2024-05-01T10:00:00Z`, without fractional seconds.
+fn word_date(iso: &str) -> String {
+    match iso.split_once('.') {
+        Some((secs, _)) if iso.ends_with('Z') => format!("{}Z", secs),
+        _ => iso.to_string(),
+    }
+}
+
+fn initials(author: &str) -> String {
+    author
+        .split_whitespace()
+        .filter_map(|w| w.chars().next())
+        .flat_map(char::to_uppercase)
+        .collect()
+}
+
+/// Swap comment and suggestion markers for sentinels.
+fn mark_sentinels(markdown: &str) -> String {
+    let mut out = String::with_capacity(markdown.len());
+    let mut pos = 0;
+    for s in suggestions::extract_suggestions(markdown) {
+        out.push_str(&markdown[pos..s.start]);
+        let num = &s.id[1..];
+        if let Some(d) = &s.deleted {
+            out.push_str(&format!("{}{}{}{}{}", DEL_START, num, ID_END, d, DEL_END));
+        }
+        if let Some(i) = &s.inserted {
+            out.push_str(&format!("{}{}{}{}{}", INS_START, num, ID_END, i, INS_END));
+        }
+        pos = s.end;
+    }
+    out.push_str(&markdown[pos..]);
+
+    let text = out;
+    let mut out = String::with_capacity(text.len());
+    let mut pos = 0;
+    for a in comments::extract_anchors(&text) {
+        let num = &a.id[1..];
+        out.push_str(&text[pos..a.start]);
+        out.push_str(&format!("{}{}{}{}", COMMENT_START, num, ID_END, a.text));
+        out.push_str(&format!("{}{}{}", COMMENT_END, num, ID_END));
+        pos = a.end;
+    }
+    out.push_str(&text[pos..]);
+    out
+}
+
+struct WordComment {
+    id: u32,
+    author: String,
+    date: String,
+    body: String,
+    para_id: String,
+    parent_para_id: Option<String>,
+    done: bool,
+}
+
+#[derive(Default)]
+struct RunFormat {
+    bold: u32,
+    italic: u32,
+    strike: u32,
+}
+
+/// An image embedded as `word/media/<name>`
+struct DocxImage {
+    name: String,
+    data: Vec<u8>,
+}
+
+struct ListLevel {
+    num_id: u32,
+}
+
+struct DocxWriter<'a> {
+    file: &'a CommentsFile,
+    body: String,
+    para_open: bool,
+    format: RunFormat,
+    heading: Option<u8>,
+    quote_depth: u32,
+    code_block: bool,
+    lists: Vec<ListLevel>,
+    /// The next paragraph starts a list item and carries its number
+    item_pending: bool,
+    table_header: bool,
+    /// Open image: (target, alt text so far)
+    image: Option<(String, String)>,
+    /// Folder image paths are relative to
+    base: Option<PathBuf>,
+    /// Inside a block of raw HTML that was already reported
+    html_block: bool,
+    /// Open `<w:hyperlink>` elements
+    links: u32,
+    metadata: bool,
+    /// Active tracked change: (is insertion, suggestion id)
+    change: Option<(bool, String)>,
+    suggestion_ids: BTreeSet<String>,
+
+    next_id: u32,
+    para_seq: u32,
+    comments: Vec<WordComment>,
+    /// Word comment ids per thread number, opened but not yet closed
+    open_threads: BTreeMap<String, Vec<u32>>,
+    anchored: Vec<String>,
+    ordered_lists: Vec<(u32, u64)>,
+    hyperlinks: Vec<String>,
+    images: Vec<DocxImage>,
+    report: DocxExportReport,
+}
+
+impl<'a> DocxWriter<'a> {
+    fn new(file: &'a CommentsFile, base: Option<PathBuf>) -> Self {
+        DocxWriter {
+            file,
+            body: String::new(),
+            para_open: false,
+            format: RunFormat::default(),
+            heading: None,
+            quote_depth: 0,
+            code_block: false,
+            lists: Vec::new(),
+            item_pending: false,
+            table_header: false,
+            image: None,
+            base,
+            html_block: false,
+            links: 0,
+            metadata: false,
+            change: None,
+            suggestion_ids: BTreeSet::new(),
+            next_id: 0,
+            para_seq: 0,
+            comments: Vec::new(),
+            open_threads: BTreeMap::new(),
+            anchored: Vec::new(),
+            ordered_lists: Vec::new(),
+            hyperlinks: Vec::new(),
+            images: Vec::new(),
+            report: DocxExportReport::default(),
+        }
+    }
+
+    fn next_para_id(&mut self) -> String {
+        self.para_seq += 1;
+        format!("{:08X}", 0x1000_0000 + self.para_seq)
+    }
+
+    fn open_para(&mut self) {
+        if self.para_open {
+            return;
+        }
+        let mut ppr = String::new();
+        let style = if let Some(level) = self.heading {
+            Some(format!("Heading{}", level))
+        } else if self.code_block {
+            Some("Code".to_string())
+        } else if self.quote_depth > 0 {
+            Some("Quote".to_string())
+        } else if !self.lists.is_empty() {
+            Some("ListParagraph".to_string())
+        } else {
+            None
+        };
+        if let Some(style) = style {
+            ppr.push_str(&format!("<w:pStyle w:val=\"{}\"/>", style));
+        }
+        if self.item_pending {
+            if let Some(list) = self.lists.last() {
+                ppr.push_str(&format!(
+                    "<w:numPr><w:ilvl w:val=\"{}\"/><w:numId w:val=\"{}\"/></w:numPr>",
+                    self.lists.len() - 1,
+                    list.num_id
+                ));
+            }
+            self.item_pending = false;
+        } else if !self.lists.is_empty() && self.heading.is_none() {
+            // Continuation paragraphs line up with the item's text
+            ppr.push_str(&format!("<w:ind w:left=\"{}\"/>", 720 * self.lists.len()));
+        }
+        self.body.push_str("<w:p>");
+        if !ppr.is_empty() {
+            self.body.push_str(&format!("<w:pPr>{}</w:pPr>", ppr));
+        }
+        self.para_open = true;
+    }
+
+    fn close_para(&mut self) {
+        if self.para_open {
+            self.body.push_str("</w:p>");
+            self.para_open = false;
+        }
+    }
+
+    fn run_props(&self, code: bool, link: bool) -> String {
+        let mut rpr = String::new();
+        if code {
+            rpr.push_str("<w:rStyle w:val=\"CodeChar\"/>");
+        } else if link {
+            rpr.push_str("<w:rStyle w:val=\"Hyperlink\"/>");
+        }
+        if self.format.bold > 0 || self.table_header {
+            rpr.push_str("<w:b/>");
+        }
+        if self.format.italic > 0 {
+            rpr.push_str("<w:i/>");
+        }
+        if self.format.strike > 0 {
+            rpr.push_str("<w:strike/>");
+        }
+        if rpr.is_empty() {
+            rpr
+        } else {
+            format!("<w:rPr>{}</w:rPr>", rpr)
+        }
+    }
+
+    fn push_run(&mut self, text: &str, code: bool) {
+        if text.is_empty() {
+            return;
+        }
+        self.open_para();
+        let rpr = self.run_props(code, self.links > 0);
+        let escaped = xml_escape(text);
+        let run = match &self.change {
+            Some((false, _)) => format!(
+                "<w:r>{}<w:delText xml:space=\"preserve\">{}</w:delText></w:r>",
+                rpr, escaped
+            ),
+            _ => format!("<w:r>{}<w:t xml:space=\"preserve\">{}</w:t></w:r>", rpr, escaped),
+        };
+        match self.change.clone() {
+            Some((inserted, id)) => {
+                let tag = if inserted { "w:ins" } else { "w:del" };
+                let (author, date) = self.suggestion_meta(&id);
+                let change_id = self.next_id;
+                self.next_id += 1;
+                self.body.push_str(&format!(
+                    "<{} w:id=\"{}\" w:author=\"{}\" w:date=\"{}\">{}</{}>",
+                    tag,
+                    change_id,
+                    xml_escape(&author),
+                    xml_escape(&date),
+                    run,
+                    tag
+                ));
+            }
+            None => self.body.push_str(&run),
+        }
+    }
+
+    fn suggestion_meta(&self, id: &str) -> (String, String) {
+        match self.file.suggestions.get(id) {
+            Some(Suggestion { author, timestamp, .. }) => (author.clone(), word_date(timestamp)),
+            None => ("Unknown".to_string(), word_date(&comments::iso_now())),
+        }
+    }
+
+    fn start_thread(&mut self, num: &str) {
+        let thread_id = format!("c{}", num);
+        let Some(thread) = self.file.comments.get(&thread_id) else {
+            return;
+        };
+        if thread.thread.is_empty() || self.open_threads.contains_key(&thread_id) {
+            return;
+        }
+        self.open_para();
+        let ids = self.add_thread_comments(thread);
+        for id in &ids {
+            self.body.push_str(&format!("<w:commentRangeStart w:id=\"{}\"/>", id));
+        }
+        self.anchored.push(thread_id.clone());
+        self.open_threads.insert(thread_id, ids);
+    }
+
+    /// One Word comment per message; replies point at the first one.
+    fn add_thread_comments(&mut self, thread: &CommentThread) -> Vec<u32> {
+        let mut ids = Vec::new();
+        let mut parent: Option<String> = None;
+        for msg in &thread.thread {
+            let id = self.next_id;
+            self.next_id += 1;
+            let para_id = self.next_para_id();
+            self.comments.push(WordComment {
+                id,
+                author: msg.author.clone(),
+                date: word_date(&msg.timestamp),
+                body: msg.body.clone(),
+                para_id: para_id.clone(),
+                parent_para_id: parent.clone(),
+                done: thread.resolved,
+            });
+            if parent.is_none() {
+                parent = Some(para_id);
+                self.report.comments += 1;
+            } else {
+                self.report.replies += 1;
+            }
+            ids.push(id);
+        }
+        ids
+    }
+
+    fn end_thread(&mut self, num: &str) {
+        let Some(ids) = self.open_threads.remove(&format!("c{}", num)) else {
+            return;
+        };
+        self.open_para();
+        for id in ids {
+            self.body.push_str(&format!(
+                "<w:commentRangeEnd w:id=\"{id}\"/><w:r><w:rPr><w:rStyle w:val=\"CommentReference\"/></w:rPr><w:commentReference w:id=\"{id}\"/></w:r>"
+            ));
+        }
+    }
+
+    /// Emit text, acting on any sentinels in it.
+    fn text(&mut self, text: &str, code: bool) {
+        let mut rest = text;
+        while let Some(pos) = rest.find(|c: char| ('\u{E000}'..='\u{E00F}').contains(&c)) {
+            self.push_run(&rest[..pos], code);
+            let sentinel = rest[pos..].chars().next().unwrap_or_default();
+            rest = &rest[pos + sentinel.len_utf8()..];
+            let mut num = String::new();
+            if matches!(sentinel, COMMENT_START | COMMENT_END | DEL_START | INS_START) {
+                if let Some((id, after)) = rest.split_once(ID_END) {
+                    num = id.to_string();
+                    rest = after;
+                }
+            }
+            match sentinel {
+                COMMENT_START => self.start_thread(&num),
+                COMMENT_END => self.end_thread(&num),
+                DEL_START | INS_START => {
+                    let id = format!("s{}", num);
+                    self.suggestion_ids.insert(id.clone());
+                    self.change = Some((sentinel == INS_START, id));
+                }
+                DEL_END | INS_END => self.change = None,
+                _ => {}
+            }
+        }
+        self.push_run(rest, code);
+    }
+
+    fn code_block_text(&mut self, text: &str) {
+        let mut lines = text.split('\n').peekable();
+        while let Some(line) = lines.next() {
+            // The block's final newline doesn't start another line
+            if line.is_empty() && lines.peek().is_none() {
+                break;
+            }
+            self.open_para();
+            self.text(line, false);
+            self.close_para();
+        }
+    }
+
+    fn hyperlink(&mut self, url: &str) {
+        let external = url.contains("://") || url.starts_with("mailto:");
+        if !external {
+            return;
+        }
+        self.open_para();
+        self.hyperlinks.push(url.to_string());
+        // Numbered apart from the fixed parts' rId1..rId5
+        let rel = format!("rIdLink{}", self.hyperlinks.len());
+        self.body.push_str(&format!("<w:hyperlink r:id=\"{}\" w:history=\"1\">", rel));
+        self.links += 1;
+    }
+
+    fn event(&mut self, event: Event) {
+        if self.metadata {
+            if let Event::End(TagEnd::MetadataBlock(_)) = event {
+                self.metadata = false;
+            }
+            return;
+        }
+        if let Some((_, alt)) = self.image.as_mut() {
+            match event {
+                Event::Text(t) | Event::Code(t) => alt.push_str(&t),
+                Event::End(TagEnd::Image) => {
+                    let (target, alt) = self.image.take().unwrap_or_default();
+                    self.image(&target, &alt);
+                }
+                _ => {}
+            }
+            return;
+        }
+
+        match event {
+            Event::Start(tag) => self.start(tag),
+            Event::End(tag) => self.end(tag),
+            Event::Text(text) => {
+                if self.code_block {
+                    self.code_block_text(&text);
+                } else {
+                    self.text(&text, false);
+                }
+            }
+            Event::Code(text) => self.text(&text, true),
+            Event::SoftBreak => self.text(" ", false),
+            Event::HardBreak => {
+                self.open_para();
+                self.body.push_str("<w:r><w:br/></w:r>");
+            }
+            Event::Rule => {
+                self.close_para();
+                self.body.push_str(
+                    "<w:p><w:pPr><w:pBdr><w:bottom w:val=\"single\" w:sz=\"6\" w:space=\"1\" w:color=\"auto\"/></w:pBdr></w:pPr></w:p>",
+                );
+            }
+            Event::TaskListMarker(checked) => {
+                self.text(if checked { "\u{2612} " } else { "\u{2610} " }, false)
+            }
+            Event::FootnoteReference(label) => self.text(&format!("[^{}]", label), false),
+            Event::InlineHtml(html) if html.trim().eq_ignore_ascii_case("<br>") => {
+                self.open_para();
+                self.body.push_str("<w:r><w:br/></w:r>");
+            }
+            Event::Html(html) if !self.html_block => {
+                // Word has nothing to render raw HTML with
+                let line = html.lines().map(str::trim).find(|l| !l.is_empty()).unwrap_or("");
+                let mut shown: String = line.chars().take(40).collect();
+                if shown.len() < line.len() {
+                    shown.push('…');
+                }
+                self.report.replaced.push(format!("HTML {}", shown));
+                self.html_block = true;
+            }
+            _ => {}
+        }
+    }
+
+    /// Embed a local image, or fall back to its alt text.
+    fn image(&mut self, target: &str, alt: &str) {
+        let Some((data, ext, width, height)) = self.load_image(target) else {
+            self.report.replaced.push(target.to_string());
+            self.format.italic += 1;
+            self.text(&format!("[Image: {}]", alt), false);
+            self.format.italic -= 1;
+            return;
+        };
+        let n = self.images.len() + 1;
+        let name = format!("image{}.{}", n, ext);
+        let mut cx = width * EMU_PER_PX;
+        let mut cy = height * EMU_PER_PX;
+        if cx > MAX_IMAGE_WIDTH {
+            cy = cy * MAX_IMAGE_WIDTH / cx;
+            cx = MAX_IMAGE_WIDTH;
+        }
+        self.open_para();
+        self.body.push_str(&format!(
+            "<w:r><w:drawing><wp:inline distT=\"0\" distB=\"0\" distL=\"0\" distR=\"0\">\
+<wp:extent cx=\"{cx}\" cy=\"{cy}\"/><wp:docPr id=\"{n}\" name=\"Picture {n}\" descr=\"{alt}\"/>\
+<wp:cNvGraphicFramePr><a:graphicFrameLocks noChangeAspect=\"1\"/></wp:cNvGraphicFramePr>\
+<a:graphic><a:graphicData uri=\"{NS_PIC}\"><pic:pic>\
+<pic:nvPicPr><pic:cNvPr id=\"{n}\" name=\"{name}\"/><pic:cNvPicPr/></pic:nvPicPr>\
+<pic:blipFill><a:blip r:embed=\"rIdImg{n}\"/><a:stretch><a:fillRect/></a:stretch></pic:blipFill>\
+<pic:spPr><a:xfrm><a:off x=\"0\" y=\"0\"/><a:ext cx=\"{cx}\" cy=\"{cy}\"/></a:xfrm>\
+<a:prstGeom prst=\"rect\"><a:avLst/></a:prstGeom></pic:spPr>\
+</pic:pic></a:graphicData></a:graphic></wp:inline></w:drawing></w:r>",
+            alt = xml_escape(alt),
+        ));
+        self.images.push(DocxImage { name, data });
+        self.report.images += 1;
+    }
+
+    /// Read a local image with its extension and size in pixels.
+    fn load_image(&self, target: &str) -> Option<(Vec<u8>, &'static str, u64, u64)> {
+        let target = target.trim();
+        if target.contains("://") || target.starts_with("data:") {
+            return None;
+        }
+        let target = target.split(['#', '?']).next().unwrap_or(target);
+        let path = PathBuf::from(target.replace("%20", " "));
+        let path = match &self.base {
+            Some(base) if path.is_relative() => base.join(path),
+            _ => path,
+        };
+        let data = fs::read(path).ok()?;
+        let (ext, width, height) = image_size(&data)?;
+        Some((data, ext, width, height))
+    }
+
+    fn start(&mut self, tag: Tag) {
+        match tag {
+            Tag::Paragraph => self.open_para(),
+            Tag::Heading { level, .. } => {
+                self.close_para();
+                self.heading = Some(heading_number(level));
+                self.open_para();
+            }
+            Tag::BlockQuote(_) => {
+                self.close_para();
+                self.quote_depth += 1;
+            }
+            Tag::CodeBlock(_) => {
+                self.close_para();
+                self.code_block = true;
+            }
+            Tag::List(start) => {
+                self.close_para();
+                let num_id = match start {
+                    Some(n) => {
+                        // Each ordered list restarts its numbering
+                        let id = BULLET_NUM_ID + 1 + self.ordered_lists.len() as u32;
+                        self.ordered_lists.push((id, n));
+                        id
+                    }
+                    None => BULLET_NUM_ID,
+                };
+                self.lists.push(ListLevel { num_id });
+            }
+            Tag::Item => {
+                self.close_para();
+                self.item_pending = true;
+            }
+            Tag::Table(_) => {
+                self.close_para();
+                self.body.push_str(
+                    "<w:tbl><w:tblPr><w:tblStyle w:val=\"TableGrid\"/><w:tblW w:w=\"0\" w:type=\"auto\"/></w:tblPr>",
+                );
+            }
+            Tag::TableHead => {
+                self.table_header = true;
+                self.body.push_str("<w:tr><w:trPr><w:tblHeader/></w:trPr>");
+            }
+            Tag::TableRow => self.body.push_str("<w:tr>"),
+            Tag::TableCell => {
+                self.body.push_str("<w:tc>");
+                self.open_para();
+            }
+            Tag::Emphasis => self.format.italic += 1,
+            Tag::Strong => self.format.bold += 1,
+            Tag::Strikethrough => self.format.strike += 1,
+            Tag::Link { dest_url, .. } => self.hyperlink(&dest_url),
+            Tag::Image { dest_url, .. } => self.image = Some((dest_url.to_string(), String::new())),
+            Tag::MetadataBlock(_) => self.metadata = true,
+            _ => {}
+        }
+    }
+
+    fn end(&mut self, tag: TagEnd) {
+        match tag {
+            TagEnd::Paragraph => self.close_para(),
+            TagEnd::Heading(_) => {
+                self.close_para();
+                self.heading = None;
+            }
+            TagEnd::BlockQuote(_) => {
+                self.close_para();
+                self.quote_depth = self.quote_depth.saturating_sub(1);
+            }
+            TagEnd::CodeBlock => {
+                self.close_para();
+                self.code_block = false;
+            }
+            TagEnd::List(_) => {
+                self.close_para();
+                self.lists.pop();
+            }
+            TagEnd::Item => {
+                // An empty item still shows its bullet
+                if self.item_pending {
+                    self.open_para();
+                }
+                self.close_para();
+            }
+            TagEnd::Table => self.body.push_str("</w:tbl>"),
+            TagEnd::TableHead => {
+                self.table_header = false;
+                self.body.push_str("</w:tr>");
+            }
+            TagEnd::TableRow => self.body.push_str("</w:tr>"),
+            TagEnd::TableCell => {
+                self.open_para();
+                self.close_para();
+                self.body.push_str("</w:tc>");
+            }
+            TagEnd::Emphasis => self.format.italic = self.format.italic.saturating_sub(1),
+            TagEnd::Strong => self.format.bold = self.format.bold.saturating_sub(1),
+            TagEnd::Strikethrough => self.format.strike = self.format.strike.saturating_sub(1),
+            TagEnd::HtmlBlock => self.html_block = false,
+            TagEnd::Link if self.links > 0 => {
+                self.body.push_str("</w:hyperlink>");
+                self.links -= 1;
+            }
+            _ => {}
+        }
+    }
+
+    fn finish(&mut self) {
+        self.close_para();
+        // A marker that never closed still needs its range ended
+        let open: Vec<String> = self.open_threads.keys().cloned().collect();
+        for id in open {
+            self.end_thread(&id[1..]);
+            self.close_para();
+        }
+        self.report.suggestions = self.suggestion_ids.len();
+        for id in self.file.comments.keys() {
+            if !self.anchored.contains(id) {
+                self.report.unanchored.push(id.clone());
+            }
+        }
+    }
+}
+
+/// Format and pixel size of a PNG, JPEG or GIF, the formats Word shows
+/// everywhere.
+fn image_size(data: &[u8]) -> Option<(&'static str, u64, u64)> {
+    let be16 = |at: usize| Some(u16::from_be_bytes([*data.get(at)?, *data.get(at + 1)?]) as u64);
+    let size = if data.starts_with(b"\x89PNG\r\n\x1a\n") && data.len() >= 24 {
+        let be32 = |at: usize| {
+            u32::from_be_bytes([data[at], data[at + 1], data[at + 2], data[at + 3]]) as u64
+        };
+        ("png", be32(16), be32(20))
+    } else if data.starts_with(b"GIF8") && data.len() >= 10 {
+        let le16 = |at: usize| u16::from_le_bytes([data[at], data[at + 1]]) as u64;
+        ("gif", le16(6), le16(8))
+    } else if data.starts_with(&[0xFF, 0xD8]) {
+        // Walk the segments to the frame header
+        let mut at = 2;
+        loop {
+            if *data.get(at)? != 0xFF {
+                return None;
+            }
+            let marker = *data.get(at + 1)?;
+            if (0xC0..=0xCF).contains(&marker) && !matches!(marker, 0xC4 | 0xC8 | 0xCC) {
+                break ("jpeg", be16(at + 7)?, be16(at + 5)?);
+            }
+            at += 2 + be16(at + 2)? as usize;
+        }
+    } else {
+        return None;
+    };
+    (size.1 > 0 && size.2 > 0).then_some(size)
+}
+
+fn heading_number(level: HeadingLevel) -> u8 {
+    match level {
+        HeadingLevel::H1 => 1,
+        HeadingLevel::H2 => 2,
+        HeadingLevel::H3 => 3,
+        HeadingLevel::H4 => 4,
+        HeadingLevel::H5 => 5,
+        HeadingLevel::H6 => 6,
+    }
+}
+
+fn document_xml(body: &str) -> String {
+    format!(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\" standalone=\"yes\"?>\n\
+<w:document xmlns:w=\"{NS_W}\" xmlns:r=\"{NS_R}\" xmlns:wp=\"{NS_WP}\" xmlns:a=\"{NS_A}\" \
+xmlns:pic=\"{NS_PIC}\"><w:body>{body}\
+<w:sectPr><w:pgSz w:w=\"12240\" w:h=\"15840\"/>\
+<w:pgMar w:top=\"1440\" w:right=\"1440\" w:bottom=\"1440\" w:left=\"1440\" w:header=\"720\" w:footer=\"720\" w:gutter=\"0\"/>\
+</w:sectPr></w:body></w:document>"
+    )
+}
+
+fn comments_xml(comments: &[WordComment]) -> String {
+    let mut out = format!(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\" standalone=\"yes\"?>\n\
+<w:comments xmlns:w=\"{NS_W}\" xmlns:w14=\"{NS_W14}\" xmlns:mc=\"{NS_MC}\" mc:Ignorable=\"w14\">"
+    );
+    for c in comments {
+        out.push_str(&format!(
+            "<w:comment w:id=\"{}\" w:author=\"{}\" w:date=\"{}\" w:initials=\"{}\">",
+            c.id,
+            xml_escape(&c.author),
+            xml_escape(&c.date),
+            xml_escape(&initials(&c.author)),
+        ));
+        let lines: Vec<&str> = c.body.split('\n').collect();
+        for (i, line) in lines.iter().enumerate() {
+            // commentsExtended refers to a comment by its last paragraph
+            if i + 1 == lines.len() {
+                out.push_str(&format!("<w:p w14:paraId=\"{}\" w14:textId=\"77777777\">", c.para_id));
+            } else {
+                out.push_str("<w:p>");
+            }
+            out.push_str("<w:pPr><w:pStyle w:val=\"CommentText\"/></w:pPr>");
+            if i == 0 {
+                out.push_str(
+                    "<w:r><w:rPr><w:rStyle w:val=\"CommentReference\"/></w:rPr><w:annotationRef/></w:r>",
+                );
+            }
+            out.push_str(&format!(
+                "<w:r><w:t xml:space=\"preserve\">{}</w:t></w:r></w:p>",
+                xml_escape(line)
+            ));
+        }
+        out.push_str("</w:comment>");
+    }
+    out.push_str("</w:comments>");
+    out
+}
+
+fn comments_extended_xml(comments: &[WordComment]) -> String {
+    let mut out = format!(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\" standalone=\"yes\"?>\n\
+<w15:commentsEx xmlns:w15=\"{NS_W15}\" xmlns:mc=\"{NS_MC}\" mc:Ignorable=\"w15\">"
+    );
+    for c in comments {
+        out.push_str(&format!("<w15:commentEx w15:paraId=\"{}\"", c.para_id));
+        if let Some(parent) = &c.parent_para_id {
+            out.push_str(&format!(" w15:paraIdParent=\"{}\"", parent));
+        }
+        out.push_str(&format!(" w15:done=\"{}\"/>", if c.done { 1 } else { 0 }));
+    }
+    out.push_str("</w15:commentsEx>");
+    out
+}
+
+fn people_xml(comments: &[WordComment]) -> String {
+    let mut out = format!(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\" standalone=\"yes\"?>\n\
+<w15:people xmlns:w15=\"{NS_W15}\" xmlns:mc=\"{NS_MC}\" mc:Ignorable=\"w15\">"
+    );
+    let mut seen: Vec<&str> = Vec::new();
+    for c in comments {
+        if seen.contains(&c.author.as_str()) {
+            continue;
+        }
+        seen.push(&c.author);
+        let author = xml_escape(&c.author);
+        out.push_str(&format!(
+            "<w15:person w15:author=\"{author}\"><w15:presenceInfo w15:providerId=\"None\" w15:userId=\"{author}\"/></w15:person>"
+        ));
+    }
+    out.push_str("</w15:people>");
+    out
+}
+
+fn numbering_xml(ordered_lists: &[(u32, u64)]) -> String {
+    let mut out = format!(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\" standalone=\"yes\"?>\n<w:numbering xmlns:w=\"{NS_W}\">"
+    );
+    let bullets = ["\u{2022}", "\u{25E6}", "\u{25AA}"];
+    out.push_str("<w:abstractNum w:abstractNumId=\"0\"><w:multiLevelType w:val=\"hybridMultilevel\"/>");
+    for level in 0..9 {
+        out.push_str(&format!(
+            "<w:lvl w:ilvl=\"{level}\"><w:start w:val=\"1\"/><w:numFmt w:val=\"bullet\"/>\
+<w:lvlText w:val=\"{}\"/><w:lvlJc w:val=\"left\"/>\
+<w:pPr><w:ind w:left=\"{}\" w:hanging=\"360\"/></w:pPr></w:lvl>",
+            bullets[level % bullets.len()],
+            720 * (level + 1)
+        ));
+    }
+    out.push_str("</w:abstractNum>");
+    let formats = ["decimal", "lowerLetter", "lowerRoman"];
+    out.push_str("<w:abstractNum w:abstractNumId=\"1\"><w:multiLevelType w:val=\"hybridMultilevel\"/>");
+    for level in 0..9 {
+        out.push_str(&format!(
+            "<w:lvl w:ilvl=\"{level}\"><w:start w:val=\"1\"/><w:numFmt w:val=\"{}\"/>\
+<w:lvlText w:val=\"%{}.\"/><w:lvlJc w:val=\"left\"/>\
+<w:pPr><w:ind w:left=\"{}\" w:hanging=\"360\"/></w:pPr></w:lvl>",
+            formats[level % formats.len()],
+            level + 1,
+            720 * (level + 1)
+        ));
+    }
+    out.push_str("</w:abstractNum>");
+    out.push_str(&format!(
+        "<w:num w:numId=\"{}\"><w:abstractNumId w:val=\"0\"/></w:num>",
+        BULLET_NUM_ID
+    ));
+    for (id, start) in ordered_lists {
+        out.push_str(&format!("<w:num w:numId=\"{}\"><w:abstractNumId w:val=\"1\"/>", id));
+        for level in 0..9 {
+            out.push_str(&format!(
+                "<w:lvlOverride w:ilvl=\"{}\"><w:startOverride w:val=\"{}\"/></w:lvlOverride>",
+                level,
+                if level == 0 { *start } else { 1 }
+            ));
+        }
+        out.push_str("</w:num>");
+    }
+    out.push_str("</w:numbering>");
+    out
+}
+
+fn styles_xml() -> String {
+    let mut styles = String::new();
+    styles.push_str(
+        "<w:docDefaults><w:rPrDefault><w:rPr><w:rFonts w:ascii=\"Calibri\" w:hAnsi=\"Calibri\" w:eastAsia=\"Calibri\" w:cs=\"Calibri\"/>\
+<w:sz w:val=\"22\"/><w:szCs w:val=\"22\"/><w:lang w:val=\"en-US\"/></w:rPr></w:rPrDefault>\
+<w:pPrDefault><w:pPr><w:spacing w:after=\"160\" w:line=\"259\" w:lineRule=\"auto\"/></w:pPr></w:pPrDefault></w:docDefaults>\
+<w:style w:type=\"paragraph\" w:default=\"1\" w:styleId=\"Normal\"><w:name w:val=\"Normal\"/><w:qFormat/></w:style>",
+    );
+    let sizes = [32, 28, 26, 24, 22, 22];
+    for (i, size) in sizes.iter().enumerate() {
+        styles.push_str(&format!(
+            "<w:style w:type=\"paragraph\" w:styleId=\"Heading{n}\"><w:name w:val=\"heading {n}\"/>\
+<w:basedOn w:val=\"Normal\"/><w:next w:val=\"Normal\"/><w:qFormat/>\
+<w:pPr><w:keepNext/><w:spacing w:before=\"240\" w:after=\"80\"/><w:outlineLvl w:val=\"{lvl}\"/></w:pPr>\
+<w:rPr><w:b/><w:sz w:val=\"{size}\"/><w:szCs w:val=\"{size}\"/></w:rPr></w:style>",
+            n = i + 1,
+            lvl = i,
+            size = size
+        ));
+    }
+    styles.push_str(
+        "<w:style w:type=\"paragraph\" w:styleId=\"Quote\"><w:name w:val=\"Quote\"/><w:basedOn w:val=\"Normal\"/><w:qFormat/>\
+<w:pPr><w:pBdr><w:left w:val=\"single\" w:sz=\"18\" w:space=\"8\" w:color=\"BFBFBF\"/></w:pBdr><w:ind w:left=\"360\"/></w:pPr>\
+<w:rPr><w:i/><w:color w:val=\"595959\"/></w:rPr></w:style>\
+<w:style w:type=\"paragraph\" w:styleId=\"Code\"><w:name w:val=\"Code\"/><w:basedOn w:val=\"Normal\"/>\
+<w:pPr><w:spacing w:after=\"0\" w:line=\"240\" w:lineRule=\"auto\"/><w:shd w:val=\"clear\" w:color=\"auto\" w:fill=\"F2F2F2\"/></w:pPr>\
+<w:rPr><w:rFonts w:ascii=\"Consolas\" w:hAnsi=\"Consolas\" w:cs=\"Consolas\"/><w:sz w:val=\"20\"/></w:rPr></w:style>\
+<w:style w:type=\"paragraph\" w:styleId=\"ListParagraph\"><w:name w:val=\"List Paragraph\"/><w:basedOn w:val=\"Normal\"/>\
+<w:qFormat/><w:pPr><w:spacing w:after=\"40\"/><w:ind w:left=\"720\"/></w:pPr></w:style>\
+<w:style w:type=\"paragraph\" w:styleId=\"CommentText\"><w:name w:val=\"annotation text\"/><w:basedOn w:val=\"Normal\"/>\
+<w:rPr><w:sz w:val=\"20\"/><w:szCs w:val=\"20\"/></w:rPr></w:style>\
+<w:style w:type=\"character\" w:default=\"1\" w:styleId=\"DefaultParagraphFont\"><w:name w:val=\"Default Paragraph Font\"/><w:uiPriority w:val=\"1\"/><w:semiHidden/></w:style>\
+<w:style w:type=\"character\" w:styleId=\"CodeChar\"><w:name w:val=\"Code Char\"/><w:basedOn w:val=\"DefaultParagraphFont\"/>\
+<w:rPr><w:rFonts w:ascii=\"Consolas\" w:hAnsi=\"Consolas\" w:cs=\"Consolas\"/><w:sz w:val=\"20\"/><w:shd w:val=\"clear\" w:color=\"auto\" w:fill=\"F2F2F2\"/></w:rPr></w:style>\
+<w:style w:type=\"character\" w:styleId=\"Hyperlink\"><w:name w:val=\"Hyperlink\"/><w:basedOn w:val=\"DefaultParagraphFont\"/>\
+<w:rPr><w:color w:val=\"0563C1\"/><w:u w:val=\"single\"/></w:rPr></w:style>\
+<w:style w:type=\"character\" w:styleId=\"CommentReference\"><w:name w:val=\"annotation reference\"/><w:basedOn w:val=\"DefaultParagraphFont\"/>\
+<w:rPr><w:sz w:val=\"16\"/><w:szCs w:val=\"16\"/></w:rPr></w:style>\
+<w:style w:type=\"table\" w:default=\"1\" w:styleId=\"TableNormal\"><w:name w:val=\"Normal Table\"/><w:semiHidden/>\
+<w:tblPr><w:tblInd w:w=\"0\" w:type=\"dxa\"/><w:tblCellMar><w:top w:w=\"0\" w:type=\"dxa\"/><w:left w:w=\"108\" w:type=\"dxa\"/>\
+<w:bottom w:w=\"0\" w:type=\"dxa\"/><w:right w:w=\"108\" w:type=\"dxa\"/></w:tblCellMar></w:tblPr></w:style>\
+<w:style w:type=\"table\" w:styleId=\"TableGrid\"><w:name w:val=\"Table Grid\"/><w:basedOn w:val=\"TableNormal\"/>\
+<w:pPr><w:spacing w:after=\"0\"/></w:pPr><w:tblPr><w:tblBorders>\
+<w:top w:val=\"single\" w:sz=\"4\" w:space=\"0\" w:color=\"auto\"/><w:left w:val=\"single\" w:sz=\"4\" w:space=\"0\" w:color=\"auto\"/>\
+<w:bottom w:val=\"single\" w:sz=\"4\" w:space=\"0\" w:color=\"auto\"/><w:right w:val=\"single\" w:sz=\"4\" w:space=\"0\" w:color=\"auto\"/>\
+<w:insideH w:val=\"single\" w:sz=\"4\" w:space=\"0\" w:color=\"auto\"/><w:insideV w:val=\"single\" w:sz=\"4\" w:space=\"0\" w:color=\"auto\"/>\
+</w:tblBorders></w:tblPr></w:style>",
+    );
+    format!(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\" standalone=\"yes\"?>\n<w:styles xmlns:w=\"{NS_W}\">{styles}</w:styles>"
+    )
+}
+
+fn content_types_xml() -> String {
+    let main = "application/vnd.openxmlformats-officedocument.wordprocessingml";
+    format!(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\" standalone=\"yes\"?>\n\
+<Types xmlns=\"http://schemas.openxmlformats.org/package/2006/content-types\">\
+<Default Extension=\"rels\" ContentType=\"application/vnd.openxmlformats-package.relationships+xml\"/>\
+<Default Extension=\"xml\" ContentType=\"application/xml\"/>\
+<Default Extension=\"png\" ContentType=\"image/png\"/>\
+<Default Extension=\"jpeg\" ContentType=\"image/jpeg\"/>\
+<Default Extension=\"gif\" ContentType=\"image/gif\"/>\
+<Override PartName=\"/word/document.xml\" ContentType=\"{main}.document.main+xml\"/>\
+<Override PartName=\"/word/styles.xml\" ContentType=\"{main}.styles+xml\"/>\
+<Override PartName=\"/word/numbering.xml\" ContentType=\"{main}.numbering+xml\"/>\
+<Override PartName=\"/word/comments.xml\" ContentType=\"{main}.comments+xml\"/>\
+<Override PartName=\"/word/commentsExtended.xml\" ContentType=\"{main}.commentsExtended+xml\"/>\
+<Override PartName=\"/word/people.xml\" ContentType=\"{main}.people+xml\"/>\
+<Override PartName=\"/docProps/core.xml\" ContentType=\"application/vnd.openxmlformats-package.core-properties+xml\"/>\
+</Types>"
+    )
+}
+
+fn package_rels_xml() -> String {
+    format!(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\" standalone=\"yes\"?>\n\
+<Relationships xmlns=\"http://schemas.openxmlformats.org/package/2006/relationships\">\
+<Relationship Id=\"rId1\" Type=\"{REL_BASE}/officeDocument\" Target=\"word/document.xml\"/>\
+<Relationship Id=\"rId2\" Type=\"http://schemas.openxmlformats.org/package/2006/relationships/metadata/core-properties\" Target=\"docProps/core.xml\"/>\
+</Relationships>"
+    )
+}
+
+fn document_rels_xml(hyperlinks: &[String], images: &[DocxImage]) -> String {
+    let mut out = format!(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\" standalone=\"yes\"?>\n\
+<Relationships xmlns=\"http://schemas.openxmlformats.org/package/2006/relationships\">\
+<Relationship Id=\"rId1\" Type=\"{REL_BASE}/styles\" Target=\"styles.xml\"/>\
+<Relationship Id=\"rId2\" Type=\"{REL_BASE}/numbering\" Target=\"numbering.xml\"/>\
+<Relationship Id=\"rId3\" Type=\"{REL_BASE}/comments\" Target=\"comments.xml\"/>\
+<Relationship Id=\"rId4\" Type=\"http://schemas.microsoft.com/office/2011/relationships/commentsExtended\" Target=\"commentsExtended.xml\"/>\
+<Relationship Id=\"rId5\" Type=\"http://schemas.microsoft.com/office/2011/relationships/people\" Target=\"people.xml\"/>"
+    );
+    for (i, url) in hyperlinks.iter().enumerate() {
+        out.push_str(&format!(
+            "<Relationship Id=\"rIdLink{}\" Type=\"{REL_BASE}/hyperlink\" Target=\"{}\" TargetMode=\"External\"/>",
+            i + 1,
+            xml_escape(url)
+        ));
+    }
+    for (i, image) in images.iter().enumerate() {
+        out.push_str(&format!(
+            "<Relationship Id=\"rIdImg{}\" Type=\"{REL_BASE}/image\" Target=\"media/{}\"/>",
+            i + 1,
+            image.name
+        ));
+    }
+    out.push_str("</Relationships>");
+    out
+}
+
+fn core_xml(title: &str, author: Option<&str>) -> String {
+    let now = word_date(&comments::iso_now());
+    format!(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\" standalone=\"yes\"?>\n\
+<cp:coreProperties xmlns:cp=\"http://schemas.openxmlformats.org/package/2006/metadata/core-properties\" \
+xmlns:dc=\"http://purl.org/dc/elements/1.1/\" xmlns:dcterms=\"http://purl.org/dc/terms/\" \
+xmlns:xsi=\"http://www.w3.org/2001/XMLSchema-instance\">\
+<dc:title>{}</dc:title><dc:creator>{}</dc:creator>\
+<dcterms:created xsi:type=\"dcterms:W3CDTF\">{now}</dcterms:created>\
+<dcterms:modified xsi:type=\"dcterms:W3CDTF\">{now}</dcterms:modified>\
+</cp:coreProperties>",
+        xml_escape(title),
+        xml_escape(author.unwrap_or("Gutter")),
+    )
+}
+
+/// Convert markdown and its comment threads to the parts of a .docx package,
+/// as (part name, content) pairs. Local images are read relative to `base`.
+pub fn markdown_to_docx_parts(
+    markdown: &str,
+    file: &CommentsFile,
+    title: &str,
+    author: Option<&str>,
+    base: Option<&Path>,
+) -> (Vec<(String, Vec<u8>)>, DocxExportReport) {
+    let source = mark_sentinels(markdown);
+    let options = Options::ENABLE_TABLES
+        | Options::ENABLE_STRIKETHROUGH
+        | Options::ENABLE_TASKLISTS
+        | Options::ENABLE_FOOTNOTES
+        | Options::ENABLE_YAML_STYLE_METADATA_BLOCKS;
+    let mut writer = DocxWriter::new(file, base.map(Path::to_path_buf));
+    for event in Parser::new_ext(&source, options) {
+        writer.event(event);
+    }
+    writer.finish();
+
+    let xml = [
+        ("[Content_Types].xml", content_types_xml()),
+        ("_rels/.rels", package_rels_xml()),
+        ("docProps/core.xml", core_xml(title, author)),
+        ("word/document.xml", document_xml(&writer.body)),
+        ("word/_rels/document.xml.rels", document_rels_xml(&writer.hyperlinks, &writer.images)),
+        ("word/styles.xml", styles_xml()),
+        ("word/numbering.xml", numbering_xml(&writer.ordered_lists)),
+        ("word/comments.xml", comments_xml(&writer.comments)),
+        ("word/commentsExtended.xml", comments_extended_xml(&writer.comments)),
+        ("word/people.xml", people_xml(&writer.comments)),
+    ];
+    let mut parts: Vec<(String, Vec<u8>)> = xml
+        .into_iter()
+        .map(|(name, xml)| (name.to_string(), xml.into_bytes()))
+        .collect();
+    for image in writer.images.drain(..) {
+        parts.push((format!("word/media/{}", image.name), image.data));
+    }
+    (parts, writer.report)
+}
+
+/// Export markdown to a Word document at `path`. Threads from `source`'s
+/// comments become native Word comments, replies included; resolved threads
+/// are marked done and pending suggestions become tracked changes. Local
+/// images are embedded; what can't be carried over is listed in the report.
+#[tauri::command]
+pub fn export_docx(
+    window: WebviewWindow,
+    content: String,
+    path: String,
+    source: Option<String>,
+    workspace: Option<String>,
+) -> Result<DocxExportReport, String> {
+    let workspace = workspace_file::owning_root(&window, workspace, source.as_deref().unwrap_or(&path));
+    let file = match &source {
+        Some(src) => {
+            let store = comment_storage::store_for(workspace.as_deref());
+            comments::load_comments_file(store.as_ref(), Path::new(src))?.unwrap_or_default()
+        }
+        None => CommentsFile::default(),
+    };
+    let markdown = comment_storage::split_embedded(&content).0;
+    let title = Path::new(source.as_deref().unwrap_or(&path))
+        .file_stem()
+        .map(|s| s.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let author = super::settings::configured_author(workspace.as_deref().map(Path::new));
+    let base = source.as_deref().and_then(|src| Path::new(src).parent());
+    let (parts, report) = markdown_to_docx_parts(markdown, &file, &title, author.as_deref(), base);
+
+    let out = File::create(&path).map_err(|e| format!("Failed to create file: {}", e))?;
+    let mut zip = zip::ZipWriter::new(out);
+    let options = SimpleFileOptions::default();
+    for (name, data) in parts {
+        zip.start_file(name, options)
+            .map_err(|e| format!("Failed to write document: {}", e))?;
+        zip.write_all(&data)
+            .map_err(|e| format!("Failed to write document: {}", e))?;
+    }
+    zip.finish().map_err(|e| format!("Failed to write document: {}", e))?;
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::comments::CommentMessage;
+
+    /// A 1000x500 PNG header; the size is all the export reads
+    fn png() -> Vec<u8> {
+        let mut data = b"\x89PNG\r\n\x1a\n\0\0\0\x0dIHDR".to_vec();
+        data.extend_from_slice(&1000u32.to_be_bytes());
+        data.extend_from_slice(&500u32.to_be_bytes());
+        data
+    }
+
+    fn part<'p>(parts: &'p [(String, Vec<u8>)], name: &str) -> &'p [u8] {
+        &parts.iter().find(|(n, _)| n == name).expect("missing part").1
+    }
+
+    #[test]
+    fn threads_become_word_comments() {
+        let markdown = "A <mark>bold claim</mark><sup>[c1]</sup> here.\n";
+        let mut file = CommentsFile::default();
+        let mut thread = CommentThread::new("2024-05-01T09:00:00Z".to_string());
+        for (author, at, body) in [
+            ("Ann Lee", "2024-05-01T09:00:00Z", "Source?"),
+            ("Bob", "2024-05-01T10:00:00Z", "Added one."),
+        ] {
+            thread.thread.push(CommentMessage {
+                id: comments::new_message_id(),
+                author: author.to_string(),
+                timestamp: at.to_string(),
+                body: body.to_string(),
+            });
+        }
+        thread.resolved = true;
+        file.comments.insert("c1".to_string(), thread);
+        let (parts, _) = markdown_to_docx_parts(markdown, &file, "t", None, None);
+
+        let xml = String::from_utf8_lossy(part(&parts, "word/comments.xml")).to_string();
+        let doc = roxmltree::Document::parse(&xml).unwrap();
+        let comments: Vec<_> = doc.descendants().filter(|n| n.has_tag_name((NS_W, "comment"))).collect();
+        let authors: Vec<_> = comments.iter().map(|c| c.attribute((NS_W, "author")).unwrap()).collect();
+        assert_eq!(authors, ["Ann Lee", "Bob"]);
+        let texts: Vec<String> = comments
+            .iter()
+            .map(|c| c.descendants().filter(|n| n.has_tag_name((NS_W, "t"))).filter_map(|n| n.text()).collect())
+            .collect();
+        assert_eq!(texts, ["Source?", "Added one."]);
+        let para_ids: Vec<_> = comments
+            .iter()
+            .map(|c| {
+                let p = c.descendants().find(|n| n.attribute((NS_W14, "paraId")).is_some()).unwrap();
+                p.attribute((NS_W14, "paraId")).unwrap().to_string()
+            })
+            .collect();
+
+        // The reply hangs off the opening comment, and both are done
+        let xml = String::from_utf8_lossy(part(&parts, "word/commentsExtended.xml")).to_string();
+        let ext = roxmltree::Document::parse(&xml).unwrap();
+        let entries: Vec<_> = ext.descendants().filter(|n| n.has_tag_name((NS_W15, "commentEx"))).collect();
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].attribute((NS_W15, "paraId")), Some(para_ids[0].as_str()));
+        assert_eq!(entries[0].attribute((NS_W15, "paraIdParent")), None);
+        assert_eq!(entries[1].attribute((NS_W15, "paraId")), Some(para_ids[1].as_str()));
+        assert_eq!(entries[1].attribute((NS_W15, "paraIdParent")), Some(para_ids[0].as_str()));
+        assert!(entries.iter().all(|e| e.attribute((NS_W15, "done")) == Some("1")));
+
+        // The opening comment spans the marked text in the document
+        let xml = String::from_utf8_lossy(part(&parts, "word/document.xml")).to_string();
+        let body = roxmltree::Document::parse(&xml).unwrap();
+        let id = comments[0].attribute((NS_W, "id")).unwrap();
+        let start = body.descendants().find(|n| n.has_tag_name((NS_W, "commentRangeStart"))).unwrap();
+        assert_eq!(start.attribute((NS_W, "id")), Some(id));
+    }
+
+    #[test]
+    fn local_images_are_embedded() {
+        let dir = std::env::temp_dir().join(format!("gutter-docx-{}", std::process::id()));
+        fs::create_dir_all(dir.join("assets")).unwrap();
+        fs::write(dir.join("assets/my chart.png"), png()).unwrap();
+
+        let markdown = "![Sales](assets/my%20chart.png)\n";
+        let file = CommentsFile::default();
+        let (parts, report) = markdown_to_docx_parts(markdown, &file, "t", None, Some(&dir));
+        fs::remove_dir_all(&dir).ok();
+
+        assert_eq!(report.images, 1);
+        assert!(report.replaced.is_empty());
+        assert_eq!(part(&parts, "word/media/image1.png"), png().as_slice());
+        let rels = String::from_utf8_lossy(part(&parts, "word/_rels/document.xml.rels")).to_string();
+        assert!(rels.contains("Id=\"rIdImg1\"") && rels.contains("Target=\"media/image1.png\""));
+        let doc = String::from_utf8_lossy(part(&parts, "word/document.xml")).to_string();
+        assert!(doc.contains("r:embed=\"rIdImg1\"") && doc.contains("descr=\"Sales\""));
+        // Scaled down to the 6in text width, keeping the aspect ratio
+        assert!(doc.contains("cx=\"5486400\" cy=\"2743200\""));
+    }
+
+    #[test]
+    fn missing_images_and_html_are_reported() {
+        let markdown = "![Logo](https://example.com/logo.png)\n\n<div class=\"note\">\nHi\n</div>\n";
+        let file = CommentsFile::default();
+        let (parts, report) = markdown_to_docx_parts(markdown, &file, "t", None, None);
+
+        assert_eq!(report.images, 0);
+        assert_eq!(
+            report.replaced,
+            vec!["https://example.com/logo.png".to_string(), "HTML <div class=\"note\">".to_string()]
+        );
+        let doc = String::from_utf8_lossy(part(&parts, "word/document.xml")).to_string();
+        assert!(doc.contains("[Image: Logo]"));
+        assert!(!parts.iter().any(|(name, _)| name.starts_with("word/media/")));
+    }
+
+    #[test]
+    fn image_sizes_are_read_from_headers() {
+        assert_eq!(image_size(&png()), Some(("png", 1000, 500)));
+        assert_eq!(image_size(b"GIF89a\x20\x00\x10\x00"), Some(("gif", 32, 16)));
+        // SOI, an APP0 segment, then SOF0 with height 20 and width 40
+        let jpeg = b"\xFF\xD8\xFF\xE0\x00\x04\x00\x00\xFF\xC0\x00\x11\x08\x00\x14\x00\x28";
+        assert_eq!(image_size(jpeg), Some(("jpeg", 40, 20)));
+        assert_eq!(image_size(b"<svg/>"), None);
+    }
+}
@@ -0,0 +1,1109 @@
+use roxmltree::{Document, Node};
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fs::{self, File};
+use std::io::Read;
+use std::path::Path;
+use tauri::WebviewWindow;
+use zip::ZipArchive;
+use super::comment_storage;
+use super::comments::{self, CommentMessage, CommentThread, CommentsFile, Suggestion, SuggestionKind};
+use super::{watcher, workspace_file};
+
+const NS_W: &str = "http://schemas.openxmlformats.org/wordprocessingml/2006/main";
+const NS_R: &str = "http://schemas.openxmlformats.org/officeDocument/2006/relationships";
+const NS_W14: &str = "http://schemas.microsoft.com/office/word/2010/wordml";
+const NS_W15: &str = "http://schemas.microsoft.com/office/word/2012/wordml";
+const MONOSPACE_FONTS: &[&str] = &["consolas", "courier", "courier new", "menlo", "monaco", "source code pro"];
+
+#[derive(Serialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct DocxImportReport {
+    pub threads: usize,
+    pub replies: usize,
+    pub suggestions: usize,
+    /// Content that could not be carried over, one message per item
+    pub unmapped: Vec<String>,
+}
+
+pub struct DocxImport {
+    pub markdown: String,
+    pub comments: CommentsFile,
+    pub report: DocxImportReport,
+}
+
+struct WordComment {
+    id: String,
+    author: String,
+    date: Option<String>,
+    body: String,
+    /// Last paragraph's id, which commentsExtended refers to
+    para_id: Option<String>,
+}
+
+#[derive(Clone, Copy, Default, PartialEq)]
+struct Format {
+    bold: bool,
+    italic: bool,
+    strike: bool,
+    code: bool,
+}
+
+enum Inline {
+    Text { text: String, format: Format, link: Option<String> },
+    Break,
+    CommentStart(String),
+    CommentEnd(String),
+    Change { inserted: bool, text: String, author: String, date: Option<String> },
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum ParaKind {
+    Normal,
+    Heading(usize),
+    Item { ordered: bool, level: usize },
+    Quote,
+    Code,
+    Rule,
+}
+
+enum Block {
+    Para(ParaKind, String),
+    Table(Vec<Vec<String>>),
+}
+
+fn w<'a, 'input>(node: Node<'a, 'input>, name: &str) -> Option<Node<'a, 'input>> {
+    node.children().find(|c| c.has_tag_name((NS_W, name)))
+}
+
+fn w_attr<'a>(node: Node<'a, '_>, name: &str) -> Option<&'a str> {
+    node.attribute((NS_W, name))
+}
+
+/// A toggle property such as `<w:b/>` or `<w:b w:val="0"/>`.
+fn is_on(props: Option<Node>, name: &str) -> bool {
+    props
+        .and_then(|p| w(p, name))
+        .map(|n| !matches!(w_attr(n, "val"), Some("0" | "false" | "off" | "none")))
+        .unwrap_or(false)
+}
+
+fn parse_xml(xml: &str) -> Result<Document<'_>, String> {
+    Document::parse(xml).map_err(|e| format!("Invalid document XML: {}", e))
+}
+
+fn read_part(archive: &mut ZipArchive<File>, name: &str) -> Result<Option<String>, String> {
+    let mut entry = match archive.by_name(name) {
+        Ok(entry) => entry,
+        Err(_) => return Ok(None),
+    };
+    let mut content = String::new();
+    entry
+        .read_to_string(&mut content)
+        .map_err(|e| format!("Failed to read {}: {}", name, e))?;
+    Ok(Some(content))
+}
+
+/// Plain text of every `w:t` (or `w:delText`) below `node`, paragraphs
+/// separated by newlines.
+fn plain_text(node: Node) -> String {
+    let mut out = String::new();
+    for d in node.descendants() {
+        if d.has_tag_name((NS_W, "t")) || d.has_tag_name((NS_W, "delText")) {
+            out.push_str(d.text().unwrap_or(""));
+        } else if d.has_tag_name((NS_W, "tab")) {
+            out.push('\t');
+        } else if d.has_tag_name((NS_W, "p")) && !out.is_empty() {
+            out.push('\n');
+        }
+    }
+    out
+}
+
+fn parse_comments(xml: &str) -> Result<Vec<WordComment>, String> {
+    let doc = parse_xml(xml)?;
+    let mut comments = Vec::new();
+    for node in doc.root_element().children().filter(|n| n.has_tag_name((NS_W, "comment"))) {
+        let para_id = node
+            .children()
+            .filter(|p| p.has_tag_name((NS_W, "p")))
+            .filter_map(|p| p.attribute((NS_W14, "paraId")))
+            .next_back()
+            .map(str::to_string);
+        comments.push(WordComment {
+            id: w_attr(node, "id").unwrap_or_default().to_string(),
+            author: w_attr(node, "author").unwrap_or("Unknown").to_string(),
+            date: w_attr(node, "date").map(str::to_string),
+            body: plain_text(node).trim().to_string(),
+            para_id,
+        });
+    }
+    Ok(comments)
+}
+
+/// paraId → (parent paraId, done)
+fn parse_comments_extended(xml: &str) -> Result<HashMap<String, (Option<String>, bool)>, String> {
+    let doc = parse_xml(xml)?;
+    let mut map = HashMap::new();
+    for node in doc.descendants().filter(|n| n.has_tag_name((NS_W15, "commentEx"))) {
+        if let Some(para_id) = node.attribute((NS_W15, "paraId")) {
+            let parent = node.attribute((NS_W15, "paraIdParent")).map(str::to_string);
+            let done = matches!(node.attribute((NS_W15, "done")), Some("1" | "true"));
+            map.insert(para_id.to_string(), (parent, done));
+        }
+    }
+    Ok(map)
+}
+
+fn parse_relationships(xml: &str) -> Result<HashMap<String, String>, String> {
+    let doc = parse_xml(xml)?;
+    Ok(doc
+        .descendants()
+        .filter(|n| n.tag_name().name() == "Relationship")
+        .filter_map(|n| Some((n.attribute("Id")?.to_string(), n.attribute("Target")?.to_string())))
+        .collect())
+}
+
+/// styleId → lowercase style name
+fn parse_styles(xml: &str) -> Result<HashMap<String, String>, String> {
+    let doc = parse_xml(xml)?;
+    Ok(doc
+        .descendants()
+        .filter(|n| n.has_tag_name((NS_W, "style")))
+        .filter_map(|n| {
+            let id = w_attr(n, "styleId")?;
+            let name = w(n, "name").and_then(|m| w_attr(m, "val")).unwrap_or(id);
+            Some((id.to_string(), name.to_lowercase()))
+        })
+        .collect())
+}
+
+/// (numId, level) → whether the level is numbered rather than bulleted
+fn parse_numbering(xml: &str) -> Result<HashMap<(String, usize), bool>, String> {
+    let doc = parse_xml(xml)?;
+    let mut abstract_levels: HashMap<String, HashMap<usize, bool>> = HashMap::new();
+    for node in doc.descendants().filter(|n| n.has_tag_name((NS_W, "abstractNum"))) {
+        let Some(id) = w_attr(node, "abstractNumId") else {
+            continue;
+        };
+        let levels = node
+            .children()
+            .filter(|l| l.has_tag_name((NS_W, "lvl")))
+            .filter_map(|l| {
+                let level = w_attr(l, "ilvl")?.parse().ok()?;
+                let format = w(l, "numFmt").and_then(|f| w_attr(f, "val")).unwrap_or("bullet");
+                Some((level, format != "bullet" && format != "none"))
+            })
+            .collect();
+        abstract_levels.insert(id.to_string(), levels);
+    }
+    let mut map = HashMap::new();
+    for node in doc.descendants().filter(|n| n.has_tag_name((NS_W, "num"))) {
+        let (Some(num_id), Some(abstract_id)) = (
+            w_attr(node, "numId"),
+            w(node, "abstractNumId").and_then(|a| w_attr(a, "val")),
+        ) else {
+            continue;
+        };
+        if let Some(levels) = abstract_levels.get(abstract_id) {
+            for (level, ordered) in levels {
+                map.insert((num_id.to_string(), *level), *ordered);
+            }
+        }
+    }
+    Ok(map)
+}
+
+/// Backslash-escape characters markdown would otherwise interpret.
+fn escape_markdown(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for ch in text.chars() {
+        if matches!(ch, '\\' | '*' | '_' | '`' | '[' | ']' | '<' | '~') {
+            out.push('\\');
+        }
+        out.push(ch);
+    }
+    out
+}
+
+/// Escape a paragraph's first characters if they would start a block.
+fn escape_line_start(text: String) -> String {
+    if text.starts_with(['#', '>', '+', '-']) {
+        return format!("\\{}", text);
+    }
+    let digits = text.bytes().take_while(|b| b.is_ascii_digit()).count();
+    if digits > 0 && text[digits..].starts_with(". ") {
+        return format!("{}\\{}", &text[..digits], &text[digits..]);
+    }
+    text
+}
+
+fn code_span(text: &str) -> String {
+    let longest = text
+        .split(|c| c != '`')
+        .map(str::len)
+        .max()
+        .unwrap_or(0);
+    let fence = "`".repeat(longest + 1);
+    let pad = if text.starts_with('`') || text.ends_with('`') { " " } else { "" };
+    format!("{fence}{pad}{text}{pad}{fence}")
+}
+
+/// Renders one paragraph's inlines as markdown, carrying comment markers
+/// and formatting through.
+struct InlineWriter<'w, 'a> {
+    importer: &'w mut Importer<'a>,
+    out: String,
+    active: Format,
+    link: Option<String>,
+    /// Trailing whitespace held back so it lands outside closing delimiters
+    pending_ws: String,
+}
+
+impl InlineWriter<'_, '_> {
+    fn close_format(&mut self) {
+        let f = self.active;
+        if f.strike {
+            self.out.push_str("~~");
+        }
+        if f.italic {
+            self.out.push('*');
+        }
+        if f.bold {
+            self.out.push_str("**");
+        }
+        self.active = Format::default();
+    }
+
+    fn open_format(&mut self, f: Format) {
+        if f.bold {
+            self.out.push_str("**");
+        }
+        if f.italic {
+            self.out.push('*');
+        }
+        if f.strike {
+            self.out.push_str("~~");
+        }
+        self.active = Format { code: false, ..f };
+    }
+
+    fn set_link(&mut self, link: Option<&String>) {
+        if self.link.as_ref() == link {
+            return;
+        }
+        self.close_format();
+        if let Some(url) = self.link.take() {
+            self.out.push_str(&format!("]({})", url));
+        }
+        if let Some(url) = link {
+            self.flush_ws();
+            self.out.push('[');
+            self.link = Some(url.clone());
+        }
+    }
+
+    fn flush_ws(&mut self) {
+        self.out.push_str(&std::mem::take(&mut self.pending_ws));
+    }
+
+    fn text(&mut self, text: &str, format: Format, link: Option<&String>) {
+        self.set_link(link);
+        let core = text.trim_matches(' ');
+        if core.is_empty() {
+            self.pending_ws.push_str(text);
+            return;
+        }
+        let lead = &text[..text.len() - text.trim_start_matches(' ').len()];
+        let trail = &text[text.trim_end_matches(' ').len()..];
+        let wanted = Format { code: false, ..format };
+        if self.active != wanted || format.code {
+            self.close_format();
+        }
+        self.flush_ws();
+        self.out.push_str(lead);
+        if format.code {
+            self.open_format(wanted);
+            self.out.push_str(&code_span(core));
+        } else {
+            if self.active != wanted {
+                self.open_format(wanted);
+            }
+            self.out.push_str(&escape_markdown(core));
+        }
+        self.pending_ws.push_str(trail);
+        if let Some(mark) = self.importer.open_mark.as_mut() {
+            mark.has_text = true;
+        }
+    }
+
+    fn line_break(&mut self) {
+        self.close_format();
+        self.pending_ws.clear();
+        self.out.push_str("<br>");
+    }
+
+    fn comment_start(&mut self, word_id: &str) {
+        let Some(snippet) = self.importer.comment_label(word_id) else {
+            return;
+        };
+        if self.importer.is_reply(word_id) || self.importer.anchored.contains(word_id) {
+            return;
+        }
+        if self.importer.open_mark.is_some() {
+            self.importer.unanchored(word_id, &snippet, "overlaps another comment");
+            return;
+        }
+        self.set_link(None);
+        self.close_format();
+        self.flush_ws();
+        let start = self.out.len();
+        self.out.push_str("<mark>");
+        self.importer.open_mark = Some(OpenMark {
+            word_id: word_id.to_string(),
+            start,
+            has_text: false,
+        });
+    }
+
+    fn comment_end(&mut self, word_id: &str) {
+        if self.importer.open_mark.as_ref().map(|m| m.word_id.as_str()) != Some(word_id) {
+            return;
+        }
+        self.set_link(None);
+        self.close_format();
+        self.end_mark();
+    }
+
+    fn end_mark(&mut self) {
+        let Some(mark) = self.importer.open_mark.take() else {
+            return;
+        };
+        let label = self.importer.comment_label(&mark.word_id).unwrap_or_default();
+        if !mark.has_text {
+            self.out.truncate(mark.start);
+            self.importer.unanchored(&mark.word_id, &label, "has an empty range");
+            return;
+        }
+        let id = self.importer.thread_id(&mark.word_id);
+        self.out.push_str(&format!("</mark><sup>[{}]</sup>", id));
+        self.importer.anchored.insert(mark.word_id);
+    }
+
+    fn changes(&mut self, changes: &[(bool, String, String, Option<String>)]) {
+        self.set_link(None);
+        self.close_format();
+        self.flush_ws();
+        let mut i = 0;
+        while i < changes.len() {
+            let (inserted, text, author, date) = &changes[i];
+            // A deletion directly followed by an insertion by the same
+            // author is one replacement
+            let paired = !inserted
+                && changes
+                    .get(i + 1)
+                    .is_some_and(|(next_ins, _, next_author, _)| *next_ins && next_author == author);
+            // Suggestions hold their text as it reads inside the marker
+            let (deleted, inserted_text) = if paired {
+                (Some(escape_markdown(text)), Some(escape_markdown(&changes[i + 1].1)))
+            } else if *inserted {
+                (None, Some(escape_markdown(text)))
+            } else {
+                (Some(escape_markdown(text)), None)
+            };
+            let id = self.importer.add_suggestion(deleted.clone(), inserted_text.clone(), author, date);
+            if let Some(d) = &deleted {
+                self.out.push_str(&format!("<del>{}</del>", d));
+            }
+            if let Some(t) = &inserted_text {
+                self.out.push_str(&format!("<ins>{}</ins>", t));
+            }
+            self.out.push_str(&format!("<sup>[{}]</sup>", id));
+            i += if paired { 2 } else { 1 };
+        }
+    }
+
+    fn finish(mut self) -> String {
+        self.set_link(None);
+        self.close_format();
+        if let Some(mark) = &self.importer.open_mark {
+            let word_id = mark.word_id.clone();
+            let label = self.importer.comment_label(&word_id).unwrap_or_default();
+            self.end_mark();
+            if self.importer.anchored.contains(&word_id) {
+                self.importer.report.unmapped.push(format!(
+                    "Comment {} spans several paragraphs; anchored to the first one only",
+                    label
+                ));
+            }
+        }
+        self.out
+    }
+}
+
+struct OpenMark {
+    word_id: String,
+    start: usize,
+    has_text: bool,
+}
+
+struct Importer<'a> {
+    comments: &'a [WordComment],
+    /// Word comment id → root comment id, for replies
+    parents: HashMap<String, String>,
+    rels: HashMap<String, String>,
+    styles: HashMap<String, String>,
+    numbering: HashMap<(String, usize), bool>,
+
+    thread_ids: HashMap<String, String>,
+    anchored: HashSet<String>,
+    reported: HashSet<String>,
+    open_mark: Option<OpenMark>,
+    suggestions: BTreeMap<String, Suggestion>,
+    images: usize,
+    footnotes: usize,
+    report: DocxImportReport,
+}
+
+impl<'a> Importer<'a> {
+    fn comment(&self, word_id: &str) -> Option<&'a WordComment> {
+        self.comments.iter().find(|c| c.id == word_id)
+    }
+
+    /// `by Author ("first words")`, for the report
+    fn comment_label(&self, word_id: &str) -> Option<String> {
+        let c = self.comment(word_id)?;
+        let snippet: String = c.body.chars().take(40).collect();
+        let ellipsis = if c.body.chars().count() > 40 { "…" } else { "" };
+        Some(format!("by {} (\"{}{}\")", c.author, snippet, ellipsis))
+    }
+
+    fn is_reply(&self, word_id: &str) -> bool {
+        self.parents.contains_key(word_id)
+    }
+
+    fn unanchored(&mut self, word_id: &str, label: &str, reason: &str) {
+        if self.reported.insert(word_id.to_string()) {
+            self.report
+                .unmapped
+                .push(format!("Comment {} {}; imported without an anchor", label, reason));
+        }
+    }
+
+    fn thread_id(&mut self, word_id: &str) -> String {
+        let next = format!("c{}", self.thread_ids.len() + 1);
+        self.thread_ids.entry(word_id.to_string()).or_insert(next).clone()
+    }
+
+    fn add_suggestion(
+        &mut self,
+        deleted: Option<String>,
+        inserted: Option<String>,
+        author: &str,
+        date: &Option<String>,
+    ) -> String {
+        let id = format!("s{}", self.suggestions.len() + 1);
+        let kind = match (&deleted, &inserted) {
+            (Some(_), Some(_)) => SuggestionKind::Replace,
+            (Some(_), None) => SuggestionKind::Delete,
+            _ => SuggestionKind::Insert,
+        };
+        self.suggestions.insert(
+            id.clone(),
+            Suggestion {
+                kind,
+                author: author.to_string(),
+                timestamp: date.clone().unwrap_or_else(comments::iso_now),
+                deleted_text: deleted,
+                inserted_text: inserted,
+                extra: serde_json::Map::new(),
+            },
+        );
+        id
+    }
+
+    fn run(&mut self, run: Node, link: Option<&String>, out: &mut Vec<Inline>) {
+        let props = w(run, "rPr");
+        let style = props.and_then(|p| w(p, "rStyle")).and_then(|s| w_attr(s, "val"));
+        let font = props
+            .and_then(|p| w(p, "rFonts"))
+            .and_then(|f| w_attr(f, "ascii"))
+            .map(str::to_lowercase);
+        let format = Format {
+            bold: is_on(props, "b"),
+            italic: is_on(props, "i"),
+            strike: is_on(props, "strike") || is_on(props, "dstrike"),
+            code: style.is_some_and(|s| {
+                let s = s.to_lowercase();
+                s.contains("code") || s.contains("verbatim")
+            }) || font.is_some_and(|f| MONOSPACE_FONTS.contains(&f.as_str())),
+        };
+        for child in run.children().filter(Node::is_element) {
+            let text = match child.tag_name().name() {
+                "t" => child.text().unwrap_or("").to_string(),
+                "tab" => " ".to_string(),
+                "noBreakHyphen" => "-".to_string(),
+                "br" | "cr" => {
+                    if w_attr(child, "type").unwrap_or("textWrapping") == "textWrapping" {
+                        out.push(Inline::Break);
+                    }
+                    continue;
+                }
+                "drawing" | "pict" | "object" => {
+                    self.images += 1;
+                    continue;
+                }
+                "footnoteReference" | "endnoteReference" => {
+                    self.footnotes += 1;
+                    continue;
+                }
+                _ => continue,
+            };
+            match out.last_mut() {
+                Some(Inline::Text { text: prev, format: f, link: l })
+                    if *f == format && l.as_ref() == link =>
+                {
+                    prev.push_str(&text)
+                }
+                _ => out.push(Inline::Text {
+                    text,
+                    format,
+                    link: link.cloned(),
+                }),
+            }
+        }
+    }
+
+    fn collect(&mut self, node: Node, link: Option<&String>, out: &mut Vec<Inline>) {
+        for child in node.children().filter(Node::is_element) {
+            if child.tag_name().namespace() != Some(NS_W) {
+                continue;
+            }
+            match child.tag_name().name() {
+                "r" => self.run(child, link, out),
+                "hyperlink" => {
+                    let url = child
+                        .attribute((NS_R, "id"))
+                        .and_then(|id| self.rels.get(id).cloned())
+                        .or_else(|| w_attr(child, "anchor").map(|a| format!("#{}", a)));
+                    self.collect(child, url.as_ref().or(link), out);
+                }
+                "ins" | "del" | "moveTo" | "moveFrom" => {
+                    let text = plain_text(child);
+                    if !text.is_empty() {
+                        let inserted = matches!(child.tag_name().name(), "ins" | "moveTo");
+                        out.push(Inline::Change {
+                            inserted,
+                            text,
+                            author: w_attr(child, "author").unwrap_or("Unknown").to_string(),
+                            date: w_attr(child, "date").map(str::to_string),
+                        });
+                    }
+                }
+                "commentRangeStart" => {
+                    out.push(Inline::CommentStart(w_attr(child, "id").unwrap_or_default().to_string()))
+                }
+                "commentRangeEnd" => {
+                    out.push(Inline::CommentEnd(w_attr(child, "id").unwrap_or_default().to_string()))
+                }
+                "smartTag" | "sdt" | "sdtContent" | "fldSimple" | "customXml" => {
+                    self.collect(child, link, out)
+                }
+                _ => {}
+            }
+        }
+    }
+
+    fn para_kind(&self, para: Node) -> ParaKind {
+        let props = w(para, "pPr");
+        let style_id = props
+            .and_then(|p| w(p, "pStyle"))
+            .and_then(|s| w_attr(s, "val"))
+            .unwrap_or("");
+        let name = self
+            .styles
+            .get(style_id)
+            .cloned()
+            .unwrap_or_else(|| style_id.to_lowercase());
+        let compact = name.replace(' ', "");
+        if let Some(n) = compact.strip_prefix("heading").and_then(|n| n.parse::<usize>().ok()) {
+            return ParaKind::Heading(n.clamp(1, 6));
+        }
+        if compact == "title" {
+            return ParaKind::Heading(1);
+        }
+        if compact == "subtitle" {
+            return ParaKind::Heading(2);
+        }
+        if compact.contains("code") || compact.contains("preformatted") || compact.contains("sourcecode") {
+            return ParaKind::Code;
+        }
+        if let Some(num) = props.and_then(|p| w(p, "numPr")) {
+            let level = w(num, "ilvl")
+                .and_then(|l| w_attr(l, "val"))
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(0);
+            if let Some(num_id) = w(num, "numId").and_then(|n| w_attr(n, "val")) {
+                // numId 0 removes numbering inherited from the style
+                if num_id != "0" {
+                    let ordered = self
+                        .numbering
+                        .get(&(num_id.to_string(), level))
+                        .copied()
+                        .unwrap_or(false);
+                    return ParaKind::Item { ordered, level };
+                }
+            }
+        }
+        if compact.contains("quote") {
+            return ParaKind::Quote;
+        }
+        if props.and_then(|p| w(p, "pBdr")).and_then(|b| w(b, "bottom")).is_some() && plain_text(para).is_empty() {
+            return ParaKind::Rule;
+        }
+        ParaKind::Normal
+    }
+
+    fn paragraph(&mut self, para: Node) -> (ParaKind, String) {
+        let kind = self.para_kind(para);
+        let mut inlines = Vec::new();
+        self.collect(para, None, &mut inlines);
+
+        if kind == ParaKind::Code {
+            // Markers can't live in a code block
+            for inline in &inlines {
+                if let Inline::CommentStart(id) = inline {
+                    if let Some(label) = self.comment_label(id) {
+                        if !self.is_reply(id) {
+                            self.unanchored(id, &label, "is inside a code block");
+                        }
+                    }
+                }
+            }
+            let text: String = inlines
+                .iter()
+                .map(|i| match i {
+                    Inline::Text { text, .. } => text.as_str(),
+                    Inline::Break => "\n",
+                    _ => "",
+                })
+                .collect();
+            return (kind, text);
+        }
+
+        let mut writer = InlineWriter {
+            importer: self,
+            out: String::new(),
+            active: Format::default(),
+            link: None,
+            pending_ws: String::new(),
+        };
+        let mut i = 0;
+        while i < inlines.len() {
+            match &inlines[i] {
+                Inline::Text { text, format, link } => writer.text(text, *format, link.as_ref()),
+                Inline::Break => writer.line_break(),
+                Inline::CommentStart(id) => writer.comment_start(id),
+                Inline::CommentEnd(id) => writer.comment_end(id),
+                Inline::Change { .. } => {
+                    // Word splits one edit into a change per run; merge them
+                    let mut changes: Vec<(bool, String, String, Option<String>)> = Vec::new();
+                    while let Some(Inline::Change { inserted, text, author, date }) = inlines.get(i) {
+                        match changes.last_mut() {
+                            Some((ins, prev, a, _)) if ins == inserted && a == author => prev.push_str(text),
+                            _ => changes.push((*inserted, text.clone(), author.clone(), date.clone())),
+                        }
+                        i += 1;
+                    }
+                    writer.changes(&changes);
+                    continue;
+                }
+            }
+            i += 1;
+        }
+        let text = writer.finish();
+        (kind, escape_line_start(text.trim().to_string()))
+    }
+
+    fn table(&mut self, table: Node) -> Vec<Vec<String>> {
+        let mut rows = Vec::new();
+        for tr in table.children().filter(|n| n.has_tag_name((NS_W, "tr"))) {
+            let mut cells = Vec::new();
+            for tc in tr.children().filter(|n| n.has_tag_name((NS_W, "tc"))) {
+                let parts: Vec<String> = tc
+                    .children()
+                    .filter(|n| n.has_tag_name((NS_W, "p")))
+                    .map(|p| self.paragraph(p).1)
+                    .filter(|t| !t.is_empty())
+                    .collect();
+                cells.push(parts.join("<br>").replace('|', "\\|"));
+            }
+            rows.push(cells);
+        }
+        rows
+    }
+
+    fn blocks(&mut self, node: Node, out: &mut Vec<Block>) {
+        for child in node.children().filter(Node::is_element) {
+            if child.tag_name().namespace() != Some(NS_W) {
+                continue;
+            }
+            match child.tag_name().name() {
+                "p" => {
+                    let (kind, text) = self.paragraph(child);
+                    if !text.trim().is_empty() || matches!(kind, ParaKind::Code | ParaKind::Rule) {
+                        out.push(Block::Para(kind, text));
+                    }
+                }
+                "tbl" => out.push(Block::Table(self.table(child))),
+                "sdt" | "sdtContent" | "customXml" => self.blocks(child, out),
+                _ => {}
+            }
+        }
+    }
+}
+
+fn render_table(rows: &[Vec<String>]) -> String {
+    let width = rows.iter().map(Vec::len).max().unwrap_or(0).max(1);
+    let line = |cells: &[String]| {
+        let mut padded: Vec<String> = cells.to_vec();
+        padded.resize(width, String::new());
+        format!("| {} |", padded.join(" | "))
+    };
+    let mut lines = Vec::new();
+    let empty = Vec::new();
+    lines.push(line(rows.first().unwrap_or(&empty)));
+    lines.push(format!("|{}", " --- |".repeat(width)));
+    for row in rows.iter().skip(1) {
+        lines.push(line(row));
+    }
+    lines.join("\n")
+}
+
+fn render_blocks(blocks: &[Block]) -> String {
+    let mut out = String::new();
+    let mut prev: Option<ParaKind> = None;
+    // Content indent of each open list level
+    let mut indents: Vec<usize> = Vec::new();
+    let mut i = 0;
+    while i < blocks.len() {
+        let sep = match (&blocks[i], prev) {
+            (_, None) => "",
+            (Block::Para(ParaKind::Item { .. }, _), Some(ParaKind::Item { .. })) => "\n",
+            (Block::Para(ParaKind::Quote, _), Some(ParaKind::Quote)) => "\n>\n",
+            _ => "\n\n",
+        };
+        out.push_str(sep);
+        match &blocks[i] {
+            Block::Table(rows) => {
+                out.push_str(&render_table(rows));
+                prev = Some(ParaKind::Normal);
+            }
+            Block::Para(ParaKind::Code, _) => {
+                let mut lines = Vec::new();
+                while let Some(Block::Para(ParaKind::Code, text)) = blocks.get(i) {
+                    lines.push(text.as_str());
+                    i += 1;
+                }
+                let body = lines.join("\n");
+                let fence = if body.contains("```") { "~~~~" } else { "```" };
+                out.push_str(&format!("{fence}\n{body}\n{fence}"));
+                prev = Some(ParaKind::Code);
+                continue;
+            }
+            Block::Para(kind, text) => {
+                match kind {
+                    ParaKind::Heading(n) => out.push_str(&format!("{} {}", "#".repeat(*n), text)),
+                    ParaKind::Item { ordered, level } => {
+                        indents.truncate(*level);
+                        let indent: usize = indents.iter().sum();
+                        let marker = if *ordered { "1. " } else { "- " };
+                        // Word has no task lists; checkbox glyphs stand in
+                        let text = match text.split_once(' ') {
+                            Some(("☒" | "☑", rest)) => format!("[x] {}", rest),
+                            Some(("☐", rest)) => format!("[ ] {}", rest),
+                            _ => text.clone(),
+                        };
+                        out.push_str(&format!("{}{}{}", " ".repeat(indent), marker, text));
+                        // Skipped levels still need their indent
+                        while indents.len() < *level {
+                            indents.push(2);
+                        }
+                        indents.push(marker.len());
+                    }
+                    ParaKind::Quote => out.push_str(&format!("> {}", text)),
+                    ParaKind::Rule => out.push_str("---"),
+                    _ => out.push_str(text),
+                }
+                if !matches!(kind, ParaKind::Item { .. }) {
+                    indents.clear();
+                }
+                prev = Some(*kind);
+            }
+        }
+        i += 1;
+    }
+    out.push('\n');
+    out
+}
+
+/// Convert an opened .docx archive to markdown with Gutter comment markers,
+/// threads and suggestions.
+pub fn convert_docx(archive: &mut ZipArchive<File>) -> Result<DocxImport, String> {
+    let document = read_part(archive, "word/document.xml")?
+        .ok_or_else(|| "Not a Word document: word/document.xml is missing".to_string())?;
+    let word_comments = match read_part(archive, "word/comments.xml")? {
+        Some(xml) => parse_comments(&xml)?,
+        None => Vec::new(),
+    };
+    let extended = match read_part(archive, "word/commentsExtended.xml")? {
+        Some(xml) => parse_comments_extended(&xml)?,
+        None => HashMap::new(),
+    };
+    let rels = match read_part(archive, "word/_rels/document.xml.rels")? {
+        Some(xml) => parse_relationships(&xml)?,
+        None => HashMap::new(),
+    };
+    let styles = match read_part(archive, "word/styles.xml")? {
+        Some(xml) => parse_styles(&xml)?,
+        None => HashMap::new(),
+    };
+    let numbering = match read_part(archive, "word/numbering.xml")? {
+        Some(xml) => parse_numbering(&xml)?,
+        None => HashMap::new(),
+    };
+
+    // Replies point at their parent's paragraph id; resolve each to the
+    // thread's root comment
+    let by_para: HashMap<&str, &str> = word_comments
+        .iter()
+        .filter_map(|c| Some((c.para_id.as_deref()?, c.id.as_str())))
+        .collect();
+    let mut parents = HashMap::new();
+    for c in &word_comments {
+        let mut root = None;
+        let mut para = c.para_id.as_deref();
+        let mut hops = 0;
+        while let Some(parent) = para.and_then(|p| extended.get(p)).and_then(|(p, _)| p.as_deref()) {
+            root = by_para.get(parent).copied();
+            para = Some(parent);
+            hops += 1;
+            if hops > word_comments.len() {
+                break;
+            }
+        }
+        if let Some(root) = root {
+            if root != c.id {
+                parents.insert(c.id.clone(), root.to_string());
+            }
+        }
+    }
+
+    let mut importer = Importer {
+        comments: &word_comments,
+        parents,
+        rels,
+        styles,
+        numbering,
+        thread_ids: HashMap::new(),
+        anchored: HashSet::new(),
+        reported: HashSet::new(),
+        open_mark: None,
+        suggestions: BTreeMap::new(),
+        images: 0,
+        footnotes: 0,
+        report: DocxImportReport::default(),
+    };
+
+    let doc = parse_xml(&document)?;
+    let body = doc
+        .root_element()
+        .children()
+        .find(|n| n.has_tag_name((NS_W, "body")))
+        .ok_or_else(|| "Not a Word document: no body".to_string())?;
+    let mut blocks = Vec::new();
+    importer.blocks(body, &mut blocks);
+    let markdown = render_blocks(&blocks);
+
+    let mut file = CommentsFile::default();
+    let roots: Vec<&WordComment> = word_comments.iter().filter(|c| !importer.is_reply(&c.id)).collect();
+    for root in roots {
+        if !importer.anchored.contains(&root.id) {
+            let label = importer.comment_label(&root.id).unwrap_or_default();
+            importer.unanchored(&root.id, &label, "has no range in the document");
+        }
+        let id = importer.thread_id(&root.id);
+        let created = root.date.clone().unwrap_or_else(comments::iso_now);
+        let mut thread = CommentThread::new(created.clone());
+        let mut replies: Vec<&WordComment> = word_comments
+            .iter()
+            .filter(|c| importer.parents.get(&c.id) == Some(&root.id))
+            .collect();
+        replies.sort_by_key(|c| c.date.as_deref());
+        for c in std::iter::once(root).chain(replies) {
+            thread.thread.push(CommentMessage {
+                id: comments::new_message_id(),
+                author: c.author.clone(),
+                timestamp: c.date.clone().unwrap_or_else(|| created.clone()),
+                body: c.body.clone(),
+            });
+        }
+        importer.report.replies += thread.thread.len() - 1;
+        // Word doesn't record who marked a thread done, or when
+        thread.resolved = root
+            .para_id
+            .as_deref()
+            .and_then(|p| extended.get(p))
+            .is_some_and(|(_, done)| *done);
+        file.comments.insert(id, thread);
+    }
+    importer.report.threads = file.comments.len();
+    importer.report.suggestions = importer.suggestions.len();
+    file.suggestions = std::mem::take(&mut importer.suggestions);
+
+    if importer.images > 0 {
+        importer
+            .report
+            .unmapped
+            .push(format!("{} image(s) were not imported", importer.images));
+    }
+    if importer.footnotes > 0 {
+        importer
+            .report
+            .unmapped
+            .push(format!("{} footnote(s) were not imported", importer.footnotes));
+    }
+
+    Ok(DocxImport {
+        markdown,
+        comments: file,
+        report: importer.report,
+    })
+}
+
+/// Import a Word document as the markdown note `output`. Word comments
+/// become Gutter threads with their replies, and tracked changes become
+/// suggestions; the report lists whatever could not be mapped. An existing
+/// note or comments file at `output` is only replaced with `overwrite`.
+#[tauri::command]
+pub fn import_docx(
+    window: WebviewWindow,
+    path: String,
+    output: String,
+    workspace: Option<String>,
+    overwrite: Option<bool>,
+) -> Result<DocxImportReport, String> {
+    let workspace = workspace_file::owning_root(&window, workspace, &output);
+    let md_path = Path::new(&output);
+    let store = comment_storage::store_for(workspace.as_deref());
+    if !overwrite.unwrap_or(false) && (md_path.exists() || store.read(md_path)?.is_some()) {
+        return Err("File already exists".to_string());
+    }
+
+    let file = File::open(&path).map_err(|e| format!("Failed to open document: {}", e))?;
+    let mut archive = ZipArchive::new(file).map_err(|e| format!("Not a Word document: {}", e))?;
+    let imported = convert_docx(&mut archive)?;
+
+    // Write the note first: the embedded store appends to it
+    watcher::mark_write(&window, &output);
+    fs::write(md_path, &imported.markdown).map_err(|e| format!("Failed to write file: {}", e))?;
+    if imported.comments.comments.is_empty() && imported.comments.suggestions.is_empty() {
+        if store.read(md_path)?.is_some() {
+            watcher::mark_write(&window, &store.location(md_path).to_string_lossy());
+            store.delete(md_path)?;
+        }
+    } else {
+        comments::save_comments_file(&window, store.as_ref(), md_path, &imported.comments)?;
+    }
+    Ok(imported.report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::super::docx_export::markdown_to_docx_parts;
+    use std::io::Write;
+
+    /// Export `markdown` with `file`'s threads and import the result.
+    fn round_trip(name: &str, markdown: &str, file: &CommentsFile) -> DocxImport {
+        let (parts, _) = markdown_to_docx_parts(markdown, file, "t", None, None);
+        let path = std::env::temp_dir()
+            .join(format!("gutter-import-{}-{}.docx", name, std::process::id()));
+        let mut zip = zip::ZipWriter::new(File::create(&path).unwrap());
+        for (part, data) in parts {
+            zip.start_file(part, zip::write::SimpleFileOptions::default()).unwrap();
+            zip.write_all(&data).unwrap();
+        }
+        zip.finish().unwrap();
+        let mut archive = ZipArchive::new(File::open(&path).unwrap()).unwrap();
+        let imported = convert_docx(&mut archive).unwrap();
+        fs::remove_file(&path).ok();
+        imported
+    }
+
+    fn message(author: &str, body: &str, timestamp: &str) -> CommentMessage {
+        CommentMessage {
+            id: comments::new_message_id(),
+            author: author.to_string(),
+            timestamp: timestamp.to_string(),
+            body: body.to_string(),
+        }
+    }
+
+    #[test]
+    fn blocks_and_formatting_map_to_markdown() {
+        let markdown = "# Title\n\nSome **bold** and *italic* text.\n\n- one\n- two\n\n1. first\n2. second\n";
+        let imported = round_trip("blocks", markdown, &CommentsFile::default());
+        // Every ordered item is written as `1.`, and a new list type starts
+        // a list without a blank line
+        assert_eq!(
+            imported.markdown,
+            "# Title\n\nSome **bold** and *italic* text.\n\n- one\n- two\n1. first\n1. second\n"
+        );
+        assert!(imported.report.unmapped.is_empty());
+    }
+
+    #[test]
+    fn comments_become_threads_with_replies() {
+        let mut file = CommentsFile::default();
+        let mut thread = CommentThread::new("2024-05-01T09:00:00Z".to_string());
+        thread.thread.push(message("Ann", "Too vague", "2024-05-01T09:00:00Z"));
+        thread.thread.push(message("Bo", "Agreed", "2024-05-01T10:00:00Z"));
+        thread.resolved = true;
+        file.comments.insert("c1".to_string(), thread);
+
+        let imported = round_trip("comments", "A <mark>vague claim</mark><sup>[c1]</sup>.\n", &file);
+        assert_eq!(imported.markdown, "A <mark>vague claim</mark><sup>[c1]</sup>.\n");
+        let thread = &imported.comments.comments["c1"];
+        let bodies: Vec<(&str, &str)> =
+            thread.thread.iter().map(|m| (m.author.as_str(), m.body.as_str())).collect();
+        assert_eq!(bodies, vec![("Ann", "Too vague"), ("Bo", "Agreed")]);
+        assert!(thread.resolved);
+        assert_eq!((imported.report.threads, imported.report.replies), (1, 1));
+    }
+
+    #[test]
+    fn tracked_changes_keep_the_marker_text() {
+        let mut file = CommentsFile::default();
+        file.suggestions.insert(
+            "s1".to_string(),
+            Suggestion {
+                kind: SuggestionKind::Replace,
+                author: "Ann".to_string(),
+                timestamp: "2024-05-01T09:00:00Z".to_string(),
+                deleted_text: Some("a\\*b".to_string()),
+                inserted_text: Some("[c]".to_string()),
+                extra: serde_json::Map::new(),
+            },
+        );
+
+        let markdown = "Swap <del>a\\*b</del><ins>\\[c\\]</ins><sup>[s1]</sup> here.\n";
+        let imported = round_trip("changes", markdown, &file);
+        assert_eq!(imported.markdown, markdown);
+        let marker = &super::super::suggestions::extract_suggestions(&imported.markdown)[0];
+        let suggestion = &imported.comments.suggestions[&marker.id];
+        assert_eq!(suggestion.deleted_text, marker.deleted);
+        assert_eq!(suggestion.inserted_text, marker.inserted);
+        assert_eq!(suggestion.author, "Ann");
+    }
+}
@@ -1,17 +1,29 @@
 use std::fs;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use std::process::Command;
-use tauri::AppHandle;
-use super::watcher;
+use tauri::WebviewWindow;
+use super::{comment_storage, ordering, settings, watcher, workspace_file};
 
 #[tauri::command]
 pub fn read_file(path: String) -> Result<String, String> {
-    fs::read_to_string(&path).map_err(|e| format!("Failed to read file: {}", e))
+    let text = fs::read_to_string(&path).map_err(|e| format!("Failed to read file: {}", e))?;
+    // Embedded comment threads are managed by the comments commands, never the editor
+    Ok(comment_storage::split_embedded(&text).0.to_string())
 }
 
 #[tauri::command]
-pub fn write_file(app: AppHandle, path: String, content: String) -> Result<(), String> {
-    watcher::mark_write(&app, &path);
+pub fn write_file(window: WebviewWindow, path: String, content: String) -> Result<(), String> {
+    watcher::mark_write(&window, &path);
+    // Keep an embedded comments block the editor content doesn't know about
+    let content = match fs::read_to_string(&path) {
+        Ok(existing) => match comment_storage::split_embedded(&existing).1 {
+            Some(json) if comment_storage::split_embedded(&content).1.is_none() => {
+                comment_storage::append_embedded(&content, json)
+            }
+            _ => content,
+        },
+        Err(_) => content,
+    };
     fs::write(&path, &content).map_err(|e| format!("Failed to write file: {}", e))
 }
 
@@ -45,16 +57,26 @@ pub fn create_directory(path: String) -> Result<(), String> {
     fs::create_dir_all(&path).map_err(|e| format!("Failed to create directory: {}", e))
 }
 
+/// Rename or move a file or folder. With `workspace`, its manual
+/// ordering follows the entry.
 #[tauri::command]
-pub fn rename_path(old_path: String, new_path: String) -> Result<(), String> {
+pub fn rename_path(old_path: String, new_path: String, workspace: Option<String>) -> Result<(), String> {
+    move_path(&old_path, &new_path)?;
+    match workspace {
+        Some(ws) => ordering::rename_entry(Path::new(&ws), Path::new(&old_path), Path::new(&new_path)),
+        None => Ok(()),
+    }
+}
+
+fn move_path(old_path: &str, new_path: &str) -> Result<(), String> {
     // Try simple rename first
-    if let Ok(_) = fs::rename(&old_path, &new_path) {
+    if let Ok(_) = fs::rename(old_path, new_path) {
         return Ok(());
     }
 
     // Fallback for cross-device/partition moves
-    let source = Path::new(&old_path);
-    let dest = Path::new(&new_path);
+    let source = Path::new(old_path);
+    let dest = Path::new(new_path);
 
     if source.is_dir() {
         copy_dir_recursive(source, dest).map_err(|e| format!("Failed to copy directory: {}", e))?;
@@ -81,41 +103,70 @@ fn copy_dir_recursive(src: &Path, dst: &Path) -> std::io::Result<()> {
     Ok(())
 }
 
+/// Delete a file or folder. With `workspace`, it also leaves the manual
+/// ordering.
 #[tauri::command]
-pub fn delete_path(path: String) -> Result<(), String> {
+pub fn delete_path(path: String, workspace: Option<String>) -> Result<(), String> {
     let p = Path::new(&path);
-    if !p.exists() {
-        return Ok(());
-    }
     if p.is_dir() {
-        fs::remove_dir_all(&path).map_err(|e| format!("Failed to delete directory: {}", e))
-    } else {
-        fs::remove_file(&path).map_err(|e| format!("Failed to delete file: {}", e))
+        fs::remove_dir_all(&path).map_err(|e| format!("Failed to delete directory: {}", e))?;
+    } else if p.exists() {
+        fs::remove_file(&path).map_err(|e| format!("Failed to delete file: {}", e))?;
+    }
+    match workspace {
+        Some(ws) => ordering::remove_entry(Path::new(&ws), p),
+        None => Ok(()),
     }
 }
 
-#[tauri::command]
-pub fn save_image(dir_path: String, filename: String, data: Vec<u8>) -> Result<String, String> {
-    let assets_dir = Path::new(&dir_path).join("assets");
-    if !assets_dir.exists() {
-        fs::create_dir_all(&assets_dir)
-            .map_err(|e| format!("Failed to create assets directory: {}", e))?;
+/// The attachment folder for notes in `dir_path`, created if needed, and
+/// the markdown link prefix for files in it.
+fn attachment_dir(dir_path: &str, workspace: Option<&str>) -> Result<(PathBuf, String), String> {
+    let folder = settings::attachment_folder(workspace.map(Path::new));
+    let dir = Path::new(dir_path).join(&folder);
+    if !dir.exists() {
+        fs::create_dir_all(&dir)
+            .map_err(|e| format!("Failed to create attachment directory: {}", e))?;
     }
-    let file_path = assets_dir.join(&filename);
+    let folder = folder.trim_end_matches('/');
+    let link = if Path::new(folder).is_absolute() || folder.starts_with('.') {
+        folder.to_string()
+    } else {
+        format!("./{}", folder)
+    };
+    Ok((dir, link))
+}
+
+/// Save pasted image data to the attachment folder (`attachmentFolder`
+/// setting) and return its relative link.
+#[tauri::command]
+pub fn save_image(
+    window: WebviewWindow,
+    dir_path: String,
+    filename: String,
+    data: Vec<u8>,
+    workspace: Option<String>,
+) -> Result<String, String> {
+    let workspace = workspace_file::owning_root(&window, workspace, &dir_path);
+    let (dir, link) = attachment_dir(&dir_path, workspace.as_deref())?;
+    let file_path = dir.join(&filename);
     fs::write(&file_path, &data).map_err(|e| format!("Failed to save image: {}", e))?;
-    Ok(format!("./assets/{}", filename))
+    Ok(format!("{}/{}", link, filename))
 }
 
 #[tauri::command]
-pub fn copy_image(source: String, dir_path: String, filename: String) -> Result<String, String> {
-    let assets_dir = Path::new(&dir_path).join("assets");
-    if !assets_dir.exists() {
-        fs::create_dir_all(&assets_dir)
-            .map_err(|e| format!("Failed to create assets directory: {}", e))?;
-    }
-    let dest = assets_dir.join(&filename);
+pub fn copy_image(
+    window: WebviewWindow,
+    source: String,
+    dir_path: String,
+    filename: String,
+    workspace: Option<String>,
+) -> Result<String, String> {
+    let workspace = workspace_file::owning_root(&window, workspace, &dir_path);
+    let (dir, link) = attachment_dir(&dir_path, workspace.as_deref())?;
+    let dest = dir.join(&filename);
     fs::copy(&source, &dest).map_err(|e| format!("Failed to copy image: {}", e))?;
-    Ok(format!("./assets/{}", filename))
+    Ok(format!("{}/{}", link, filename))
 }
 
 #[tauri::command]
@@ -171,10 +222,8 @@ pub fn read_file_data_url(path: String) -> Result<String, String> {
     Ok(format!("data:{};base64,{}", mime, b64))
 }
 
+/// The file this window was opened for, if it hasn't been taken yet.
 #[tauri::command]
-pub fn get_open_file_path(app: tauri::AppHandle) -> Option<String> {
-    use tauri::Manager;
-    let state = app.state::<crate::OpenFileState>();
-    let path = state.path.lock().unwrap().take();
-    path
+pub fn get_open_file_path(window: WebviewWindow) -> Option<String> {
+    super::windows::take_pending(&window)
 }
@@ -0,0 +1,327 @@
+use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
+use std::collections::HashMap;
+use std::fs;
+use std::iter::Peekable;
+use std::path::{Path, PathBuf};
+use std::str::Chars;
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+use tauri::Manager;
+use super::comment_storage::{self, CommentStore};
+use super::comments;
+
+/// Tree metadata for one entry. Directories only get size and times.
+#[derive(Serialize, Clone, Default)]
+pub struct FileMeta {
+    pub size: u64,
+    /// Milliseconds since the epoch; `None` where the filesystem has no
+    /// creation time
+    pub created: Option<u64>,
+    pub modified: Option<u64>,
+    /// Frontmatter `title`, else the first `#` heading
+    pub title: Option<String>,
+    pub tags: Vec<String>,
+    pub word_count: usize,
+    pub comment_count: usize,
+    pub unresolved_comment_count: usize,
+}
+
+/// Tree order. Directories come first except under manual order.
+#[derive(Deserialize, Clone, Copy, Default, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum SortKey {
+    /// `.gutter/order.json` where a folder has one, else by name
+    #[default]
+    Manual,
+    Name,
+    Modified,
+    Created,
+    Title,
+    /// Name order with digit runs compared by value, so `2` < `10`
+    Natural,
+}
+
+/// What a cached entry was computed from: the note's mtime and size, and
+/// the mtime of wherever its comments live.
+#[derive(PartialEq, Clone, Copy)]
+struct Stamp {
+    modified: Option<SystemTime>,
+    len: u64,
+    comments_modified: Option<SystemTime>,
+}
+
+pub struct MetaCache {
+    entries: Mutex<HashMap<PathBuf, (Stamp, FileMeta)>>,
+}
+
+pub fn init(app: &tauri::App) {
+    app.manage(MetaCache {
+        entries: Mutex::new(HashMap::new()),
+    });
+}
+
+fn millis(time: std::io::Result<SystemTime>) -> Option<u64> {
+    let since = time.ok()?.duration_since(UNIX_EPOCH).ok()?;
+    Some(since.as_millis() as u64)
+}
+
+pub fn is_markdown(path: &Path) -> bool {
+    matches!(path.extension().and_then(|e| e.to_str()), Some("md" | "markdown"))
+}
+
+impl MetaCache {
+    /// Metadata for `path`, re-reading the note only when it or its
+    /// comments changed since the last call.
+    pub fn get(&self, path: &Path, store: &dyn CommentStore) -> Option<FileMeta> {
+        let stat = fs::metadata(path).ok()?;
+        let mut base = FileMeta {
+            size: if stat.is_dir() { 0 } else { stat.len() },
+            created: millis(stat.created()),
+            modified: millis(stat.modified()),
+            ..FileMeta::default()
+        };
+        if stat.is_dir() || !is_markdown(path) {
+            return Some(base);
+        }
+
+        let stamp = Stamp {
+            modified: stat.modified().ok(),
+            len: stat.len(),
+            comments_modified: fs::metadata(store.location(path)).and_then(|m| m.modified()).ok(),
+        };
+        if let Some((cached, meta)) = self.entries.lock().unwrap().get(path) {
+            if *cached == stamp {
+                return Some(meta.clone());
+            }
+        }
+
+        if let Ok(text) = fs::read_to_string(path) {
+            let body = comment_storage::split_embedded(&text).0;
+            let (front, content) = split_frontmatter(body);
+            if let Some(front) = front {
+                base.title = frontmatter_value(front, "title");
+                base.tags = frontmatter_list(front, "tags");
+            }
+            if base.title.is_none() {
+                base.title = first_heading(content);
+            }
+            base.word_count = word_count(content);
+        }
+        if let Ok(Some(file)) = comments::load_comments_file(store, path) {
+            base.comment_count = file.comments.len();
+            base.unresolved_comment_count = file.comments.values().filter(|t| !t.resolved).count();
+        }
+        self.entries
+            .lock()
+            .unwrap()
+            .insert(path.to_path_buf(), (stamp, base.clone()));
+        Some(base)
+    }
+
+    /// Drop what's cached for `path` and anything below it, once it's gone.
+    pub fn forget(&self, path: &Path) {
+        self.entries.lock().unwrap().retain(|p, _| !p.starts_with(path));
+    }
+}
+
+/// YAML frontmatter between leading `---` lines, and the rest.
+pub fn split_frontmatter(text: &str) -> (Option<&str>, &str) {
+    let Some(rest) = text.strip_prefix("---\n").or_else(|| text.strip_prefix("---\r\n")) else {
+        return (None, text);
+    };
+    let mut offset = 0;
+    for line in rest.split_inclusive('\n') {
+        if line.trim_end() == "---" {
+            return (Some(&rest[..offset]), &rest[offset + line.len()..]);
+        }
+        offset += line.len();
+    }
+    (None, text)
+}
+
+fn unquote(value: &str) -> &str {
+    let value = value.trim();
+    for q in ['"', '\''] {
+        if let Some(inner) = value.strip_prefix(q).and_then(|v| v.strip_suffix(q)) {
+            return inner;
+        }
+    }
+    value
+}
+
+/// A top-level scalar like `title: My note`.
+pub fn frontmatter_value(front: &str, key: &str) -> Option<String> {
+    front.lines().find_map(|line| {
+        let value = line.strip_prefix(key)?.strip_prefix(':')?;
+        let value = unquote(value);
+        (!value.is_empty()).then(|| value.to_string())
+    })
+}
+
+/// A top-level list, either `tags: [a, b]` or one `- item` per line.
+pub fn frontmatter_list(front: &str, key: &str) -> Vec<String> {
+    let mut lines = front.lines();
+    while let Some(line) = lines.next() {
+        let Some(value) = line.strip_prefix(key).and_then(|l| l.strip_prefix(':')) else {
+            continue;
+        };
+        let value = value.trim();
+        if let Some(inline) = value.strip_prefix('[').and_then(|v| v.strip_suffix(']')) {
+            return inline
+                .split(',')
+                .map(unquote)
+                .filter(|t| !t.is_empty())
+                .map(str::to_string)
+                .collect();
+        }
+        if !value.is_empty() {
+            return vec![unquote(value).to_string()];
+        }
+        return lines
+            .map_while(|l| l.trim_start().strip_prefix("- ").map(unquote))
+            .filter(|t| !t.is_empty())
+            .map(str::to_string)
+            .collect();
+    }
+    Vec::new()
+}
+
+fn first_heading(content: &str) -> Option<String> {
+    content.lines().find_map(|line| {
+        let title = line.strip_prefix("# ")?.trim();
+        (!title.is_empty()).then(|| title.to_string())
+    })
+}
+
+/// Words outside HTML tags and comment/suggestion ids.
+fn word_count(content: &str) -> usize {
+    let mut text = String::with_capacity(content.len());
+    let mut chars = content.chars().peekable();
+    while let Some(ch) = chars.next() {
+        let opens_tag = ch == '<' && chars.peek().is_some_and(|c| c.is_ascii_alphabetic() || *c == '/');
+        if opens_tag {
+            chars.by_ref().find(|c| *c == '>');
+            text.push(' ');
+        } else {
+            text.push(ch);
+        }
+    }
+    text.split_whitespace()
+        .filter(|w| w.chars().any(char::is_alphanumeric))
+        .filter(|w| !is_marker_id(w))
+        .count()
+}
+
+/// `[c12]` or `[s3]` left over from a `<sup>` marker
+fn is_marker_id(word: &str) -> bool {
+    word.strip_prefix('[')
+        .and_then(|w| w.strip_suffix(']'))
+        .and_then(|w| w.strip_prefix(['c', 's']))
+        .is_some_and(|n| !n.is_empty() && n.bytes().all(|b| b.is_ascii_digit()))
+}
+
+fn take_digits(chars: &mut Peekable<Chars>) -> String {
+    let mut digits = String::new();
+    while let Some(d) = chars.next_if(char::is_ascii_digit) {
+        digits.push(d);
+    }
+    digits
+}
+
+/// Compare with runs of digits taken as numbers, case-insensitively.
+pub fn natural_cmp(a: &str, b: &str) -> Ordering {
+    let (a, b) = (a.to_lowercase(), b.to_lowercase());
+    let mut a = a.chars().peekable();
+    let mut b = b.chars().peekable();
+    loop {
+        match (a.peek().copied(), b.peek().copied()) {
+            (None, None) => return Ordering::Equal,
+            (None, Some(_)) => return Ordering::Less,
+            (Some(_), None) => return Ordering::Greater,
+            (Some(x), Some(y)) if x.is_ascii_digit() && y.is_ascii_digit() => {
+                let (da, db) = (take_digits(&mut a), take_digits(&mut b));
+                let (ta, tb) = (da.trim_start_matches('0'), db.trim_start_matches('0'));
+                // By value, then fewer leading zeros first
+                let order = ta
+                    .len()
+                    .cmp(&tb.len())
+                    .then_with(|| ta.cmp(tb))
+                    .then_with(|| da.len().cmp(&db.len()));
+                if order != Ordering::Equal {
+                    return order;
+                }
+            }
+            (Some(x), Some(y)) => {
+                if x != y {
+                    return x.cmp(&y);
+                }
+                a.next();
+                b.next();
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn natural_order() {
+        for (a, b) in [
+            ("file2", "file10"),
+            ("file1", "file01"),
+            ("file01", "file2"),
+            ("Chapter 9", "chapter 10"),
+            ("notes", "notes 1"),
+            ("a10b2", "a10b10"),
+        ] {
+            assert_eq!(natural_cmp(a, b), Ordering::Less, "{} < {}", a, b);
+            assert_eq!(natural_cmp(b, a), Ordering::Greater, "{} > {}", b, a);
+        }
+        assert_eq!(natural_cmp("File10", "file10"), Ordering::Equal);
+    }
+
+    #[test]
+    fn frontmatter_is_split_off() {
+        for (text, front, body) in [
+            ("---\ntitle: A\n---\nBody\n", Some("title: A\n"), "Body\n"),
+            ("---\r\ntitle: A\r\n---\r\nBody\r\n", Some("title: A\r\n"), "Body\r\n"),
+            ("---\ntitle: A\nBody with no closing line\n", None, "---\ntitle: A\nBody with no closing line\n"),
+            ("No frontmatter\n---\n", None, "No frontmatter\n---\n"),
+            ("---\n---\nEmpty\n", Some(""), "Empty\n"),
+        ] {
+            assert_eq!(split_frontmatter(text), (front, body), "{:?}", text);
+        }
+        let (front, _) = split_frontmatter("---\r\ntitle: \"Quoted\"\r\n---\r\n");
+        assert_eq!(frontmatter_value(front.unwrap(), "title").as_deref(), Some("Quoted"));
+    }
+
+    #[test]
+    fn tags_as_lists() {
+        for (front, tags) in [
+            ("tags: [a, \"b c\", 'd']\n", vec!["a", "b c", "d"]),
+            ("tags:\n  - a\n  - 'b'\ntitle: x\n", vec!["a", "b"]),
+            ("tags: single\n", vec!["single"]),
+            ("tags: []\n", vec![]),
+            ("title: none\n", vec![]),
+            ("tagsx: [a]\n", vec![]),
+        ] {
+            assert_eq!(frontmatter_list(front, "tags"), tags, "{:?}", front);
+        }
+    }
+
+    #[test]
+    fn word_counts_skip_frontmatter_and_markup() {
+        for (text, words) in [
+            ("---\ntitle: Many words in a title\ntags: [a, b]\n---\nJust three words\n", 3),
+            ("Some <mark>marked text</mark><sup>[c12]</sup> here.\n", 4),
+            ("Kept <del>old</del><ins>new</ins><sup>[s3]</sup> - and a dash\n", 6),
+            ("# Heading\n\n1 < 2 is true\n", 5),
+            ("", 0),
+        ] {
+            assert_eq!(word_count(split_frontmatter(text).1), words, "{:?}", text);
+        }
+    }
+}
@@ -4,6 +4,7 @@ use std::fs;
 use std::path::PathBuf;
 use std::process::Command;
 use std::time::{SystemTime, UNIX_EPOCH};
+use super::app_dirs;
 
 /// Create a Command that hides the console window on Windows.
 fn git_command() -> Command {
@@ -42,9 +43,7 @@ fn history_dir(file_path: &str) -> PathBuf {
     let mut hasher = Sha256::new();
     hasher.update(file_path.as_bytes());
     let hash = format!("{:x}", hasher.finalize());
-    dirs::home_dir()
-        .unwrap_or_else(|| PathBuf::from("."))
-        .join(".gutter")
+    app_dirs::state_dir()
         .join("history")
         .join(&hash[..16]) // Use first 16 chars for shorter directory names
 }
@@ -0,0 +1,190 @@
+use ignore::gitignore::{Gitignore, GitignoreBuilder};
+use ignore::Match;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use super::settings;
+
+/// Workspace-specific ignore file, same syntax as `.gitignore`. Its rules
+/// take precedence over `.gitignore` in the same folder.
+pub const GUTTERIGNORE: &str = ".gutterignore";
+
+/// Whether a change to `path` invalidates the ignore rules.
+pub fn is_ignore_file(path: &Path) -> bool {
+    let name = path.file_name().and_then(|n| n.to_str()).unwrap_or("");
+    name == ".gitignore"
+        || name == GUTTERIGNORE
+        || path.ends_with(".git/info/exclude")
+        || settings::is_workspace_settings(path)
+}
+
+/// Gitignore-style rules for a workspace: global git excludes,
+/// `.git/info/exclude`, the `ignorePatterns` setting, and every
+/// `.gitignore` / `.gutterignore` from the root down. Per-folder files are
+/// read on first use and cached.
+pub struct IgnoreRules {
+    root: PathBuf,
+    /// Global excludes, `.git/info/exclude`, then settings; lowest
+    /// precedence
+    base: Vec<Gitignore>,
+    dirs: Mutex<HashMap<PathBuf, Option<Gitignore>>>,
+}
+
+impl IgnoreRules {
+    pub fn new(root: &Path) -> Self {
+        let (global, _) = GitignoreBuilder::new(root).build_global();
+        Self::with_global(root, global)
+    }
+
+    /// Rules for `root` on top of the given global excludes.
+    fn with_global(root: &Path, global: Gitignore) -> Self {
+        let mut base = Vec::new();
+        if !global.is_empty() {
+            base.push(global);
+        }
+        let exclude = root.join(".git").join("info").join("exclude");
+        if exclude.is_file() {
+            let mut builder = GitignoreBuilder::new(root);
+            builder.add(&exclude);
+            if let Ok(rules) = builder.build() {
+                base.push(rules);
+            }
+        }
+        let patterns = settings::ignore_patterns(Some(root));
+        if !patterns.is_empty() {
+            let mut builder = GitignoreBuilder::new(root);
+            for pattern in &patterns {
+                // A malformed pattern only drops that rule
+                let _ = builder.add_line(None, pattern);
+            }
+            if let Ok(rules) = builder.build() {
+                base.push(rules);
+            }
+        }
+        Self {
+            root: root.to_path_buf(),
+            base,
+            dirs: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Whether `path` is ignored, either itself or through an ignored
+    /// parent folder. Paths outside the root are never ignored.
+    pub fn is_ignored(&self, path: &Path, is_dir: bool) -> bool {
+        let Ok(rel) = path.strip_prefix(&self.root) else {
+            return false;
+        };
+        let components: Vec<_> = rel.components().collect();
+        let mut current = self.root.clone();
+        for (i, component) in components.iter().enumerate() {
+            current.push(component);
+            let last = i + 1 == components.len();
+            if self.matches(&current, if last { is_dir } else { true }) {
+                return true;
+            }
+        }
+        false
+    }
+
+    /// Whether `path` itself matches a rule, for walks that have already
+    /// checked its parents.
+    pub fn is_ignored_entry(&self, path: &Path, is_dir: bool) -> bool {
+        path.starts_with(&self.root) && self.matches(path, is_dir)
+    }
+
+    fn matches(&self, path: &Path, is_dir: bool) -> bool {
+        let mut dirs = self.dirs.lock().unwrap();
+        // The closest folder with an opinion decides
+        for dir in path.ancestors().skip(1) {
+            if !dir.starts_with(&self.root) {
+                break;
+            }
+            let rules = dirs.entry(dir.to_path_buf()).or_insert_with(|| load_dir_rules(dir));
+            if let Some(rules) = rules {
+                match rules.matched(path, is_dir) {
+                    Match::Ignore(_) => return true,
+                    Match::Whitelist(_) => return false,
+                    Match::None => {}
+                }
+            }
+        }
+        for rules in self.base.iter().rev() {
+            match rules.matched(path, is_dir) {
+                Match::Ignore(_) => return true,
+                Match::Whitelist(_) => return false,
+                Match::None => {}
+            }
+        }
+        false
+    }
+}
+
+/// `.gitignore` then `.gutterignore` in `dir`; later rules win.
+fn load_dir_rules(dir: &Path) -> Option<Gitignore> {
+    let files: Vec<PathBuf> = [".gitignore", GUTTERIGNORE]
+        .iter()
+        .map(|name| dir.join(name))
+        .filter(|p| p.is_file())
+        .collect();
+    if files.is_empty() {
+        return None;
+    }
+    let mut builder = GitignoreBuilder::new(dir);
+    for file in &files {
+        // A malformed line only drops that rule
+        builder.add(file);
+    }
+    builder.build().ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    fn write(path: &Path, text: &str) {
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, text).unwrap();
+    }
+
+    #[test]
+    fn layers_take_precedence_in_order() {
+        let root = std::env::temp_dir().join(format!("gutter-ignore-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+        let global_file = root.join("global-excludes");
+        write(&global_file, "*.log\ndrafts/\nglobal-excludes\n");
+        write(&root.join(".git/info/exclude"), "tmp/\n");
+        write(&root.join(".gutter/config.json"), "{ \"ignorePatterns\": [\"*.scratch\"] }");
+        write(&root.join(".gitignore"), "build/\n*.bak\n!keep.log\nshared.md\n");
+        write(&root.join(GUTTERIGNORE), "!build/\nprivate.md\n");
+        write(&root.join("notes/.gitignore"), "!important.bak\n");
+
+        let mut builder = GitignoreBuilder::new(&root);
+        builder.add(&global_file);
+        let rules = IgnoreRules::with_global(&root, builder.build().unwrap());
+
+        for (path, is_dir, ignored) in [
+            // Global excludes, and .gitignore re-including over them
+            ("app.log", false, true),
+            ("keep.log", false, false),
+            ("drafts/plan.md", false, true),
+            // .git/info/exclude and the ignorePatterns setting
+            ("tmp/cache.md", false, true),
+            ("idea.scratch", false, true),
+            // .gutterignore beats .gitignore in the same folder
+            ("build", true, false),
+            ("build/out.md", false, false),
+            ("private.md", false, true),
+            ("shared.md", false, true),
+            // A deeper folder's rules beat the root's
+            ("old.bak", false, true),
+            ("notes/other.bak", false, true),
+            ("notes/important.bak", false, false),
+            ("notes/today.md", false, false),
+        ] {
+            assert_eq!(rules.is_ignored(&root.join(path), is_dir), ignored, "{}", path);
+        }
+        assert!(!rules.is_ignored(Path::new("/elsewhere/app.log"), false));
+        fs::remove_dir_all(&root).unwrap();
+    }
+}
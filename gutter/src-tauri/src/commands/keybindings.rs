@@ -0,0 +1,235 @@
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap};
+use tauri::AppHandle;
+
+/// Commands that take a shortcut: id, label, and the shortcut it has
+/// unless the `keybindings` setting says otherwise. Ids match the menu
+/// items; `command_palette` is handled by the editor alone. The palette
+/// keeps its long-standing Cmd/Ctrl+Shift+P, so Previous Comment pairs
+/// with Next Comment as Cmd/Ctrl+Alt+Shift+N rather than sharing it.
+pub const COMMANDS: &[(&str, &str, &str)] = &[
+    ("new_file", "New File", "CmdOrCtrl+N"),
+    ("new_window", "New Window", ""),
+    ("open", "Open File", "CmdOrCtrl+O"),
+    ("open_folder", "Open Folder", ""),
+    ("open_workspace", "Open Workspace", ""),
+    ("add_folder_to_workspace", "Add Folder to Workspace", ""),
+    ("save", "Save", "CmdOrCtrl+S"),
+    ("new_from_template", "New from Template", ""),
+    ("save_as_template", "Save as Template", ""),
+    ("export", "Export", "CmdOrCtrl+Shift+E"),
+    ("preferences", "Preferences", "CmdOrCtrl+,"),
+    ("toggle_tree", "Toggle File Tree", "CmdOrCtrl+\\"),
+    ("toggle_comments", "Toggle Comments", "CmdOrCtrl+Shift+C"),
+    ("toggle_history", "Version History", "CmdOrCtrl+Shift+H"),
+    ("toggle_tags", "Tag Browser", "CmdOrCtrl+Shift+T"),
+    ("toggle_outline", "Toggle Outline", ""),
+    ("toggle_source", "Toggle Source Mode", "CmdOrCtrl+/"),
+    ("toggle_reading", "Reading Mode", "CmdOrCtrl+Shift+R"),
+    ("cycle_theme", "Cycle Theme", "CmdOrCtrl+Shift+D"),
+    ("search", "Search", "CmdOrCtrl+K"),
+    ("quick_open", "Quick Open File", "CmdOrCtrl+P"),
+    ("command_palette", "Command Palette", "CmdOrCtrl+Shift+P"),
+    ("find", "Find", "CmdOrCtrl+F"),
+    ("replace", "Find and Replace", "CmdOrCtrl+H"),
+    ("new_comment", "New Comment", "CmdOrCtrl+Shift+M"),
+    ("next_comment", "Next Comment", "CmdOrCtrl+Shift+N"),
+    ("prev_comment", "Previous Comment", "CmdOrCtrl+Alt+Shift+N"),
+];
+
+/// Modifiers in the order a normalized shortcut lists them.
+const MODIFIERS: &[&str] = &["CmdOrCtrl", "Cmd", "Ctrl", "Alt", "Shift"];
+
+const NAMED_KEYS: &[&str] = &[
+    "Enter", "Escape", "Tab", "Space", "Backspace", "Delete", "Insert", "Home", "End", "PageUp",
+    "PageDown", "Up", "Down", "Left", "Right",
+];
+
+fn modifier(name: &str) -> Option<&'static str> {
+    match name.to_ascii_lowercase().as_str() {
+        "cmdorctrl" | "cmdorcontrol" | "commandorctrl" | "commandorcontrol" => Some("CmdOrCtrl"),
+        "cmd" | "command" | "super" | "meta" => Some("Cmd"),
+        "ctrl" | "control" => Some("Ctrl"),
+        "alt" | "option" => Some("Alt"),
+        "shift" => Some("Shift"),
+        _ => None,
+    }
+}
+
+fn key(name: &str) -> Option<String> {
+    let mut chars = name.chars();
+    if let (Some(c), None) = (chars.next(), chars.next()) {
+        return (c.is_ascii_alphanumeric() || ",./\\;'[]-=`".contains(c))
+            .then(|| c.to_ascii_uppercase().to_string());
+    }
+    let lower = name.to_ascii_lowercase();
+    if let Some(n) = lower.strip_prefix('f').and_then(|n| n.parse::<u8>().ok()) {
+        return (1..=24).contains(&n).then(|| format!("F{}", n));
+    }
+    let lower = match lower.as_str() {
+        "esc" => "escape",
+        "return" => "enter",
+        "arrowup" => "up",
+        "arrowdown" => "down",
+        "arrowleft" => "left",
+        "arrowright" => "right",
+        other => other,
+    };
+    NAMED_KEYS
+        .iter()
+        .find(|k| k.to_ascii_lowercase() == lower)
+        .map(|k| k.to_string())
+}
+
+/// `shortcut` written the standard way (`CmdOrCtrl+Shift+E`: modifiers in
+/// a fixed order, then the key), or `None` if it isn't one. Apart from
+/// function keys, a shortcut needs a modifier so it can't eat typing.
+pub fn normalize(shortcut: &str) -> Option<String> {
+    let parts: Vec<&str> = shortcut.split('+').map(str::trim).collect();
+    let (last, mods) = parts.split_last()?;
+    let key = key(last)?;
+    let mut found = Vec::new();
+    for part in mods {
+        let m = modifier(part)?;
+        if found.contains(&m) {
+            return None;
+        }
+        found.push(m);
+    }
+    let function_key = key.len() > 1 && key.starts_with('F');
+    if found.is_empty() && !function_key {
+        return None;
+    }
+    let mut out: Vec<String> = MODIFIERS
+        .iter()
+        .filter(|m| found.contains(m))
+        .map(|m| m.to_string())
+        .collect();
+    out.push(key);
+    Some(out.join("+"))
+}
+
+/// The keys a normalized shortcut takes on this platform, so that
+/// `CmdOrCtrl+S` and `Ctrl+S` are seen as the same on Windows and Linux.
+fn platform_keys(shortcut: &str) -> String {
+    let cmd_or_ctrl = if cfg!(target_os = "macos") { "Cmd" } else { "Ctrl" };
+    let mut parts: Vec<&str> = shortcut
+        .split('+')
+        .map(|p| if p == "CmdOrCtrl" { cmd_or_ctrl } else { p })
+        .collect();
+    let key = parts.pop().unwrap_or_default();
+    parts.sort_unstable();
+    parts.dedup();
+    parts.push(key);
+    parts.join("+")
+}
+
+/// What's wrong with a `keybindings` setting, if anything.
+pub fn problem(bindings: &BTreeMap<String, String>) -> Option<String> {
+    for (id, shortcut) in bindings {
+        if !COMMANDS.iter().any(|(c, _, _)| c == id) {
+            return Some(format!("unknown command '{}'", id));
+        }
+        if !shortcut.is_empty() && normalize(shortcut).is_none() {
+            return Some(format!("'{}' for {} is not a valid shortcut", shortcut, id));
+        }
+    }
+    None
+}
+
+/// A command and the shortcut it ended up with.
+#[derive(Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct BoundCommand {
+    pub id: String,
+    pub label: String,
+    pub default: Option<String>,
+    /// `None` when unbound, by choice or because of a conflict
+    pub shortcut: Option<String>,
+    /// Whether `shortcut` comes from the `keybindings` setting
+    pub custom: bool,
+}
+
+/// Several commands wanting the same keys.
+#[derive(Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct KeybindingConflict {
+    pub shortcut: String,
+    pub commands: Vec<String>,
+    /// The one command that keeps the shortcut, if any
+    pub kept: Option<String>,
+}
+
+#[derive(Serialize, Clone)]
+pub struct Keybindings {
+    pub commands: Vec<BoundCommand>,
+    pub conflicts: Vec<KeybindingConflict>,
+}
+
+/// Work out every command's shortcut from the defaults and the user's
+/// `bindings` (an empty shortcut unbinds). When commands collide, a
+/// single custom binding wins and the defaults it collides with are
+/// dropped; otherwise none of them gets the shortcut.
+pub fn resolve(bindings: &BTreeMap<String, String>) -> Keybindings {
+    let mut commands: Vec<BoundCommand> = COMMANDS
+        .iter()
+        .map(|(id, label, default)| {
+            let default = normalize(default);
+            let custom = bindings.get(*id).filter(|s| s.is_empty() || normalize(s).is_some());
+            BoundCommand {
+                id: id.to_string(),
+                label: label.to_string(),
+                shortcut: match custom {
+                    Some(s) => normalize(s),
+                    None => default.clone(),
+                },
+                default,
+                custom: custom.is_some(),
+            }
+        })
+        .collect();
+
+    let mut by_keys: BTreeMap<String, Vec<usize>> = BTreeMap::new();
+    for (i, command) in commands.iter().enumerate() {
+        if let Some(shortcut) = &command.shortcut {
+            by_keys.entry(platform_keys(shortcut)).or_default().push(i);
+        }
+    }
+
+    let mut conflicts = Vec::new();
+    for group in by_keys.values().filter(|g| g.len() > 1) {
+        let custom: Vec<usize> = group.iter().copied().filter(|&i| commands[i].custom).collect();
+        let kept = match custom.as_slice() {
+            [only] => Some(*only),
+            _ => None,
+        };
+        conflicts.push(KeybindingConflict {
+            shortcut: commands[group[0]].shortcut.clone().unwrap_or_default(),
+            commands: group.iter().map(|&i| commands[i].id.clone()).collect(),
+            kept: kept.map(|i| commands[i].id.clone()),
+        });
+        for &i in group {
+            if Some(i) != kept {
+                commands[i].shortcut = None;
+            }
+        }
+    }
+
+    Keybindings { commands, conflicts }
+}
+
+/// Apply the user's `bindings` to the menu and report every command's
+/// shortcut, with any conflicts. The editor calls this whenever the
+/// `keybindings` setting changes and uses the result for its own keys.
+#[tauri::command]
+pub fn set_keybindings(app: AppHandle, bindings: BTreeMap<String, String>) -> Result<Keybindings, String> {
+    let resolved = resolve(&bindings);
+    let shortcuts: HashMap<&str, Option<&str>> = resolved
+        .commands
+        .iter()
+        .map(|c| (c.id.as_str(), c.shortcut.as_deref()))
+        .collect();
+    crate::menu::set_accelerators(&app, &shortcuts)
+        .map_err(|e| format!("Failed to update menu shortcuts: {}", e))?;
+    Ok(resolved)
+}
@@ -0,0 +1,216 @@
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::SystemTime;
+use tauri::{AppHandle, Manager};
+use super::comment_storage;
+use super::comments::{self, CommentMessage, ThreadStatus};
+use super::{settings, workspace};
+
+#[derive(Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct MentionedThread {
+    pub path: String,
+    pub thread_id: String,
+    pub status: ThreadStatus,
+    /// Only the messages that mention the person
+    pub messages: Vec<CommentMessage>,
+}
+
+struct FileMentions {
+    modified: Option<SystemTime>,
+    // (normalized person, thread)
+    mentions: Vec<(String, MentionedThread)>,
+}
+
+#[derive(Default)]
+struct MentionIndex {
+    files: HashMap<PathBuf, FileMentions>,
+}
+
+struct MentionIndexState {
+    // Keyed by workspace root
+    indexes: Mutex<HashMap<PathBuf, MentionIndex>>,
+}
+
+pub fn init(app: &tauri::App) {
+    app.manage(MentionIndexState {
+        indexes: Mutex::new(HashMap::new()),
+    });
+}
+
+/// Names mentioned as `@name` in a comment body, lowercased and deduplicated.
+/// An `@` inside a word (e.g. an email address) is not a mention.
+pub fn parse_mentions(body: &str) -> Vec<String> {
+    let mut names: Vec<String> = Vec::new();
+    for (i, _) in body.match_indices('@') {
+        let prev = body[..i].chars().next_back();
+        if prev.is_some_and(|p| p.is_alphanumeric() || p == '_') {
+            continue;
+        }
+        let rest = &body[i + 1..];
+        let len: usize = rest
+            .chars()
+            .take_while(|ch| ch.is_alphanumeric() || matches!(ch, '_' | '.' | '-'))
+            .map(char::len_utf8)
+            .sum();
+        // Sentence punctuation after a name isn't part of it
+        let name = rest[..len].trim_end_matches(['.', '-']).to_lowercase();
+        if !name.is_empty() && !names.contains(&name) {
+            names.push(name);
+        }
+    }
+    names
+}
+
+/// Handles a person might be mentioned by: "Ada Lovelace" answers to
+/// `@adalovelace`, `@ada.lovelace`, `@ada_lovelace`, `@ada-lovelace` and `@ada`.
+pub fn mention_aliases(person: &str) -> Vec<String> {
+    let person = person.trim().trim_start_matches('@').to_lowercase();
+    let parts: Vec<&str> = person.split_whitespace().collect();
+    if parts.is_empty() {
+        return Vec::new();
+    }
+    let mut aliases = vec![parts.concat()];
+    if parts.len() > 1 {
+        for sep in [".", "_", "-"] {
+            aliases.push(parts.join(sep));
+        }
+        aliases.push(parts[0].to_string());
+    }
+    aliases
+}
+
+fn scan_file(
+    store: &dyn comment_storage::CommentStore,
+    md_path: &Path,
+) -> Vec<(String, MentionedThread)> {
+    let Ok(Some(file)) = comments::load_comments_file(store, md_path) else {
+        return Vec::new();
+    };
+    let mut result = Vec::new();
+    for (id, thread) in &file.comments {
+        let mut by_person: BTreeMap<String, Vec<CommentMessage>> = BTreeMap::new();
+        for msg in &thread.thread {
+            for name in parse_mentions(&msg.body) {
+                by_person.entry(name).or_default().push(msg.clone());
+            }
+        }
+        for (person, messages) in by_person {
+            result.push((
+                person,
+                MentionedThread {
+                    path: md_path.to_string_lossy().to_string(),
+                    thread_id: id.clone(),
+                    status: thread.effective_status(),
+                    messages,
+                },
+            ));
+        }
+    }
+    result
+}
+
+/// Bring the workspace's index up to date, rescanning only comment stores
+/// whose modification time changed since the last refresh.
+fn refresh(index: &mut MentionIndex, ws: &Path) {
+    let store = comment_storage::store_for(Some(&ws.to_string_lossy()));
+    let files = workspace::collect_markdown_files(ws);
+
+    let present: HashSet<&PathBuf> = files.iter().collect();
+    index.files.retain(|path, _| present.contains(path));
+    for md_path in files {
+        let location = store.location(&md_path);
+        let modified = fs::metadata(&location).and_then(|m| m.modified()).ok();
+        if let Some(cached) = index.files.get(&md_path) {
+            if cached.modified == modified {
+                continue;
+            }
+        }
+        let mentions = if modified.is_some() {
+            scan_file(store.as_ref(), &md_path)
+        } else {
+            Vec::new()
+        };
+        index.files.insert(md_path, FileMentions { modified, mentions });
+    }
+}
+
+fn with_index<T>(
+    app: &AppHandle,
+    workspace: &str,
+    f: impl FnOnce(&MentionIndex) -> T,
+) -> Result<T, String> {
+    let state = app.state::<MentionIndexState>();
+    let mut guard = state.indexes.lock().map_err(|e| e.to_string())?;
+    let ws = PathBuf::from(workspace);
+    let index = guard.entry(ws.clone()).or_default();
+    refresh(index, &ws);
+    Ok(f(index))
+}
+
+fn sorted(mut threads: Vec<MentionedThread>) -> Vec<MentionedThread> {
+    threads.sort_by(|a, b| a.path.cmp(&b.path).then_with(|| a.thread_id.cmp(&b.thread_id)));
+    threads
+}
+
+/// Every mentioned person with the threads mentioning them.
+#[tauri::command]
+pub fn mention_index(
+    app: AppHandle,
+    workspace: String,
+) -> Result<BTreeMap<String, Vec<MentionedThread>>, String> {
+    with_index(&app, &workspace, |index| {
+        let mut by_person: BTreeMap<String, Vec<MentionedThread>> = BTreeMap::new();
+        for file in index.files.values() {
+            for (person, thread) in &file.mentions {
+                by_person.entry(person.clone()).or_default().push(thread.clone());
+            }
+        }
+        by_person.into_iter().map(|(k, v)| (k, sorted(v))).collect()
+    })
+}
+
+#[tauri::command]
+pub fn mentions_for(
+    app: AppHandle,
+    workspace: String,
+    person: String,
+) -> Result<Vec<MentionedThread>, String> {
+    let aliases = mention_aliases(&person);
+    with_index(&app, &workspace, |index| {
+        let mut threads: Vec<MentionedThread> = Vec::new();
+        for file in index.files.values() {
+            for (name, thread) in &file.mentions {
+                if !aliases.contains(name) {
+                    continue;
+                }
+                // A thread can mention the same person under two aliases
+                match threads
+                    .iter_mut()
+                    .find(|t| t.path == thread.path && t.thread_id == thread.thread_id)
+                {
+                    Some(existing) => {
+                        for msg in &thread.messages {
+                            if !existing.messages.iter().any(|m| m.id == msg.id) {
+                                existing.messages.push(msg.clone());
+                            }
+                        }
+                    }
+                    None => threads.push(thread.clone()),
+                }
+            }
+        }
+        sorted(threads)
+    })
+}
+
+/// Threads mentioning the `defaultAuthor` configured in settings.
+#[tauri::command]
+pub fn mentions_for_me(app: AppHandle, workspace: String) -> Result<Vec<MentionedThread>, String> {
+    let author = settings::configured_author(Some(Path::new(&workspace)))
+        .ok_or_else(|| "No author name configured in settings".to_string())?;
+    mentions_for(app, workspace, author)
+}
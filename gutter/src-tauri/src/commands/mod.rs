@@ -1,8 +1,28 @@
 pub mod file_io;
 pub mod comments;
+pub mod comment_storage;
+pub mod comment_export;
+pub mod critic_markup;
+pub mod suggestions;
+pub mod anchoring;
+pub mod review_package;
+pub mod docx_export;
+pub mod docx_import;
+pub mod file_meta;
+pub mod ignore_rules;
+pub mod ordering;
+pub mod mentions;
 pub mod workspace;
+pub mod workspace_file;
+pub mod app_dirs;
 pub mod settings;
+pub mod settings_watcher;
+pub mod keybindings;
+pub mod recent;
+pub mod session;
+pub mod recovery;
 pub mod watcher;
+pub mod windows;
 pub mod export;
 pub mod search;
 pub mod templates;
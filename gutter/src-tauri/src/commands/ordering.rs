@@ -0,0 +1,261 @@
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use tauri::State;
+use super::file_meta::MetaCache;
+use super::ignore_rules::IgnoreRules;
+use super::workspace::{self, ListOptions, Listing};
+
+/// Manual ordering for a workspace, stored in `.gutter/order.json`. Each
+/// folder (keyed by its path relative to the workspace, `""` for the root)
+/// lists entry names in order; entries it doesn't name follow in the
+/// default order.
+#[derive(Serialize, Deserialize, Default)]
+pub struct FolderOrder {
+    #[serde(default)]
+    pub folders: BTreeMap<String, Vec<String>>,
+    #[serde(flatten)]
+    pub extra: serde_json::Map<String, serde_json::Value>,
+}
+
+fn order_path(workspace: &Path) -> PathBuf {
+    workspace.join(".gutter").join("order.json")
+}
+
+/// The workspace's manual ordering; empty if unset or unreadable.
+pub fn load_order(workspace: &Path) -> FolderOrder {
+    fs::read_to_string(order_path(workspace))
+        .ok()
+        .and_then(|s| serde_json::from_str(&s).ok())
+        .unwrap_or_default()
+}
+
+fn save_order(workspace: &Path, order: &FolderOrder) -> Result<(), String> {
+    let path = order_path(workspace);
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(|e| format!("Failed to create .gutter dir: {}", e))?;
+    }
+    let json = serde_json::to_string_pretty(order).map_err(|e| format!("Failed to serialize: {}", e))?;
+    fs::write(&path, json).map_err(|e| format!("Failed to write order: {}", e))
+}
+
+/// Key for `dir` in `folders`: its path under `workspace` with `/`
+/// separators. `None` for folders outside the workspace.
+pub fn folder_key(workspace: &Path, dir: &Path) -> Option<String> {
+    let rel = dir.strip_prefix(workspace).ok()?;
+    let parts: Vec<String> = rel
+        .components()
+        .map(|c| c.as_os_str().to_string_lossy().to_string())
+        .collect();
+    Some(parts.join("/"))
+}
+
+impl FolderOrder {
+    /// Move entries of `dir` named in its manual order to the front, in
+    /// that order. The rest keep their relative order.
+    pub fn apply(&self, workspace: &Path, dir: &Path, paths: &mut [(PathBuf, bool)]) {
+        let Some(names) = folder_key(workspace, dir).and_then(|k| self.folders.get(&k)) else {
+            return;
+        };
+        paths.sort_by_key(|(p, _)| {
+            let name = p.file_name().unwrap_or_default().to_string_lossy();
+            names.iter().position(|n| *n == name).unwrap_or(usize::MAX)
+        });
+    }
+}
+
+/// Rewrite `key` if it is `from` or a folder below it.
+fn moved_key(key: &str, from: &str, to: &str) -> Option<String> {
+    if key == from {
+        Some(to.to_string())
+    } else {
+        key.strip_prefix(from)
+            .filter(|rest| rest.starts_with('/'))
+            .map(|rest| format!("{}{}", to, rest))
+    }
+}
+
+/// Keep the workspace's ordering in step with a rename or move of `old`
+/// to `new`: a renamed entry keeps its place, a moved one leaves its old
+/// folder's list, and folder lists below it follow it.
+pub fn rename_entry(workspace: &Path, old: &Path, new: &Path) -> Result<(), String> {
+    let mut order = load_order(workspace);
+    let Some(old_key) = folder_key(workspace, old).filter(|k| !k.is_empty()) else {
+        return Ok(());
+    };
+    let old_name = old.file_name().unwrap_or_default().to_string_lossy().to_string();
+    let new_name = new.file_name().unwrap_or_default().to_string_lossy().to_string();
+    let same_folder = old.parent() == new.parent();
+    let mut changed = false;
+
+    if let Some(names) = old
+        .parent()
+        .and_then(|dir| folder_key(workspace, dir))
+        .and_then(|k| order.folders.get_mut(&k))
+    {
+        if let Some(i) = names.iter().position(|n| *n == old_name) {
+            if same_folder {
+                names[i] = new_name;
+            } else {
+                names.remove(i);
+            }
+            changed = true;
+        }
+    }
+
+    let new_key = folder_key(workspace, new);
+    let below: Vec<String> = order
+        .folders
+        .keys()
+        .filter(|k| moved_key(k, &old_key, "").is_some())
+        .cloned()
+        .collect();
+    for key in below {
+        let names = order.folders.remove(&key).unwrap_or_default();
+        // Folders moved out of the workspace take their order with them
+        if let Some(to) = new_key.as_deref().and_then(|to| moved_key(&key, &old_key, to)) {
+            order.folders.insert(to, names);
+        }
+        changed = true;
+    }
+
+    if changed {
+        save_order(workspace, &order)?;
+    }
+    Ok(())
+}
+
+/// Drop a deleted entry, and any folder lists below it, from the ordering.
+pub fn remove_entry(workspace: &Path, path: &Path) -> Result<(), String> {
+    let mut order = load_order(workspace);
+    let Some(key) = folder_key(workspace, path).filter(|k| !k.is_empty()) else {
+        return Ok(());
+    };
+    let name = path.file_name().unwrap_or_default().to_string_lossy().to_string();
+    let before = order.folders.len();
+    order.folders.retain(|k, _| moved_key(k, &key, "").is_none());
+    let mut changed = order.folders.len() != before;
+    if let Some(names) = path
+        .parent()
+        .and_then(|dir| folder_key(workspace, dir))
+        .and_then(|k| order.folders.get_mut(&k))
+    {
+        let count = names.len();
+        names.retain(|n| *n != name);
+        changed |= names.len() != count;
+    }
+    if changed {
+        save_order(workspace, &order)?;
+    }
+    Ok(())
+}
+
+#[tauri::command]
+pub fn get_folder_order(workspace: String, folder: String) -> Result<Vec<String>, String> {
+    let ws = Path::new(&workspace);
+    let key = folder_key(ws, Path::new(&folder)).ok_or("Folder is outside the workspace")?;
+    Ok(load_order(ws).folders.remove(&key).unwrap_or_default())
+}
+
+/// Set the manual order of `folder`'s entries by name. An empty list
+/// returns the folder to the default order.
+#[tauri::command]
+pub fn set_folder_order(workspace: String, folder: String, names: Vec<String>) -> Result<(), String> {
+    let ws = Path::new(&workspace);
+    let key = folder_key(ws, Path::new(&folder)).ok_or("Folder is outside the workspace")?;
+    let mut order = load_order(ws);
+    if names.is_empty() {
+        order.folders.remove(&key);
+    } else {
+        order.folders.insert(key, names);
+    }
+    save_order(ws, &order)
+}
+
+/// Move the entry at `path` to position `index` among its siblings as the
+/// tree currently shows them (sorted by `options`, the tree's own), pinning
+/// the whole folder to manual order. Returns the folder's new order.
+#[tauri::command]
+pub fn move_entry(
+    cache: State<'_, MetaCache>,
+    workspace: String,
+    path: String,
+    index: usize,
+    options: Option<ListOptions>,
+) -> Result<Vec<String>, String> {
+    let ws = Path::new(&workspace);
+    let entry = Path::new(&path);
+    let dir = entry.parent().ok_or("Entry has no parent folder")?;
+    let key = folder_key(ws, dir).ok_or("Entry is outside the workspace")?;
+    let name = entry
+        .file_name()
+        .ok_or("Entry has no name")?
+        .to_string_lossy()
+        .to_string();
+
+    let rules = IgnoreRules::new(ws);
+    let listing = Listing::new(ws, &rules, &cache, options.unwrap_or_default());
+    let mut names: Vec<String> = workspace::ordered_children(dir, &listing)?
+        .iter()
+        .map(|(p, _)| p.file_name().unwrap_or_default().to_string_lossy().to_string())
+        .filter(|n| *n != name)
+        .collect();
+    names.insert(index.min(names.len()), name);
+
+    let mut order = listing.order;
+    order.folders.insert(key, names.clone());
+    save_order(ws, &order)?;
+    Ok(names)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn workspace(name: &str) -> PathBuf {
+        let ws = std::env::temp_dir().join(format!("gutter-order-{}-{}", name, std::process::id()));
+        let mut order = FolderOrder::default();
+        order.folders.insert(String::new(), vec!["intro.md".into(), "parts".into(), "outro.md".into()]);
+        order.folders.insert("parts".into(), vec!["b.md".into(), "a.md".into()]);
+        order.folders.insert("parts/sub".into(), vec!["x.md".into()]);
+        save_order(&ws, &order).unwrap();
+        ws
+    }
+
+    #[test]
+    fn renamed_entries_keep_their_place() {
+        let ws = workspace("rename");
+        rename_entry(&ws, &ws.join("intro.md"), &ws.join("preface.md")).unwrap();
+        rename_entry(&ws, &ws.join("parts"), &ws.join("chapters")).unwrap();
+        let order = load_order(&ws);
+        fs::remove_dir_all(&ws).ok();
+
+        assert_eq!(order.folders[""], vec!["preface.md", "chapters", "outro.md"]);
+        assert_eq!(order.folders["chapters"], vec!["b.md", "a.md"]);
+        assert_eq!(order.folders["chapters/sub"], vec!["x.md"]);
+        assert!(!order.folders.contains_key("parts"));
+    }
+
+    #[test]
+    fn moved_entries_leave_their_old_folder() {
+        let ws = workspace("move");
+        rename_entry(&ws, &ws.join("parts/b.md"), &ws.join("b.md")).unwrap();
+        let order = load_order(&ws);
+        fs::remove_dir_all(&ws).ok();
+
+        assert_eq!(order.folders["parts"], vec!["a.md"]);
+        assert_eq!(order.folders[""], vec!["intro.md", "parts", "outro.md"]);
+    }
+
+    #[test]
+    fn deleted_entries_are_dropped() {
+        let ws = workspace("delete");
+        remove_entry(&ws, &ws.join("parts")).unwrap();
+        let order = load_order(&ws);
+        fs::remove_dir_all(&ws).ok();
+
+        assert_eq!(order.folders[""], vec!["intro.md", "outro.md"]);
+        assert_eq!(order.folders.len(), 1);
+    }
+}
@@ -0,0 +1,192 @@
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+use tauri::{AppHandle, Emitter, Manager};
+use super::{app_dirs, settings};
+
+/// Unpinned entries kept per kind; pinned ones don't count.
+const MAX_RECENT: usize = 20;
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum RecentKind {
+    File,
+    /// A folder or a `.gutter-workspace` file
+    Workspace,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct RecentEntry {
+    pub path: String,
+    pub kind: RecentKind,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub pinned: bool,
+    /// Milliseconds since the epoch
+    #[serde(default)]
+    pub opened: u64,
+}
+
+struct RecentState {
+    /// Pinned entries first, then most recently opened first
+    entries: Mutex<Vec<RecentEntry>>,
+}
+
+fn registry_path() -> PathBuf {
+    app_dirs::state_dir().join("recent.json")
+}
+
+/// The saved registry. Before there was one, recent files were a setting;
+/// those seed it.
+fn load() -> Vec<RecentEntry> {
+    match fs::read_to_string(registry_path()) {
+        Ok(text) => serde_json::from_str(&text).unwrap_or_default(),
+        Err(_) => settings::load(None)
+            .recent_files
+            .into_iter()
+            .map(|path| RecentEntry {
+                path,
+                kind: RecentKind::File,
+                pinned: false,
+                opened: 0,
+            })
+            .collect(),
+    }
+}
+
+fn save(entries: &[RecentEntry]) -> Result<(), String> {
+    let path = registry_path();
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir).map_err(|e| format!("Failed to create state directory: {}", e))?;
+    }
+    let json = serde_json::to_string_pretty(entries)
+        .map_err(|e| format!("Failed to serialize recent list: {}", e))?;
+    fs::write(&path, json).map_err(|e| format!("Failed to write recent list: {}", e))
+}
+
+pub fn init(app: &tauri::App) {
+    let mut entries = load();
+    prune(&mut entries);
+    app.manage(RecentState {
+        entries: Mutex::new(entries),
+    });
+}
+
+fn now_ms() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or(0)
+}
+
+/// Restore the order and cap after a change.
+fn tidy(entries: &mut Vec<RecentEntry>) {
+    entries.sort_by(|a, b| b.pinned.cmp(&a.pinned).then(b.opened.cmp(&a.opened)));
+    for kind in [RecentKind::File, RecentKind::Workspace] {
+        let mut kept = 0;
+        entries.retain(|e| {
+            if e.kind != kind || e.pinned {
+                return true;
+            }
+            kept += 1;
+            kept <= MAX_RECENT
+        });
+    }
+}
+
+/// Drop entries whose path is gone. Pinned ones stay, since they may just
+/// be on a drive that isn't mounted.
+fn prune(entries: &mut Vec<RecentEntry>) -> bool {
+    let before = entries.len();
+    entries.retain(|e| e.pinned || Path::new(&e.path).exists());
+    entries.len() != before
+}
+
+/// Apply `change` to the registry, save it, and tell the menu and every
+/// window.
+fn update(app: &AppHandle, change: impl FnOnce(&mut Vec<RecentEntry>)) -> Result<Vec<RecentEntry>, String> {
+    let state = app.state::<RecentState>();
+    let mut entries = state.entries.lock().map_err(|e| e.to_string())?;
+    change(&mut entries);
+    tidy(&mut entries);
+    save(&entries)?;
+    let snapshot = entries.clone();
+    drop(entries);
+    if let Err(e) = crate::menu::rebuild_open_recent(app, &snapshot) {
+        log::warn!("Failed to rebuild Open Recent menu: {}", e);
+    }
+    let _ = app.emit("recent-changed", snapshot.clone());
+    Ok(snapshot)
+}
+
+/// Recent entries of `kind` (all kinds without one), pinned first, with
+/// entries whose path no longer exists removed.
+#[tauri::command]
+pub fn list_recent(app: AppHandle, kind: Option<RecentKind>) -> Result<Vec<RecentEntry>, String> {
+    let pruned = {
+        let state = app.state::<RecentState>();
+        let mut entries = state.entries.lock().map_err(|e| e.to_string())?;
+        prune(&mut entries)
+    };
+    let entries = if pruned {
+        update(&app, |_| {})?
+    } else {
+        current(&app)
+    };
+    Ok(entries
+        .into_iter()
+        .filter(|e| kind.map_or(true, |k| e.kind == k))
+        .collect())
+}
+
+/// Current entries, as last saved.
+pub fn current(app: &AppHandle) -> Vec<RecentEntry> {
+    app.state::<RecentState>()
+        .entries
+        .lock()
+        .map(|e| e.clone())
+        .unwrap_or_default()
+}
+
+/// Record that `path` was just opened, moving it to the top.
+#[tauri::command]
+pub fn add_recent(app: AppHandle, path: String, kind: RecentKind) -> Result<Vec<RecentEntry>, String> {
+    update(&app, |entries| {
+        let pinned = entries.iter().any(|e| e.path == path && e.pinned);
+        entries.retain(|e| e.path != path);
+        entries.insert(
+            0,
+            RecentEntry {
+                path,
+                kind,
+                pinned,
+                opened: now_ms(),
+            },
+        );
+    })
+}
+
+/// Pin or unpin `path`. Pinned entries stay at the top and aren't dropped
+/// by the cap or when missing.
+#[tauri::command]
+pub fn pin_recent(app: AppHandle, path: String, pinned: bool) -> Result<Vec<RecentEntry>, String> {
+    update(&app, |entries| {
+        if let Some(entry) = entries.iter_mut().find(|e| e.path == path) {
+            entry.pinned = pinned;
+        }
+    })
+}
+
+#[tauri::command]
+pub fn remove_recent(app: AppHandle, path: String) -> Result<Vec<RecentEntry>, String> {
+    update(&app, |entries| entries.retain(|e| e.path != path))
+}
+
+/// Forget unpinned entries of `kind` (all kinds without one).
+#[tauri::command]
+pub fn clear_recent(app: AppHandle, kind: Option<RecentKind>) -> Result<Vec<RecentEntry>, String> {
+    update(&app, |entries| {
+        entries.retain(|e| e.pinned || kind.is_some_and(|k| e.kind != k))
+    })
+}
@@ -0,0 +1,269 @@
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+use std::time::{SystemTime, UNIX_EPOCH};
+use super::app_dirs;
+
+/// Lines of unchanged text around each change in a diff.
+const CONTEXT: usize = 3;
+
+/// Above this many line pairs, a diff just replaces the changed middle
+/// rather than aligning it line by line.
+const MAX_DIFF_CELLS: usize = 4_000_000;
+
+/// When this run first touched the journal. Anything older belongs to an
+/// earlier run and is left for its recovery prompt.
+static RUN_STARTED: OnceLock<u64> = OnceLock::new();
+
+/// A dirty buffer as last streamed from the editor.
+#[derive(Serialize, Deserialize)]
+struct JournalEntry {
+    /// File path, or `untitled:<n>` for a buffer never saved
+    path: String,
+    content: String,
+    /// Milliseconds since the epoch
+    updated: u64,
+}
+
+/// An unsaved buffer left behind by a crash.
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RecoverableBuffer {
+    pub path: String,
+    pub content: String,
+    /// When it was journaled, in milliseconds since the epoch
+    pub updated: u64,
+    /// Whether the file still exists; if not, the diff is against nothing
+    pub on_disk: bool,
+    /// Unified diff from the file on disk to the journaled text
+    pub diff: String,
+    /// When the file on disk was last modified, if that was after the
+    /// buffer was journaled, in milliseconds since the epoch
+    pub changed_on_disk: Option<u64>,
+}
+
+/// Journal entries live under the state directory, in `recovery`
+/// (`~/.gutter/recovery` unless XDG or portable paths apply).
+fn recovery_dir() -> PathBuf {
+    app_dirs::state_dir().join("recovery")
+}
+
+fn entry_path(path: &str) -> PathBuf {
+    let mut hasher = Sha256::new();
+    hasher.update(path.as_bytes());
+    let hash = format!("{:x}", hasher.finalize());
+    recovery_dir().join(format!("{}.json", &hash[..16]))
+}
+
+fn now_ms() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or(0)
+}
+
+fn modified_ms(path: &Path) -> Option<u64> {
+    let modified = fs::metadata(path).ok()?.modified().ok()?;
+    modified.duration_since(UNIX_EPOCH).ok().map(|d| d.as_millis() as u64)
+}
+
+/// Save the current text of a dirty buffer. Written to a temporary file
+/// and renamed, so a crash mid-write leaves the previous copy intact.
+#[tauri::command]
+pub fn journal_buffer(path: String, content: String) -> Result<(), String> {
+    RUN_STARTED.get_or_init(now_ms);
+    fs::create_dir_all(recovery_dir())
+        .map_err(|e| format!("Failed to create recovery dir: {}", e))?;
+    let target = entry_path(&path);
+    let entry = JournalEntry {
+        path,
+        content,
+        updated: now_ms(),
+    };
+    let json = serde_json::to_string(&entry)
+        .map_err(|e| format!("Failed to serialize journal entry: {}", e))?;
+    let tmp = target.with_extension("json.tmp");
+    fs::write(&tmp, json).map_err(|e| format!("Failed to write journal entry: {}", e))?;
+    fs::rename(&tmp, &target).map_err(|e| format!("Failed to write journal entry: {}", e))
+}
+
+/// Drop the journal entry for a buffer that was saved, closed or
+/// recovered.
+#[tauri::command]
+pub fn discard_buffer(path: String) -> Result<(), String> {
+    let target = entry_path(&path);
+    if !target.exists() {
+        return Ok(());
+    }
+    fs::remove_file(&target).map_err(|e| format!("Failed to discard journal entry: {}", e))
+}
+
+/// Drop what this run journaled, on a clean exit. Buffers left over from
+/// an earlier run that the user put off deciding on are kept.
+pub fn clear() -> Result<(), String> {
+    let started = match RUN_STARTED.get() {
+        Some(ms) => *ms,
+        None => return Ok(()),
+    };
+    let dir = recovery_dir();
+    if !dir.exists() {
+        return Ok(());
+    }
+    for dir_entry in fs::read_dir(&dir).map_err(|e| format!("Failed to read recovery dir: {}", e))? {
+        let file = match dir_entry {
+            Ok(e) => e.path(),
+            Err(_) => continue,
+        };
+        let entry: Option<JournalEntry> = fs::read_to_string(&file)
+            .ok()
+            .and_then(|text| serde_json::from_str(&text).ok());
+        if entry.is_some_and(|e| e.updated >= started) {
+            let _ = fs::remove_file(&file);
+        }
+    }
+    Ok(())
+}
+
+/// Buffers journaled by an earlier run that hold text the file on disk
+/// doesn't, newest first. Entries whose text the file already holds are
+/// deleted instead; a file modified since is flagged, not trusted to have
+/// caught up.
+#[tauri::command]
+pub fn list_recoverable() -> Result<Vec<RecoverableBuffer>, String> {
+    RUN_STARTED.get_or_init(now_ms);
+    let dir = recovery_dir();
+    if !dir.exists() {
+        return Ok(Vec::new());
+    }
+    let mut buffers = Vec::new();
+    for dir_entry in fs::read_dir(&dir).map_err(|e| format!("Failed to read recovery dir: {}", e))? {
+        let file = match dir_entry {
+            Ok(e) => e.path(),
+            Err(_) => continue,
+        };
+        if file.extension().and_then(|e| e.to_str()) != Some("json") {
+            continue;
+        }
+        let entry: JournalEntry = match fs::read_to_string(&file)
+            .ok()
+            .and_then(|text| serde_json::from_str(&text).ok())
+        {
+            Some(entry) => entry,
+            None => continue,
+        };
+        let disk = if entry.path.starts_with("untitled:") {
+            None
+        } else {
+            fs::read_to_string(&entry.path).ok()
+        };
+        if disk.as_deref() == Some(entry.content.as_str()) {
+            let _ = fs::remove_file(&file);
+            continue;
+        }
+        let changed_on_disk = disk
+            .as_ref()
+            .and_then(|_| modified_ms(Path::new(&entry.path)))
+            .filter(|m| *m >= entry.updated);
+        let base = disk.as_deref().unwrap_or("");
+        buffers.push(RecoverableBuffer {
+            diff: unified_diff(base, &entry.content),
+            on_disk: disk.is_some(),
+            path: entry.path,
+            content: entry.content,
+            updated: entry.updated,
+            changed_on_disk,
+        });
+    }
+    buffers.sort_by_key(|b| std::cmp::Reverse(b.updated));
+    Ok(buffers)
+}
+
+/// Line-level edit script from `a` to `b`: `' '` kept, `'-'` removed,
+/// `'+'` added.
+fn line_ops<'a>(a: &[&'a str], b: &[&'a str]) -> Vec<(char, &'a str)> {
+    let pre = a.iter().zip(b).take_while(|(x, y)| x == y).count();
+    let suf = a[pre..]
+        .iter()
+        .rev()
+        .zip(b[pre..].iter().rev())
+        .take_while(|(x, y)| x == y)
+        .count();
+    let (am, bm) = (&a[pre..a.len() - suf], &b[pre..b.len() - suf]);
+    let mut ops: Vec<(char, &str)> = a[..pre].iter().map(|l| (' ', *l)).collect();
+
+    if am.len() * bm.len() > MAX_DIFF_CELLS {
+        ops.extend(am.iter().map(|l| ('-', *l)));
+        ops.extend(bm.iter().map(|l| ('+', *l)));
+    } else {
+        // lcs[i * w + j]: longest common subsequence of am[i..] and bm[j..]
+        let (n, m, w) = (am.len(), bm.len(), bm.len() + 1);
+        let mut lcs = vec![0u32; (n + 1) * w];
+        for i in (0..n).rev() {
+            for j in (0..m).rev() {
+                lcs[i * w + j] = if am[i] == bm[j] {
+                    lcs[(i + 1) * w + j + 1] + 1
+                } else {
+                    lcs[(i + 1) * w + j].max(lcs[i * w + j + 1])
+                };
+            }
+        }
+        let (mut i, mut j) = (0, 0);
+        while i < n || j < m {
+            if i < n && j < m && am[i] == bm[j] {
+                ops.push((' ', am[i]));
+                i += 1;
+                j += 1;
+            } else if i < n && (j == m || lcs[(i + 1) * w + j] >= lcs[i * w + j + 1]) {
+                ops.push(('-', am[i]));
+                i += 1;
+            } else {
+                ops.push(('+', bm[j]));
+                j += 1;
+            }
+        }
+    }
+
+    ops.extend(a[a.len() - suf..].iter().map(|l| (' ', *l)));
+    ops
+}
+
+/// Unified diff of `old` to `new`, hunks only (no file headers).
+pub fn unified_diff(old: &str, new: &str) -> String {
+    let a: Vec<&str> = old.lines().collect();
+    let b: Vec<&str> = new.lines().collect();
+    let ops = line_ops(&a, &b);
+    let changes: Vec<usize> = ops
+        .iter()
+        .enumerate()
+        .filter(|(_, (kind, _))| *kind != ' ')
+        .map(|(i, _)| i)
+        .collect();
+
+    let mut out = String::new();
+    let mut idx = 0;
+    while idx < changes.len() {
+        let start = changes[idx].saturating_sub(CONTEXT);
+        // Changes close enough to share context go in one hunk
+        while idx + 1 < changes.len() && changes[idx + 1] - changes[idx] <= 2 * CONTEXT + 1 {
+            idx += 1;
+        }
+        let end = (changes[idx] + CONTEXT + 1).min(ops.len());
+        let count = |ops: &[(char, &str)], skip: char| ops.iter().filter(|(k, _)| *k != skip).count();
+        let (old_before, new_before) = (count(&ops[..start], '+'), count(&ops[..start], '-'));
+        let hunk = &ops[start..end];
+        let (old_len, new_len) = (count(hunk, '+'), count(hunk, '-'));
+        // Empty ranges are numbered by the line before them
+        let old_start = if old_len == 0 { old_before } else { old_before + 1 };
+        let new_start = if new_len == 0 { new_before } else { new_before + 1 };
+        out.push_str(&format!("@@ -{},{} +{},{} @@\n", old_start, old_len, new_start, new_len));
+        for (kind, line) in hunk {
+            out.push(*kind);
+            out.push_str(line);
+            out.push('\n');
+        }
+        idx += 1;
+    }
+    out
+}
@@ -0,0 +1,495 @@
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::HashSet;
+use std::fs::{self, File};
+use std::io::{Read, Write};
+use std::path::{Component, Path};
+use tauri::WebviewWindow;
+use zip::write::SimpleFileOptions;
+use zip::ZipArchive;
+use super::anchoring;
+use super::comment_storage;
+use super::comments::{self, CommentMessage, CommentThread, CommentsFile};
+use super::{settings, watcher, workspace_file};
+
+const PACKAGE_FORMAT: &str = "gutter-review";
+const MANIFEST_NAME: &str = "manifest.json";
+/// A reviewer's thread is only anchored automatically when its text is found
+/// with at least this confidence; otherwise it is imported unanchored.
+const MIN_ANCHOR_CONFIDENCE: f64 = 0.9;
+
+/// `manifest.json` at the root of a review package.
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReviewManifest {
+    pub format: String,
+    pub version: u32,
+    /// Archive entry of the note
+    pub note: String,
+    /// Archive entry of the note's comments
+    pub comments: String,
+    #[serde(default)]
+    pub assets: Vec<String>,
+    #[serde(default)]
+    pub reviewers: Vec<String>,
+    #[serde(default)]
+    pub created_by: Option<String>,
+    pub created_at: String,
+    /// SHA-256 of the note as exported, to tell whether it changed since
+    #[serde(default)]
+    pub note_sha256: String,
+    #[serde(flatten)]
+    pub extra: serde_json::Map<String, serde_json::Value>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReviewExportReport {
+    pub threads: usize,
+    pub assets: Vec<String>,
+    /// Referenced assets that don't exist or lie outside the note's folder
+    pub skipped_assets: Vec<String>,
+}
+
+#[derive(Serialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct ReviewImportReport {
+    pub reviewers: Vec<String>,
+    pub threads_added: usize,
+    pub replies_added: usize,
+    pub threads_resolved: usize,
+    /// New threads whose anchor text couldn't be found in the note
+    pub unanchored: Vec<String>,
+    /// True when the note was edited after the package was exported
+    pub note_changed: bool,
+}
+
+fn sha256_hex(text: &str) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(text.as_bytes());
+    format!("{:x}", hasher.finalize())
+}
+
+/// Local image and link targets referenced by a note, as written.
+pub fn referenced_assets(markdown: &str) -> Vec<String> {
+    let mut targets: Vec<String> = Vec::new();
+    let mut push = |raw: &str| {
+        let raw = raw.trim();
+        let raw = raw
+            .strip_prefix('<')
+            .and_then(|r| r.strip_suffix('>'))
+            .unwrap_or(raw);
+        // Drop an optional link title: ![alt](img.png "Title")
+        let target = raw.split_once(" \"").map_or(raw, |(t, _)| t).trim();
+        let remote = target.contains("://")
+            || target.starts_with("data:")
+            || target.starts_with("mailto:");
+        if target.is_empty() || remote || target.starts_with('#') {
+            return;
+        }
+        let target = target.split(['#', '?']).next().unwrap_or(target);
+        // Markdown links to other notes aren't assets
+        if target.ends_with(".md") {
+            return;
+        }
+        if !targets.iter().any(|t| t == target) {
+            targets.push(target.to_string());
+        }
+    };
+
+    for (i, _) in markdown.match_indices("](") {
+        let rest = &markdown[i + 2..];
+        if let Some(end) = rest.find(')') {
+            push(&rest[..end]);
+        }
+    }
+    for (i, _) in markdown.match_indices("<img ") {
+        let tag = &markdown[i..];
+        let tag = &tag[..tag.find('>').unwrap_or(tag.len())];
+        if let Some(src) = tag.split_once("src=\"").and_then(|(_, r)| r.split_once('"')) {
+            push(src.0);
+        }
+    }
+    targets
+}
+
+/// Archive path for an asset reference, or `None` if it leaves the note's folder.
+fn asset_entry(target: &str) -> Option<String> {
+    let decoded = target.replace("%20", " ");
+    let mut parts = Vec::new();
+    for component in Path::new(&decoded).components() {
+        match component {
+            Component::Normal(part) => parts.push(part.to_string_lossy().to_string()),
+            Component::CurDir => {}
+            _ => return None,
+        }
+    }
+    if parts.is_empty() {
+        None
+    } else {
+        Some(parts.join("/"))
+    }
+}
+
+fn read_entry(archive: &mut ZipArchive<File>, name: &str) -> Result<String, String> {
+    let mut entry = archive
+        .by_name(name)
+        .map_err(|_| format!("Review package is missing {}", name))?;
+    let mut content = String::new();
+    entry
+        .read_to_string(&mut content)
+        .map_err(|e| format!("Failed to read {}: {}", name, e))?;
+    Ok(content)
+}
+
+/// Bundle a note, its comments and the assets it references into a zip
+/// archive for reviewers outside the workspace.
+#[tauri::command]
+pub fn export_review_package(
+    window: WebviewWindow,
+    path: String,
+    output: String,
+    reviewers: Option<Vec<String>>,
+    workspace: Option<String>,
+) -> Result<ReviewExportReport, String> {
+    let workspace = workspace_file::owning_root(&window, workspace, &path);
+    let md_path = Path::new(&path);
+    let text = fs::read_to_string(md_path).map_err(|e| format!("Failed to read file: {}", e))?;
+    let markdown = comment_storage::split_embedded(&text).0;
+    let store = comment_storage::store_for(workspace.as_deref());
+    let file = comments::load_comments_file(store.as_ref(), md_path)?.unwrap_or_default();
+
+    let note_name = md_path
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_else(|| "note.md".to_string());
+    let stem = md_path.file_stem().unwrap_or_default().to_string_lossy();
+    let comments_name = format!("{}.comments.json", stem);
+    let note_dir = md_path.parent().unwrap_or(Path::new("."));
+
+    let out = File::create(&output).map_err(|e| format!("Failed to create package: {}", e))?;
+    let mut zip = zip::ZipWriter::new(out);
+    let options = SimpleFileOptions::default();
+    let zip_err = |e: zip::result::ZipError| format!("Failed to write package: {}", e);
+    let io_err = |e: std::io::Error| format!("Failed to write package: {}", e);
+
+    let mut assets = Vec::new();
+    let mut skipped_assets = Vec::new();
+    for target in referenced_assets(markdown) {
+        let Some(entry) = asset_entry(&target) else {
+            skipped_assets.push(target);
+            continue;
+        };
+        // Entry names the note and manifest already use can't be assets
+        if entry == note_name
+            || entry == comments_name
+            || entry == MANIFEST_NAME
+            || assets.contains(&entry)
+        {
+            continue;
+        }
+        match fs::read(note_dir.join(&entry)) {
+            Ok(data) => {
+                zip.start_file(entry.as_str(), options).map_err(zip_err)?;
+                zip.write_all(&data).map_err(io_err)?;
+                assets.push(entry);
+            }
+            Err(_) => skipped_assets.push(target),
+        }
+    }
+
+    let json = serde_json::to_string_pretty(&file)
+        .map_err(|e| format!("Failed to serialize comments: {}", e))?;
+    let manifest = ReviewManifest {
+        format: PACKAGE_FORMAT.to_string(),
+        version: 1,
+        note: note_name.clone(),
+        comments: comments_name.clone(),
+        assets: assets.clone(),
+        reviewers: reviewers.unwrap_or_default(),
+        created_by: settings::configured_author(workspace.as_deref().map(Path::new)),
+        created_at: comments::iso_now(),
+        note_sha256: sha256_hex(markdown),
+        extra: serde_json::Map::new(),
+    };
+    let manifest_json = serde_json::to_string_pretty(&manifest)
+        .map_err(|e| format!("Failed to serialize manifest: {}", e))?;
+
+    for (name, content) in [
+        (MANIFEST_NAME, manifest_json.as_str()),
+        (note_name.as_str(), markdown),
+        (comments_name.as_str(), json.as_str()),
+    ] {
+        zip.start_file(name, options).map_err(zip_err)?;
+        zip.write_all(content.as_bytes()).map_err(io_err)?;
+    }
+    zip.finish().map_err(zip_err)?;
+
+    Ok(ReviewExportReport {
+        threads: file.comments.len(),
+        assets,
+        skipped_assets,
+    })
+}
+
+/// Whether two threads are the same conversation: same creation time and
+/// same opening message.
+fn same_thread(a: &CommentThread, b: &CommentThread) -> bool {
+    a.created_at == b.created_at
+        && match (a.thread.first(), b.thread.first()) {
+            (Some(x), Some(y)) => x.id == y.id || (x.author == y.author && x.body == y.body),
+            (None, None) => true,
+            _ => false,
+        }
+}
+
+fn has_message(thread: &CommentThread, msg: &CommentMessage) -> bool {
+    thread.thread.iter().any(|m| {
+        m.id == msg.id || (m.author == msg.author && m.timestamp == msg.timestamp && m.body == msg.body)
+    })
+}
+
+fn next_thread_id(file: &CommentsFile, markdown: &str) -> String {
+    let in_text: HashSet<String> = comments::extract_anchors(markdown)
+        .into_iter()
+        .map(|a| a.id)
+        .collect();
+    let max = file
+        .comments
+        .keys()
+        .chain(in_text.iter())
+        .filter_map(|id| id.strip_prefix('c')?.parse::<u64>().ok())
+        .max()
+        .unwrap_or(0);
+    format!("c{}", max + 1)
+}
+
+/// Merge threads and replies from a reviewer's copy into `local`. New threads
+/// are anchored in `markdown` where their text can be found. Returns the
+/// updated note body.
+pub fn merge_review(
+    local: &mut CommentsFile,
+    markdown: &str,
+    returned: &CommentsFile,
+    returned_markdown: &str,
+    report: &mut ReviewImportReport,
+) -> String {
+    let mut markdown = markdown.to_string();
+    let mut reviewed = returned.clone();
+    anchoring::update_fingerprints(&mut reviewed, returned_markdown);
+
+    for (id, incoming) in &reviewed.comments {
+        // Known thread: find it by id first, then by content in case the
+        // reviewer's copy numbered it differently
+        let existing = local
+            .comments
+            .get(id)
+            .filter(|t| same_thread(t, incoming))
+            .map(|_| id.clone())
+            .or_else(|| {
+                local
+                    .comments
+                    .iter()
+                    .find(|(_, t)| same_thread(t, incoming))
+                    .map(|(k, _)| k.clone())
+            });
+
+        if let Some(thread) = existing.and_then(|k| local.comments.get_mut(&k)) {
+            for msg in &incoming.thread {
+                if !has_message(thread, msg) {
+                    thread.thread.push(msg.clone());
+                    report.replies_added += 1;
+                }
+            }
+            if incoming.resolved && !thread.resolved {
+                thread.resolved = true;
+                thread.resolved_by = incoming.resolved_by.clone();
+                thread.resolved_at = incoming.resolved_at.clone();
+                report.threads_resolved += 1;
+            }
+            continue;
+        }
+
+        let new_id = if local.comments.contains_key(id)
+            || comments::extract_anchors(&markdown).iter().any(|a| &a.id == id)
+        {
+            next_thread_id(local, &markdown)
+        } else {
+            id.clone()
+        };
+        let mut thread = incoming.clone();
+        let best = thread
+            .fingerprint
+            .as_ref()
+            .and_then(|fp| anchoring::find_candidates(&new_id, fp, &markdown).into_iter().next())
+            .filter(|c| c.confidence >= MIN_ANCHOR_CONFIDENCE);
+        match best {
+            Some(c) => {
+                markdown = format!(
+                    "{}<mark>{}</mark><sup>[{}]</sup>{}",
+                    &markdown[..c.start],
+                    &markdown[c.start..c.end],
+                    new_id,
+                    &markdown[c.end..],
+                );
+            }
+            None => {
+                thread.fingerprint = None;
+                report.unanchored.push(new_id.clone());
+            }
+        }
+        local.comments.insert(new_id, thread);
+        report.threads_added += 1;
+    }
+    markdown
+}
+
+/// Merge the threads and replies in a returned review package into the
+/// note's comments. Threads already present are never duplicated.
+#[tauri::command]
+pub fn import_review_package(
+    window: WebviewWindow,
+    archive: String,
+    path: String,
+    workspace: Option<String>,
+) -> Result<ReviewImportReport, String> {
+    let workspace = workspace_file::owning_root(&window, workspace, &path);
+    let file = File::open(&archive).map_err(|e| format!("Failed to open package: {}", e))?;
+    let mut zip = ZipArchive::new(file).map_err(|e| format!("Invalid review package: {}", e))?;
+    let manifest: ReviewManifest = serde_json::from_str(&read_entry(&mut zip, MANIFEST_NAME)?)
+        .map_err(|e| format!("Invalid review manifest: {}", e))?;
+    if manifest.format != PACKAGE_FORMAT {
+        return Err(format!("Not a review package: {}", manifest.format));
+    }
+    let returned = comments::parse_comments_file(&read_entry(&mut zip, &manifest.comments)?)?;
+    let returned_markdown = read_entry(&mut zip, &manifest.note)?;
+
+    let md_path = Path::new(&path);
+    let text = fs::read_to_string(md_path).map_err(|e| format!("Failed to read file: {}", e))?;
+    let markdown = comment_storage::split_embedded(&text).0;
+    let store = comment_storage::store_for(workspace.as_deref());
+    let mut local = comments::load_comments_file(store.as_ref(), md_path)?.unwrap_or_default();
+
+    let mut report = ReviewImportReport {
+        reviewers: manifest.reviewers.clone(),
+        note_changed: !manifest.note_sha256.is_empty() && manifest.note_sha256 != sha256_hex(markdown),
+        ..Default::default()
+    };
+    let merged = merge_review(&mut local, markdown, &returned, &returned_markdown, &mut report);
+    if report.threads_added + report.replies_added + report.threads_resolved == 0 {
+        return Ok(report);
+    }
+
+    // Rewrite the document first: the embedded store appends to it
+    if merged != markdown {
+        watcher::mark_write(&window, &path);
+        fs::write(md_path, &merged).map_err(|e| format!("Failed to write file: {}", e))?;
+    }
+    anchoring::update_fingerprints(&mut local, &merged);
+    comments::save_comments_file(&window, store.as_ref(), md_path, &local)?;
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const NOTE: &str = "Intro <mark>bold claim</mark><sup>[c1]</sup> then a paragraph that reviewers read.\n";
+
+    fn message(id: &str, author: &str, timestamp: &str, body: &str) -> CommentMessage {
+        CommentMessage {
+            id: id.to_string(),
+            author: author.to_string(),
+            timestamp: timestamp.to_string(),
+            body: body.to_string(),
+        }
+    }
+
+    fn thread(created_at: &str, messages: Vec<CommentMessage>) -> CommentThread {
+        let mut thread = CommentThread::new(created_at.to_string());
+        thread.thread = messages;
+        thread
+    }
+
+    fn local_file() -> CommentsFile {
+        let mut file = CommentsFile::default();
+        file.comments.insert(
+            "c1".to_string(),
+            thread(
+                "2024-05-01T09:00:00Z",
+                vec![message("m_1", "Ann", "2024-05-01T09:00:00Z", "Source?")],
+            ),
+        );
+        file
+    }
+
+    /// The reviewer's copy: a reply on c1 and a new thread they numbered c2
+    fn returned() -> (CommentsFile, String) {
+        let mut file = local_file();
+        file.comments
+            .get_mut("c1")
+            .unwrap()
+            .thread
+            .push(message("m_2", "Rev", "2024-05-02T09:00:00Z", "Added one."));
+        file.comments.insert(
+            "c2".to_string(),
+            thread(
+                "2024-05-02T10:00:00Z",
+                vec![message("m_3", "Rev", "2024-05-02T10:00:00Z", "Expand this.")],
+            ),
+        );
+        let markdown = NOTE.replace("a paragraph that", "<mark>a paragraph that</mark><sup>[c2]</sup>");
+        (file, markdown)
+    }
+
+    #[test]
+    fn reply_to_an_existing_thread_is_appended_once() {
+        let (returned, returned_markdown) = returned();
+        let mut local = local_file();
+        let mut report = ReviewImportReport::default();
+        merge_review(&mut local, NOTE, &returned, &returned_markdown, &mut report);
+
+        assert_eq!(report.replies_added, 1);
+        let c1 = &local.comments["c1"];
+        let bodies: Vec<&str> = c1.thread.iter().map(|m| m.body.as_str()).collect();
+        assert_eq!(bodies, ["Source?", "Added one."]);
+    }
+
+    #[test]
+    fn importing_twice_adds_nothing_the_second_time() {
+        let (returned, returned_markdown) = returned();
+        let mut local = local_file();
+        let mut first = ReviewImportReport::default();
+        let merged = merge_review(&mut local, NOTE, &returned, &returned_markdown, &mut first);
+        assert_eq!((first.threads_added, first.replies_added), (1, 1));
+
+        let before = serde_json::to_string(&local).unwrap();
+        let mut second = ReviewImportReport::default();
+        let again = merge_review(&mut local, &merged, &returned, &returned_markdown, &mut second);
+        assert_eq!((second.threads_added, second.replies_added, second.threads_resolved), (0, 0, 0));
+        assert_eq!(again, merged);
+        assert_eq!(serde_json::to_string(&local).unwrap(), before);
+    }
+
+    #[test]
+    fn new_thread_gets_a_fresh_id_when_its_id_is_taken() {
+        let (returned, returned_markdown) = returned();
+        let mut local = local_file();
+        // The author opened their own c2 after sending the package
+        local.comments.insert(
+            "c2".to_string(),
+            thread(
+                "2024-05-03T09:00:00Z",
+                vec![message("m_4", "Ann", "2024-05-03T09:00:00Z", "Mine.")],
+            ),
+        );
+        let mut report = ReviewImportReport::default();
+        let merged = merge_review(&mut local, NOTE, &returned, &returned_markdown, &mut report);
+
+        assert_eq!(report.threads_added, 1);
+        assert!(report.unanchored.is_empty());
+        assert_eq!(local.comments["c2"].thread[0].body, "Mine.");
+        assert_eq!(local.comments["c3"].thread[0].body, "Expand this.");
+        assert!(merged.contains("<mark>a paragraph that</mark><sup>[c3]</sup>"));
+    }
+}
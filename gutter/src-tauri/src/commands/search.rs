@@ -1,17 +1,21 @@
 use serde::Serialize;
 use std::fs;
 use std::path::Path;
+use super::workspace;
 
 #[derive(Serialize, Clone)]
 #[serde(tag = "type")]
 pub enum SearchResult {
     Heading {
+        /// Workspace root the file was found under
+        root: String,
         path: String,
         text: String,
         level: u8,
         line: usize,
     },
     Content {
+        root: String,
         path: String,
         line: usize,
         snippet: String,
@@ -24,8 +28,14 @@ const MAX_CONTENT: usize = 40;
 const MAX_FILE_SIZE: u64 = 1_000_000; // 1MB
 const SNIPPET_LEN: usize = 80;
 
+/// Search `workspace`, or every folder in `roots` for a multi-root
+/// workspace. Results are capped across all roots, in root order.
 #[tauri::command]
-pub fn search_workspace(workspace: String, query: String) -> Result<Vec<SearchResult>, String> {
+pub fn search_workspace(
+    workspace: String,
+    query: String,
+    roots: Option<Vec<String>>,
+) -> Result<Vec<SearchResult>, String> {
     if query.is_empty() {
         return Ok(vec![]);
     }
@@ -34,66 +44,32 @@ pub fn search_workspace(workspace: String, query: String) -> Result<Vec<SearchRe
     let mut headings: Vec<SearchResult> = Vec::new();
     let mut content: Vec<SearchResult> = Vec::new();
 
-    collect_md_files(Path::new(&workspace), &query_lower, &mut headings, &mut content, 0);
-
-    // Truncate to caps
-    headings.truncate(MAX_HEADINGS);
-    content.truncate(MAX_CONTENT);
-
-    headings.extend(content);
-    Ok(headings)
-}
-
-fn collect_md_files(
-    dir: &Path,
-    query: &str,
-    headings: &mut Vec<SearchResult>,
-    content: &mut Vec<SearchResult>,
-    depth: u32,
-) {
-    if depth > 10 {
-        return;
-    }
-    if headings.len() >= MAX_HEADINGS && content.len() >= MAX_CONTENT {
-        return;
-    }
-
-    let entries = match fs::read_dir(dir) {
-        Ok(e) => e,
-        Err(_) => return,
-    };
-
-    for entry in entries.flatten() {
-        let path = entry.path();
-        let name = entry.file_name().to_string_lossy().to_string();
-
-        // Skip hidden files and comment files
-        if name.starts_with('.') {
-            continue;
-        }
-        if name.ends_with(".comments.json") || name.ends_with(".comments.md") {
-            continue;
-        }
-
-        if path.is_dir() {
-            collect_md_files(&path, query, headings, content, depth + 1);
-        } else if name.ends_with(".md") || name.ends_with(".markdown") {
+    let roots = roots.filter(|r| !r.is_empty()).unwrap_or_else(|| vec![workspace]);
+    'roots: for root in &roots {
+        for path in workspace::collect_markdown_files(Path::new(root)) {
+            if headings.len() >= MAX_HEADINGS && content.len() >= MAX_CONTENT {
+                break 'roots;
+            }
             // Skip large files
             if let Ok(meta) = fs::metadata(&path) {
                 if meta.len() > MAX_FILE_SIZE {
                     continue;
                 }
             }
-            search_file(&path, query, headings, content);
-        }
-
-        if headings.len() >= MAX_HEADINGS && content.len() >= MAX_CONTENT {
-            return;
+            search_file(root, &path, &query_lower, &mut headings, &mut content);
         }
     }
+
+    // Truncate to caps
+    headings.truncate(MAX_HEADINGS);
+    content.truncate(MAX_CONTENT);
+
+    headings.extend(content);
+    Ok(headings)
 }
 
 fn search_file(
+    root: &str,
     path: &Path,
     query: &str,
     headings: &mut Vec<SearchResult>,
@@ -115,6 +91,7 @@ fn search_file(
                 let heading_text = line[level as usize..].trim_start_matches(' ').to_string();
                 if heading_text.to_lowercase().contains(query) {
                     headings.push(SearchResult::Heading {
+                        root: root.to_string(),
                         path: path_str.clone(),
                         text: heading_text,
                         level,
@@ -129,6 +106,7 @@ fn search_file(
             if let Some(match_pos) = line_lower.find(query) {
                 let (snippet, match_offset) = build_snippet(line, match_pos, query.len());
                 content.push(SearchResult::Content {
+                    root: root.to_string(),
                     path: path_str.clone(),
                     line: line_idx + 1,
                     snippet,
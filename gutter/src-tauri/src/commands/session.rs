@@ -0,0 +1,131 @@
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::Mutex;
+use std::time::Duration;
+use tauri::{AppHandle, Manager};
+use super::app_dirs;
+
+/// How often sessions updated since the last write are saved.
+const FLUSH_INTERVAL: Duration = Duration::from_secs(5);
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct SessionTab {
+    pub path: String,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub pinned: bool,
+    /// Editor selection (document position) when the tab was last seen
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cursor: Option<u32>,
+    /// Scroll offset of the editor, in pixels
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub scroll: Option<f64>,
+}
+
+/// Which side panels are open.
+#[derive(Serialize, Deserialize, Clone, Default)]
+#[serde(rename_all = "camelCase", default)]
+pub struct PanelLayout {
+    pub file_tree: bool,
+    pub comments: bool,
+    pub outline: bool,
+    pub history: bool,
+    pub tags: bool,
+}
+
+/// What a window had open in a workspace, restored when it's reopened.
+#[derive(Serialize, Deserialize, Clone, Default)]
+#[serde(rename_all = "camelCase", default)]
+pub struct Session {
+    /// Folder or `.gutter-workspace` file the session belongs to
+    pub workspace: String,
+    pub tabs: Vec<SessionTab>,
+    pub active_tab: Option<String>,
+    pub panels: PanelLayout,
+    pub source_mode: bool,
+}
+
+struct SessionState {
+    /// Updates not yet written, by workspace
+    pending: Mutex<HashMap<String, Session>>,
+}
+
+/// `<state dir>/sessions/<hash of the workspace path>.json`
+fn session_path(workspace: &str) -> PathBuf {
+    let mut hasher = Sha256::new();
+    hasher.update(workspace.as_bytes());
+    let hash = format!("{:x}", hasher.finalize());
+    app_dirs::state_dir()
+        .join("sessions")
+        .join(format!("{}.json", &hash[..16]))
+}
+
+fn write_session(session: &Session) -> Result<(), String> {
+    let path = session_path(&session.workspace);
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir).map_err(|e| format!("Failed to create sessions dir: {}", e))?;
+    }
+    let json = serde_json::to_string_pretty(session)
+        .map_err(|e| format!("Failed to serialize session: {}", e))?;
+    fs::write(&path, json).map_err(|e| format!("Failed to write session: {}", e))
+}
+
+pub fn init(app: &tauri::App) {
+    app.manage(SessionState {
+        pending: Mutex::new(HashMap::new()),
+    });
+    let app_handle = app.handle().clone();
+    std::thread::spawn(move || loop {
+        std::thread::sleep(FLUSH_INTERVAL);
+        if let Err(e) = flush(&app_handle) {
+            log::warn!("{}", e);
+        }
+    });
+}
+
+/// Write every session updated since the last flush. Called periodically
+/// and when the window goes away.
+pub fn flush(app: &AppHandle) -> Result<(), String> {
+    let state = app.state::<SessionState>();
+    let pending: Vec<Session> = {
+        let mut guard = state.pending.lock().map_err(|e| e.to_string())?;
+        guard.drain().map(|(_, s)| s).collect()
+    };
+    for session in &pending {
+        write_session(session)?;
+    }
+    Ok(())
+}
+
+/// Record the window's current session. Kept in memory and written on
+/// the next flush, so this is cheap to call on every change.
+#[tauri::command]
+pub fn update_session(app: AppHandle, session: Session) -> Result<(), String> {
+    if session.workspace.is_empty() {
+        return Err("Session has no workspace".to_string());
+    }
+    let state = app.state::<SessionState>();
+    let mut guard = state.pending.lock().map_err(|e| e.to_string())?;
+    guard.insert(session.workspace.clone(), session);
+    Ok(())
+}
+
+/// The last session saved for `workspace`, if any.
+#[tauri::command]
+pub fn load_session(app: AppHandle, workspace: String) -> Result<Option<Session>, String> {
+    let state = app.state::<SessionState>();
+    if let Some(session) = state.pending.lock().map_err(|e| e.to_string())?.get(&workspace) {
+        return Ok(Some(session.clone()));
+    }
+    let path = session_path(&workspace);
+    if !path.exists() {
+        return Ok(None);
+    }
+    let text = fs::read_to_string(&path).map_err(|e| format!("Failed to read session: {}", e))?;
+    // A session we can't read isn't worth an error; start fresh
+    Ok(serde_json::from_str::<Session>(&text)
+        .ok()
+        .filter(|s| s.workspace == workspace))
+}
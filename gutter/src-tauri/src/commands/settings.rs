@@ -1,32 +1,543 @@
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use super::{app_dirs, keybindings};
+
+type SettingsMap = serde_json::Map<String, serde_json::Value>;
+
+/// Which file a setting's value comes from.
+#[derive(Serialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum SettingsLayer {
+    Global,
+    Workspace,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Default, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum Theme {
+    #[default]
+    Light,
+    Dark,
+    System,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Default, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum FontFamily {
+    #[default]
+    Serif,
+    Sans,
+    Mono,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Default, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum EditorWidth {
+    Narrow,
+    #[default]
+    Medium,
+    Wide,
+    Full,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Default, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum LineHeight {
+    Compact,
+    #[default]
+    Comfortable,
+    Spacious,
+}
+
+/// Sidebar widths in pixels. Missing panels keep their default.
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase", default)]
+pub struct PanelWidths {
+    pub file_tree: u32,
+    pub comments: u32,
+    pub history: u32,
+    pub tags: u32,
+}
+
+impl Default for PanelWidths {
+    fn default() -> Self {
+        PanelWidths {
+            file_tree: 224,
+            comments: 288,
+            history: 288,
+            tags: 288,
+        }
+    }
+}
+
+/// Everything in `config.json`. Missing keys take their default; keys this
+/// version doesn't know are kept in `extra` and written back unchanged.
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase", default)]
+pub struct Settings {
+    /// Format version the file was last migrated to
+    pub version: u32,
+    pub theme: Theme,
+    pub font_size: u32,
+    pub font_family: FontFamily,
+    /// Milliseconds; 0 turns auto-save off
+    pub auto_save_interval: u64,
+    pub panel_widths: PanelWidths,
+    /// Superseded by the recent registry, which it seeds once
+    pub recent_files: Vec<String>,
+    pub spell_check_enabled: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub default_author: Option<String>,
+    pub editor_width: EditorWidth,
+    pub line_height: LineHeight,
+    pub accent_color: String,
+    /// Descend into symlinked folders when walking a workspace
+    pub follow_symlinks: bool,
+    /// Where pasted and dropped images go, relative to the note's folder
+    pub attachment_folder: String,
+    /// Templates folder; relative paths are taken from the workspace, else
+    /// from the settings directory
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub templates_folder: Option<String>,
+    /// Extra gitignore-style patterns applied across the workspace
+    pub ignore_patterns: Vec<String>,
+    /// Command id -> shortcut, overriding the default; an empty shortcut
+    /// unbinds the command
+    pub keybindings: BTreeMap<String, String>,
+    #[serde(flatten)]
+    pub extra: SettingsMap,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Settings {
+            version: SETTINGS_VERSION,
+            theme: Theme::default(),
+            font_size: 16,
+            font_family: FontFamily::default(),
+            auto_save_interval: 0,
+            panel_widths: PanelWidths::default(),
+            recent_files: Vec::new(),
+            spell_check_enabled: false,
+            default_author: None,
+            editor_width: EditorWidth::default(),
+            line_height: LineHeight::default(),
+            accent_color: "teal".to_string(),
+            follow_symlinks: true,
+            attachment_folder: "assets".to_string(),
+            templates_folder: None,
+            ignore_patterns: Vec::new(),
+            keybindings: BTreeMap::new(),
+            extra: SettingsMap::new(),
+        }
+    }
+}
+
+const ACCENT_COLORS: &[&str] = &["indigo", "blue", "violet", "rose", "orange", "green", "teal"];
+
+impl Settings {
+    /// What's wrong with `key`'s value beyond its type, if anything.
+    fn problem(&self, key: &str) -> Option<String> {
+        match key {
+            "fontSize" if !(12..=24).contains(&self.font_size) => {
+                Some("must be between 12 and 24".to_string())
+            }
+            "autoSaveInterval" if self.auto_save_interval > 60_000 => {
+                Some("must be at most 60000 (one minute)".to_string())
+            }
+            "panelWidths" => {
+                let w = &self.panel_widths;
+                [w.file_tree, w.comments, w.history, w.tags]
+                    .iter()
+                    .any(|px| !(120..=2000).contains(px))
+                    .then(|| "widths must be between 120 and 2000".to_string())
+            }
+            "accentColor" if !ACCENT_COLORS.contains(&self.accent_color.as_str()) => {
+                Some(format!("must be one of {}", ACCENT_COLORS.join(", ")))
+            }
+            "attachmentFolder" if self.attachment_folder.trim().is_empty() => {
+                Some("must not be empty".to_string())
+            }
+            "keybindings" => keybindings::problem(&self.keybindings),
+            _ => None,
+        }
+    }
+}
+
+/// A value that was ignored in favor of the default.
+#[derive(Serialize, Clone)]
+pub struct SettingsIssue {
+    pub layer: SettingsLayer,
+    /// Empty when the whole file couldn't be read
+    pub key: String,
+    pub message: String,
+}
+
+/// Upgrades, each from version `i` to `i + 1`. Files without a `version`
+/// are version 0.
+const MIGRATIONS: &[fn(&mut SettingsMap)] = &[migrate_legacy_font_family];
+
+pub const SETTINGS_VERSION: u32 = MIGRATIONS.len() as u32;
+
+/// Font families from before the serif/sans/mono choice become serif.
+fn migrate_legacy_font_family(map: &mut SettingsMap) {
+    if let Some(family) = map.get_mut("fontFamily") {
+        if !matches!(family.as_str(), Some("serif" | "sans" | "mono")) {
+            *family = serde_json::Value::from("serif");
+        }
+    }
+}
+
+/// Bring a settings object up to `SETTINGS_VERSION`. Objects from a newer
+/// version are left alone.
+fn migrate(map: &mut SettingsMap) {
+    let from = map.get("version").and_then(|v| v.as_u64()).unwrap_or(0) as usize;
+    if from >= MIGRATIONS.len() {
+        return;
+    }
+    for migration in &MIGRATIONS[from..] {
+        migration(map);
+    }
+    map.insert("version".to_string(), SETTINGS_VERSION.into());
+}
+
+/// Split a settings object into values to use and issues: each known key
+/// must deserialize and pass `Settings::problem`, else it's dropped so the
+/// default applies. Unknown keys always pass.
+fn validate(map: SettingsMap, layer: SettingsLayer) -> (SettingsMap, Vec<SettingsIssue>) {
+    let mut valid = SettingsMap::new();
+    let mut issues = Vec::new();
+    for (key, value) in map {
+        let mut probe = SettingsMap::new();
+        probe.insert(key.clone(), value.clone());
+        let problem = match serde_json::from_value::<Settings>(probe.into()) {
+            Ok(parsed) if parsed.extra.contains_key(&key) => None,
+            Ok(parsed) => parsed.problem(&key),
+            Err(e) => Some(e.to_string()),
+        };
+        match problem {
+            Some(message) => issues.push(SettingsIssue { layer, key, message }),
+            None => {
+                valid.insert(key, value);
+            }
+        }
+    }
+    (valid, issues)
+}
+
+fn to_settings(map: SettingsMap) -> Settings {
+    // Only validated values get here, so this can't fail
+    serde_json::from_value(map.into()).unwrap_or_default()
+}
 
 fn settings_dir() -> PathBuf {
-    dirs::home_dir()
-        .unwrap_or_else(|| PathBuf::from("."))
-        .join(".gutter")
+    app_dirs::config_dir()
 }
 
-fn settings_path() -> PathBuf {
+pub fn settings_path() -> PathBuf {
     settings_dir().join("config.json")
 }
 
-#[tauri::command]
-pub fn read_settings() -> Result<String, String> {
-    let path = settings_path();
-    if !path.exists() {
-        return Ok("{}".to_string());
+/// Per-workspace overrides, `<workspace>/.gutter/config.json`.
+pub fn workspace_settings_path(workspace: &Path) -> PathBuf {
+    workspace.join(".gutter").join("config.json")
+}
+
+/// Whether `path` is a workspace settings file.
+pub fn is_workspace_settings(path: &Path) -> bool {
+    path.ends_with(".gutter/config.json")
+}
+
+/// The settings object in `path`, migrated; empty if the file is missing.
+fn read_layer(path: &Path) -> Result<SettingsMap, String> {
+    let text = match fs::read_to_string(path) {
+        Ok(text) => text,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(SettingsMap::new()),
+        Err(e) => return Err(format!("Failed to read settings: {}", e)),
+    };
+    let mut map = match serde_json::from_str(&text) {
+        Ok(serde_json::Value::Object(map)) => map,
+        Ok(_) => return Err("Invalid settings: expected a JSON object".to_string()),
+        Err(e) => return Err(format!("Invalid settings: {}", e)),
+    };
+    migrate(&mut map);
+    Ok(map)
+}
+
+/// A layer's valid values, with an issue for the file or each bad value.
+fn load_layer(path: &Path, layer: SettingsLayer, issues: &mut Vec<SettingsIssue>) -> SettingsMap {
+    match read_layer(path) {
+        Ok(map) => {
+            let (valid, found) = validate(map, layer);
+            issues.extend(found);
+            valid
+        }
+        Err(message) => {
+            issues.push(SettingsIssue { layer, key: String::new(), message });
+            SettingsMap::new()
+        }
     }
-    fs::read_to_string(&path).map_err(|e| format!("Failed to read settings: {}", e))
 }
 
+/// Settings as loaded for a window: the merged view plus what the editor
+/// needs to save each value back to its own layer.
+#[derive(Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct LoadedSettings {
+    /// Global values with the workspace's overrides on top
+    pub settings: Settings,
+    /// Global values alone
+    pub global: Settings,
+    /// Keys the workspace overrides
+    pub workspace_keys: Vec<String>,
+    pub issues: Vec<SettingsIssue>,
+}
+
+fn load_all(workspace: Option<&Path>) -> LoadedSettings {
+    let mut issues = Vec::new();
+    let global = load_layer(&settings_path(), SettingsLayer::Global, &mut issues);
+    let mut overrides = match workspace {
+        Some(ws) => load_layer(&workspace_settings_path(ws), SettingsLayer::Workspace, &mut issues),
+        None => SettingsMap::new(),
+    };
+    // Migration stamps a version; it isn't a workspace preference
+    overrides.remove("version");
+    let workspace_keys = overrides.keys().cloned().collect();
+    let mut merged = global.clone();
+    // Overrides replace whole top-level keys; objects aren't merged
+    merged.extend(overrides);
+    LoadedSettings {
+        settings: to_settings(merged),
+        global: to_settings(global),
+        workspace_keys,
+        issues,
+    }
+}
+
+/// Settings as they apply in `workspace`. Invalid values fall back to
+/// their defaults.
+pub fn load(workspace: Option<&Path>) -> Settings {
+    load_all(workspace).settings
+}
+
+/// Settings for `workspace` (global alone without one), with anything
+/// that was ignored reported in `issues`.
+#[tauri::command]
+pub fn read_settings(workspace: Option<String>) -> LoadedSettings {
+    load_all(workspace.as_deref().map(Path::new))
+}
+
+/// Update the global settings with the keys in `content`. Values are
+/// validated and the whole write refused if any is bad; keys `content`
+/// doesn't mention keep their value in the file.
 #[tauri::command]
 pub fn write_settings(content: String) -> Result<(), String> {
+    let mut incoming = match serde_json::from_str(&content) {
+        Ok(serde_json::Value::Object(map)) => map,
+        Ok(_) => return Err("Invalid settings: expected a JSON object".to_string()),
+        Err(e) => return Err(format!("Invalid settings: {}", e)),
+    };
+    migrate(&mut incoming);
+    let (valid, issues) = validate(incoming, SettingsLayer::Global);
+    if !issues.is_empty() {
+        return Err(describe(&issues));
+    }
     let dir = settings_dir();
     if !dir.exists() {
         fs::create_dir_all(&dir)
             .map_err(|e| format!("Failed to create settings directory: {}", e))?;
     }
-    fs::write(settings_path(), &content)
+
+    // Start from what's on disk so hand-added keys survive. A file that
+    // doesn't parse is set aside rather than lost.
+    let mut merged = match read_layer(&settings_path()) {
+        Ok(existing) => validate(existing, SettingsLayer::Global).0,
+        Err(_) => {
+            fs::copy(settings_path(), dir.join("config.json.bak"))
+                .map_err(|e| format!("Failed to back up settings: {}", e))?;
+            SettingsMap::new()
+        }
+    };
+    merged.extend(valid);
+    let settings = to_settings(merged);
+    let json = serde_json::to_string_pretty(&settings)
+        .map_err(|e| format!("Failed to serialize settings: {}", e))?;
+    fs::write(settings_path(), json)
         .map_err(|e| format!("Failed to write settings: {}", e))
 }
+
+fn describe(issues: &[SettingsIssue]) -> String {
+    let parts: Vec<String> = issues
+        .iter()
+        .map(|i| format!("{}: {}", i.key, i.message))
+        .collect();
+    format!("Invalid settings: {}", parts.join("; "))
+}
+
+/// The workspace's overrides as JSON; `{}` if it has none.
+#[tauri::command]
+pub fn read_workspace_settings(workspace: String) -> Result<String, String> {
+    let path = workspace_settings_path(Path::new(&workspace));
+    if !path.exists() {
+        return Ok("{}".to_string());
+    }
+    fs::read_to_string(&path).map_err(|e| format!("Failed to read workspace settings: {}", e))
+}
+
+/// Replace the workspace's overrides. Only the keys given are written;
+/// each must be valid.
+#[tauri::command]
+pub fn write_workspace_settings(workspace: String, content: String) -> Result<(), String> {
+    let parsed = match serde_json::from_str(&content) {
+        Ok(serde_json::Value::Object(map)) => map,
+        Ok(_) => return Err("Invalid settings: expected a JSON object".to_string()),
+        Err(e) => return Err(format!("Invalid settings: {}", e)),
+    };
+    let (_, issues) = validate(parsed, SettingsLayer::Workspace);
+    if !issues.is_empty() {
+        return Err(describe(&issues));
+    }
+    let path = workspace_settings_path(Path::new(&workspace));
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir).map_err(|e| format!("Failed to create .gutter dir: {}", e))?;
+    }
+    fs::write(&path, &content).map_err(|e| format!("Failed to write workspace settings: {}", e))
+}
+
+/// Settings as they apply in `workspace`: global values with the
+/// workspace's overrides on top.
+#[tauri::command]
+pub fn read_merged_settings(workspace: Option<String>) -> Settings {
+    load(workspace.as_deref().map(Path::new))
+}
+
+/// For each key set in either file, the file its value comes from.
+#[tauri::command]
+pub fn settings_sources(workspace: Option<String>) -> BTreeMap<String, SettingsLayer> {
+    let mut sources: BTreeMap<String, SettingsLayer> = read_layer(&settings_path())
+        .unwrap_or_default()
+        .into_iter()
+        .map(|(key, _)| (key, SettingsLayer::Global))
+        .collect();
+    if let Some(ws) = workspace {
+        for (key, _) in read_layer(&workspace_settings_path(Path::new(&ws))).unwrap_or_default() {
+            if key != "version" {
+                sources.insert(key, SettingsLayer::Workspace);
+            }
+        }
+    }
+    sources
+}
+
+/// The `defaultAuthor` configured for `workspace`, if any.
+pub fn configured_author(workspace: Option<&Path>) -> Option<String> {
+    load(workspace)
+        .default_author
+        .map(|s| s.trim().to_string())
+        .filter(|s| !s.is_empty())
+}
+
+/// Whether workspace traversal follows symlinked folders
+/// (`followSymlinks`, on by default).
+pub fn follow_symlinks(workspace: Option<&Path>) -> bool {
+    load(workspace).follow_symlinks
+}
+
+/// Where pasted and dropped images go, relative to the note's folder
+/// (`attachmentFolder`, `assets` by default).
+pub fn attachment_folder(workspace: Option<&Path>) -> String {
+    load(workspace).attachment_folder.trim().to_string()
+}
+
+/// Extra gitignore-style patterns applied across the workspace
+/// (`ignorePatterns`).
+pub fn ignore_patterns(workspace: Option<&Path>) -> Vec<String> {
+    load(workspace).ignore_patterns
+}
+
+/// The templates folder (`templatesFolder`), if one is configured.
+/// Relative paths are taken from the workspace, else from the settings
+/// directory.
+pub fn templates_folder(workspace: Option<&Path>) -> Option<PathBuf> {
+    let folder = load(workspace).templates_folder?;
+    let folder = folder.trim();
+    if folder.is_empty() {
+        return None;
+    }
+    let base = workspace.map(Path::to_path_buf).unwrap_or_else(settings_dir);
+    Some(base.join(folder))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn map(value: serde_json::Value) -> SettingsMap {
+        value.as_object().unwrap().clone()
+    }
+
+    #[test]
+    fn bad_values_are_rejected_per_key() {
+        let (valid, issues) = validate(
+            map(json!({
+                "fontSize": 40,
+                "theme": 3,
+                "accentColor": "plaid",
+                "lineHeight": "spacious",
+                "autoSaveInterval": 5000,
+            })),
+            SettingsLayer::Workspace,
+        );
+        let rejected: Vec<&str> = issues.iter().map(|i| i.key.as_str()).collect();
+        assert_eq!(rejected, ["accentColor", "fontSize", "theme"]);
+        assert!(issues.iter().all(|i| i.layer == SettingsLayer::Workspace));
+        assert_eq!(issues[1].message, "must be between 12 and 24");
+
+        let settings = to_settings(valid);
+        assert_eq!(settings.font_size, 16);
+        assert!(settings.theme == Theme::Light);
+        assert_eq!(settings.accent_color, "teal");
+        assert!(settings.line_height == LineHeight::Spacious);
+        assert_eq!(settings.auto_save_interval, 5000);
+    }
+
+    #[test]
+    fn unknown_keys_are_kept() {
+        let (valid, issues) = validate(
+            map(json!({ "futureFeature": { "on": true }, "fontSize": 18 })),
+            SettingsLayer::Global,
+        );
+        assert!(issues.is_empty());
+        let settings = to_settings(valid);
+        assert_eq!(settings.font_size, 18);
+        assert_eq!(settings.extra["futureFeature"], json!({ "on": true }));
+        let written = serde_json::to_value(&settings).unwrap();
+        assert_eq!(written["futureFeature"], json!({ "on": true }));
+    }
+
+    #[test]
+    fn migrations_run_once_and_bump_the_version() {
+        let mut settings = map(json!({ "fontFamily": "Georgia", "fontSize": 18 }));
+        migrate(&mut settings);
+        assert_eq!(settings["fontFamily"], json!("serif"));
+        assert_eq!(settings["version"], json!(SETTINGS_VERSION));
+        assert_eq!(settings["fontSize"], json!(18));
+
+        // Already migrated: a later odd value is validation's business
+        settings.insert("fontFamily".to_string(), json!("Georgia"));
+        migrate(&mut settings);
+        assert_eq!(settings["fontFamily"], json!("Georgia"));
+
+        let mut newer = map(json!({ "version": SETTINGS_VERSION + 1, "fontFamily": "Georgia" }));
+        migrate(&mut newer);
+        assert_eq!(newer["fontFamily"], json!("Georgia"));
+        assert_eq!(newer["version"], json!(SETTINGS_VERSION + 1));
+
+        let mut modern = map(json!({ "fontFamily": "mono" }));
+        migrate(&mut modern);
+        assert_eq!(modern["fontFamily"], json!("mono"));
+    }
+}
@@ -0,0 +1,142 @@
+use notify::{Event, EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{mpsc, Arc, Mutex};
+use std::time::Duration;
+use tauri::{AppHandle, Emitter, Manager, WebviewWindow};
+use super::settings::{self, LoadedSettings};
+
+/// Editors and sync tools often write a file in several steps; wait for
+/// this much quiet before reading it.
+const DEBOUNCE: Duration = Duration::from_millis(300);
+
+struct SettingsWatcherState {
+    // Window label -> watcher for that window's workspace
+    watchers: Mutex<HashMap<String, Arc<Mutex<RecommendedWatcher>>>>,
+}
+
+pub fn init(app: &tauri::App) {
+    app.manage(SettingsWatcherState {
+        watchers: Mutex::new(HashMap::new()),
+    });
+}
+
+/// Stop watching for a window that has closed.
+pub fn forget(app: &AppHandle, label: &str) {
+    let state = app.state::<SettingsWatcherState>();
+    state.watchers.lock().unwrap().remove(label);
+}
+
+/// Payload of `settings-changed`: settings as `read_settings` would return
+/// them for `workspace`.
+#[derive(Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct SettingsChanged {
+    pub workspace: Option<String>,
+    #[serde(flatten)]
+    pub loaded: LoadedSettings,
+}
+
+fn is_settings_event(event: &Event, files: &[PathBuf]) -> bool {
+    matches!(
+        event.kind,
+        EventKind::Create(_) | EventKind::Modify(_) | EventKind::Remove(_)
+    ) && event.paths.iter().any(|p| files.iter().any(|f| f == p))
+}
+
+/// Watch the global settings file, and `workspace`'s overrides if it has
+/// any, emitting `settings-changed` whenever either is edited — by hand,
+/// by another window or by a sync tool. The event goes to the calling
+/// window, and replaces any earlier watch it made.
+#[tauri::command]
+pub fn watch_settings(window: WebviewWindow, workspace: Option<String>) -> Result<(), String> {
+    let label = window.label().to_string();
+    let state = window.state::<SettingsWatcherState>();
+    let mut guard = state.watchers.lock().map_err(|e| e.to_string())?;
+
+    guard.remove(&label);
+
+    let global = settings::settings_path();
+    // The global folder is watched even before anything is saved there
+    if let Some(dir) = global.parent() {
+        fs::create_dir_all(dir).map_err(|e| format!("Failed to create settings directory: {}", e))?;
+    }
+    let mut files = vec![global];
+    if let Some(ws) = &workspace {
+        files.push(settings::workspace_settings_path(Path::new(ws)));
+    }
+    // Watch the folders, not the files: editors save by replacing the file,
+    // which would drop a watch on the file itself. A workspace without a
+    // `.gutter` folder yet is watched at its root until one appears.
+    let mut dirs: Vec<PathBuf> = Vec::new();
+    let mut missing: Option<PathBuf> = None;
+    for file in &files {
+        let Some(dir) = file.parent() else {
+            continue;
+        };
+        if dir.is_dir() {
+            dirs.push(dir.to_path_buf());
+        } else if let Some(root) = dir.parent().filter(|r| r.is_dir()) {
+            dirs.push(root.to_path_buf());
+            missing = Some(dir.to_path_buf());
+        }
+    }
+
+    let (tx, rx) = mpsc::channel::<()>();
+    let created = missing.clone();
+    let mut watcher = RecommendedWatcher::new(
+        move |res: Result<Event, notify::Error>| {
+            if let Ok(event) = res {
+                let appeared = created.as_ref().is_some_and(|dir| {
+                    matches!(event.kind, EventKind::Create(_)) && event.paths.iter().any(|p| p == dir)
+                });
+                if appeared || is_settings_event(&event, &files) {
+                    let _ = tx.send(());
+                }
+            }
+        },
+        notify::Config::default(),
+    )
+    .map_err(|e| format!("Failed to create settings watcher: {}", e))?;
+
+    for dir in &dirs {
+        watcher
+            .watch(dir, RecursiveMode::NonRecursive)
+            .map_err(|e| format!("Failed to watch {}: {}", dir.display(), e))?;
+    }
+    let watcher = Arc::new(Mutex::new(watcher));
+    let handle = Arc::downgrade(&watcher);
+
+    // Ends when the watcher, and with it the sender, is dropped
+    let app_handle = window.app_handle().clone();
+    let event_label = label.clone();
+    std::thread::spawn(move || {
+        while rx.recv().is_ok() {
+            while rx.recv_timeout(DEBOUNCE).is_ok() {}
+            // Once `.gutter` exists, watch inside it; its config may already
+            // be there, so the read below still picks it up
+            if let Some(dir) = missing.as_ref().filter(|d| d.is_dir()) {
+                if let Some(watcher) = handle.upgrade() {
+                    match watcher.lock().unwrap().watch(dir, RecursiveMode::NonRecursive) {
+                        Ok(()) => missing = None,
+                        Err(e) => log::warn!("Failed to watch {}: {}", dir.display(), e),
+                    }
+                }
+            }
+            let loaded = settings::read_settings(workspace.clone());
+            let _ = app_handle.emit_to(
+                event_label.as_str(),
+                "settings-changed",
+                SettingsChanged {
+                    workspace: workspace.clone(),
+                    loaded,
+                },
+            );
+        }
+    });
+
+    guard.insert(label, watcher);
+    Ok(())
+}
@@ -0,0 +1,261 @@
+use serde::Serialize;
+use std::fs;
+use std::path::Path;
+use tauri::WebviewWindow;
+use super::comment_storage;
+use super::comments::{self, SuggestionKind};
+use super::{watcher, workspace_file};
+
+/// A `<del>old</del><ins>new</ins><sup>[sN]</sup>` marker in a markdown
+/// document. At least one of the two parts is present.
+#[derive(Clone, Debug)]
+pub struct SuggestionMarker {
+    pub id: String,
+    pub deleted: Option<String>,
+    pub inserted: Option<String>,
+    /// Byte range of the whole marker, tags included
+    pub start: usize,
+    pub end: usize,
+}
+
+impl SuggestionMarker {
+    pub fn kind(&self) -> SuggestionKind {
+        match (&self.deleted, &self.inserted) {
+            (Some(_), Some(_)) => SuggestionKind::Replace,
+            (Some(_), None) => SuggestionKind::Delete,
+            _ => SuggestionKind::Insert,
+        }
+    }
+
+    /// Text that replaces the marker when the suggestion is accepted or rejected
+    fn resolved_text(&self, accept: bool) -> &str {
+        let part = if accept { &self.inserted } else { &self.deleted };
+        part.as_deref().unwrap_or("")
+    }
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SuggestionView {
+    pub id: String,
+    pub kind: SuggestionKind,
+    pub author: Option<String>,
+    pub timestamp: Option<String>,
+    pub deleted_text: Option<String>,
+    pub inserted_text: Option<String>,
+    /// False when the sidecar lists a suggestion whose marker is gone
+    pub anchored: bool,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SuggestionOutcome {
+    /// The rewritten document, for the editor to reload
+    pub markdown: String,
+    pub applied: usize,
+}
+
+/// Length of `<tag>…</tag>` at the start of `s` and its inner text.
+fn tagged<'a>(s: &'a str, tag: &str) -> Option<(usize, &'a str)> {
+    let open = format!("<{}>", tag);
+    let close = format!("</{}>", tag);
+    let inner = s.strip_prefix(open.as_str())?;
+    let end = inner.find(close.as_str())?;
+    Some((open.len() + end + close.len(), &inner[..end]))
+}
+
+fn parse_marker(s: &str) -> Option<(Option<String>, Option<String>, String, usize)> {
+    let mut len = 0;
+    let deleted = tagged(s, "del").map(|(l, text)| {
+        len += l;
+        text.to_string()
+    });
+    let inserted = tagged(&s[len..], "ins").map(|(l, text)| {
+        len += l;
+        text.to_string()
+    });
+    if deleted.is_none() && inserted.is_none() {
+        return None;
+    }
+    let rest = s[len..].strip_prefix("<sup>[s")?;
+    let digits = rest.bytes().take_while(|b| b.is_ascii_digit()).count();
+    if digits == 0 || !rest[digits..].starts_with("]</sup>") {
+        return None;
+    }
+    let id = format!("s{}", &rest[..digits]);
+    len += "<sup>[s".len() + digits + "]</sup>".len();
+    Some((deleted, inserted, id, len))
+}
+
+/// Suggestion markers in document order.
+pub fn extract_suggestions(markdown: &str) -> Vec<SuggestionMarker> {
+    let mut markers = Vec::new();
+    let mut pos = 0;
+    while let Some(rel) = markdown[pos..].find('<') {
+        let start = pos + rel;
+        match parse_marker(&markdown[start..]) {
+            Some((deleted, inserted, id, len)) => {
+                markers.push(SuggestionMarker {
+                    id,
+                    deleted,
+                    inserted,
+                    start,
+                    end: start + len,
+                });
+                pos = start + len;
+            }
+            None => pos = start + 1,
+        }
+    }
+    markers
+}
+
+/// Accept or reject the suggestions `selected` picks, returning the new
+/// document and the ids that were resolved.
+pub fn resolve_markers(
+    markdown: &str,
+    accept: bool,
+    selected: impl Fn(&str) -> bool,
+) -> (String, Vec<String>) {
+    let mut out = String::with_capacity(markdown.len());
+    let mut resolved = Vec::new();
+    let mut pos = 0;
+    for marker in extract_suggestions(markdown) {
+        if !selected(&marker.id) {
+            continue;
+        }
+        out.push_str(&markdown[pos..marker.start]);
+        out.push_str(marker.resolved_text(accept));
+        pos = marker.end;
+        resolved.push(marker.id);
+    }
+    out.push_str(&markdown[pos..]);
+    (out, resolved)
+}
+
+fn resolve(
+    window: &WebviewWindow,
+    path: &str,
+    workspace: Option<&str>,
+    target: Option<&str>,
+    accept: bool,
+) -> Result<SuggestionOutcome, String> {
+    let workspace = workspace_file::owning_root(window, workspace.map(str::to_string), path);
+    let workspace = workspace.as_deref();
+    let md_path = Path::new(path);
+    let text = fs::read_to_string(md_path).map_err(|e| format!("Failed to read file: {}", e))?;
+    let markdown = comment_storage::split_embedded(&text).0;
+    let store = comment_storage::store_for(workspace);
+    let mut file = comments::load_comments_file(store.as_ref(), md_path)?.unwrap_or_default();
+
+    let (rewritten, resolved) =
+        resolve_markers(markdown, accept, |id| target.map_or(true, |t| t == id));
+    match target {
+        Some(t) if resolved.is_empty() => return Err(format!("Suggestion not found: {}", t)),
+        Some(t) => {
+            file.suggestions.remove(t);
+        }
+        // Resolving everything also clears entries whose marker was lost
+        None => file.suggestions.clear(),
+    }
+
+    // Rewrite the document first: the embedded store appends to it
+    watcher::mark_write(window, path);
+    fs::write(md_path, &rewritten).map_err(|e| format!("Failed to write file: {}", e))?;
+    if file.comments.is_empty() && file.suggestions.is_empty() {
+        watcher::mark_write(window, &store.location(md_path).to_string_lossy());
+        store.delete(md_path)?;
+    } else {
+        comments::save_comments_file(window, store.as_ref(), md_path, &file)?;
+    }
+
+    Ok(SuggestionOutcome {
+        markdown: rewritten,
+        applied: resolved.len(),
+    })
+}
+
+/// Pending suggestions for a note, in document order, followed by any the
+/// sidecar still lists but whose marker is gone.
+#[tauri::command]
+pub fn list_suggestions(
+    window: WebviewWindow,
+    path: String,
+    workspace: Option<String>,
+) -> Result<Vec<SuggestionView>, String> {
+    let workspace = workspace_file::owning_root(&window, workspace, &path);
+    let text = fs::read_to_string(&path).map_err(|e| format!("Failed to read file: {}", e))?;
+    let markdown = comment_storage::split_embedded(&text).0;
+    let store = comment_storage::store_for(workspace.as_deref());
+    let file = comments::load_comments_file(store.as_ref(), Path::new(&path))?.unwrap_or_default();
+
+    let markers = extract_suggestions(markdown);
+    let mut views: Vec<SuggestionView> = markers
+        .iter()
+        .map(|m| {
+            let meta = file.suggestions.get(&m.id);
+            SuggestionView {
+                id: m.id.clone(),
+                kind: m.kind(),
+                author: meta.map(|s| s.author.clone()),
+                timestamp: meta.map(|s| s.timestamp.clone()),
+                deleted_text: m.deleted.clone(),
+                inserted_text: m.inserted.clone(),
+                anchored: true,
+            }
+        })
+        .collect();
+    for (id, s) in &file.suggestions {
+        if markers.iter().any(|m| &m.id == id) {
+            continue;
+        }
+        views.push(SuggestionView {
+            id: id.clone(),
+            kind: s.kind,
+            author: Some(s.author.clone()),
+            timestamp: Some(s.timestamp.clone()),
+            deleted_text: s.deleted_text.clone(),
+            inserted_text: s.inserted_text.clone(),
+            anchored: false,
+        });
+    }
+    Ok(views)
+}
+
+#[tauri::command]
+pub fn accept_suggestion(
+    window: WebviewWindow,
+    path: String,
+    id: String,
+    workspace: Option<String>,
+) -> Result<SuggestionOutcome, String> {
+    resolve(&window, &path, workspace.as_deref(), Some(&id), true)
+}
+
+#[tauri::command]
+pub fn reject_suggestion(
+    window: WebviewWindow,
+    path: String,
+    id: String,
+    workspace: Option<String>,
+) -> Result<SuggestionOutcome, String> {
+    resolve(&window, &path, workspace.as_deref(), Some(&id), false)
+}
+
+#[tauri::command]
+pub fn accept_all_suggestions(
+    window: WebviewWindow,
+    path: String,
+    workspace: Option<String>,
+) -> Result<SuggestionOutcome, String> {
+    resolve(&window, &path, workspace.as_deref(), None, true)
+}
+
+#[tauri::command]
+pub fn reject_all_suggestions(
+    window: WebviewWindow,
+    path: String,
+    workspace: Option<String>,
+) -> Result<SuggestionOutcome, String> {
+    resolve(&window, &path, workspace.as_deref(), None, false)
+}
@@ -1,4 +1,7 @@
 use std::fs;
+use std::path::{Path, PathBuf};
+use super::app_dirs;
+use super::settings;
 
 const TEMPLATE_MEETING_NOTES: &str = r#"# Meeting Notes — {{date}}
 
@@ -147,14 +150,24 @@ const DEFAULT_TEMPLATES: &[(&str, &str)] = &[
     ("Weekly Review", TEMPLATE_WEEKLY_REVIEW),
 ];
 
-fn templates_dir() -> Result<std::path::PathBuf, String> {
-    let home = dirs::home_dir().ok_or_else(|| "Could not determine home directory".to_string())?;
-    Ok(home.join(".gutter").join("templates"))
+fn default_templates_dir() -> PathBuf {
+    app_dirs::data_dir().join("templates")
 }
 
+/// The `templatesFolder` setting for `workspace`, else the default
+/// templates folder in the app's data directory.
+fn templates_dir(workspace: Option<&str>) -> Result<PathBuf, String> {
+    match settings::templates_folder(workspace.map(Path::new)) {
+        Some(dir) => Ok(dir),
+        None => Ok(default_templates_dir()),
+    }
+}
+
+/// Seed the default templates folder with the built-in templates on first
+/// run. A configured templates folder is left as the user set it up.
 #[tauri::command]
 pub fn init_default_templates() -> Result<(), String> {
-    let dir = templates_dir()?;
+    let dir = default_templates_dir();
     if dir.exists() {
         return Ok(());
     }
@@ -168,8 +181,8 @@ pub fn init_default_templates() -> Result<(), String> {
 }
 
 #[tauri::command]
-pub fn list_templates() -> Result<Vec<String>, String> {
-    let dir = templates_dir()?;
+pub fn list_templates(workspace: Option<String>) -> Result<Vec<String>, String> {
+    let dir = templates_dir(workspace.as_deref())?;
     if !dir.exists() {
         return Ok(Vec::new());
     }
@@ -190,14 +203,14 @@ pub fn list_templates() -> Result<Vec<String>, String> {
 }
 
 #[tauri::command]
-pub fn read_template(name: String) -> Result<String, String> {
-    let file_path = templates_dir()?.join(format!("{}.md", name));
+pub fn read_template(name: String, workspace: Option<String>) -> Result<String, String> {
+    let file_path = templates_dir(workspace.as_deref())?.join(format!("{}.md", name));
     fs::read_to_string(&file_path).map_err(|e| format!("Failed to read template: {}", e))
 }
 
 #[tauri::command]
-pub fn save_template(name: String, content: String) -> Result<(), String> {
-    let dir = templates_dir()?;
+pub fn save_template(name: String, content: String, workspace: Option<String>) -> Result<(), String> {
+    let dir = templates_dir(workspace.as_deref())?;
     if !dir.exists() {
         fs::create_dir_all(&dir)
             .map_err(|e| format!("Failed to create templates dir: {}", e))?;
@@ -207,8 +220,8 @@ pub fn save_template(name: String, content: String) -> Result<(), String> {
 }
 
 #[tauri::command]
-pub fn delete_template(name: String) -> Result<(), String> {
-    let file_path = templates_dir()?.join(format!("{}.md", name));
+pub fn delete_template(name: String, workspace: Option<String>) -> Result<(), String> {
+    let file_path = templates_dir(workspace.as_deref())?.join(format!("{}.md", name));
     if file_path.exists() {
         fs::remove_file(&file_path).map_err(|e| format!("Failed to delete template: {}", e))
     } else {
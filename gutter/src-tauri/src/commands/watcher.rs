@@ -1,44 +1,89 @@
+use notify::event::ModifyKind;
 use notify::{Event, EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+use serde::Serialize;
+use std::collections::HashMap;
 use std::path::{Path, PathBuf};
 use std::sync::Mutex;
 use std::time::{Duration, Instant};
-use tauri::{AppHandle, Emitter, Manager};
+use tauri::{AppHandle, Emitter, Manager, WebviewWindow};
+use super::ignore_rules::{self, IgnoreRules};
+use super::file_meta::MetaCache;
+use super::workspace::{self, FileEntry, ListOptions, Listing};
+use super::workspace_file;
+
+/// A window's watcher and the folders it covers.
+struct WindowWatcher {
+    // Only held: dropping it stops the watch
+    _watcher: RecommendedWatcher,
+    roots: Vec<String>,
+}
 
 struct WatcherState {
-    watcher: Mutex<Option<RecommendedWatcher>>,
+    // Window label -> that window's watcher
+    watchers: Mutex<HashMap<String, WindowWatcher>>,
 }
 
 struct IgnoredPathState {
-    // Maps absolute path -> Instant when it should stop being ignored
-    paths: Mutex<std::collections::HashMap<PathBuf, Instant>>,
+    // Window label -> absolute path -> Instant when it should stop being ignored
+    paths: Mutex<HashMap<String, HashMap<PathBuf, Instant>>>,
 }
 
 pub fn init(app: &tauri::App) {
     app.manage(WatcherState {
-        watcher: Mutex::new(None),
+        watchers: Mutex::new(HashMap::new()),
     });
     app.manage(IgnoredPathState {
-        paths: Mutex::new(std::collections::HashMap::new()),
+        paths: Mutex::new(HashMap::new()),
     });
 }
 
-/// Temporarily suppress watcher events for a specific file path
-pub fn mark_write(app: &AppHandle, path: &str) {
-    let state = app.state::<IgnoredPathState>();
+/// Temporarily suppress watcher events for a specific file path. Only the
+/// writing window's watcher ignores it; other windows still hear about it.
+pub fn mark_write(window: &WebviewWindow, path: &str) {
+    let state = window.state::<IgnoredPathState>();
     let mut guard = state.paths.lock().unwrap();
     // Ignore this path for the next 2 seconds
-    guard.insert(PathBuf::from(path), Instant::now() + Duration::from_secs(2));
+    guard
+        .entry(window.label().to_string())
+        .or_default()
+        .insert(PathBuf::from(path), Instant::now() + Duration::from_secs(2));
 }
 
-fn is_suppressed(app: &AppHandle, path: &Path) -> bool {
+fn is_suppressed(app: &AppHandle, label: &str, path: &Path) -> bool {
     let state = app.state::<IgnoredPathState>();
     let mut guard = state.paths.lock().unwrap();
+    let Some(paths) = guard.get_mut(label) else {
+        return false;
+    };
 
     // Clean up expired entries while we're here
     let now = Instant::now();
-    guard.retain(|_, expiry| *expiry > now);
+    paths.retain(|_, expiry| *expiry > now);
+
+    paths.contains_key(path)
+}
+
+/// Folders each window is watching, by window label.
+pub fn watched_roots(app: &AppHandle) -> Vec<(String, Vec<String>)> {
+    let state = app.state::<WatcherState>();
+    let guard = state.watchers.lock().unwrap();
+    guard
+        .iter()
+        .map(|(label, w)| (label.clone(), w.roots.clone()))
+        .collect()
+}
+
+/// Roots `window` is watching; empty before it opens a workspace.
+pub fn window_roots(window: &WebviewWindow) -> Vec<String> {
+    let state = window.state::<WatcherState>();
+    let guard = state.watchers.lock().unwrap();
+    guard.get(window.label()).map(|w| w.roots.clone()).unwrap_or_default()
+}
 
-    guard.contains_key(path)
+/// Drop everything kept for a window that has closed.
+pub fn forget(app: &AppHandle, label: &str) {
+    app.state::<WatcherState>().watchers.lock().unwrap().remove(label);
+    app.state::<IgnoredPathState>().paths.lock().unwrap().remove(label);
 }
 
 fn is_ignored_path(path: &Path) -> bool {
@@ -55,41 +100,121 @@ fn is_ignored_path(path: &Path) -> bool {
     false
 }
 
+/// Structural change under the watched folder, so the tree can be patched
+/// instead of re-read. Entries in `upserted` replace any existing entry
+/// with the same path; new directories come with their subtree.
+#[derive(Serialize, Clone)]
+pub struct TreeDiff {
+    pub root: String,
+    pub upserted: Vec<FileEntry>,
+    pub removed: Vec<String>,
+    /// Sibling names in tree order for each folder an entry was upserted
+    /// into, so manual and sorted orders survive the patch
+    pub order: HashMap<String, Vec<String>>,
+}
+
+/// How the window's tree was read, so diffs match it.
+#[derive(Clone)]
+struct TreeShape {
+    options: ListOptions,
+    /// Multi-root trees start one level further down, under each root's entry
+    multi: bool,
+}
+
+fn tree_diff(app: &AppHandle, root: &str, paths: &[PathBuf], rules: &IgnoreRules, shape: &TreeShape) -> TreeDiff {
+    let cache = app.state::<MetaCache>();
+    let listing = Listing::new(Path::new(root), rules, &cache, shape.options.clone());
+    let mut diff = TreeDiff {
+        root: root.to_string(),
+        upserted: Vec::new(),
+        removed: Vec::new(),
+        order: HashMap::new(),
+    };
+    for path in paths {
+        // Anything below a hidden folder isn't in the tree at all
+        let hidden = path
+            .strip_prefix(root)
+            .map(|rel| rel.components().any(|c| workspace::is_hidden_name(&c.as_os_str().to_string_lossy())))
+            .unwrap_or(true);
+        let unfollowed = !listing.follow_symlinks && workspace::behind_symlink(Path::new(root), path);
+        if hidden || unfollowed || rules.is_ignored(path, path.is_dir()) {
+            continue;
+        }
+        // Renames report either or both ends, so go by what's on disk now
+        match path.symlink_metadata() {
+            Ok(_) => {
+                // Load as far down as the full read would have
+                let below = path.strip_prefix(root).map_or(0, |rel| rel.components().count()) as u32;
+                let levels = workspace::MAX_DEPTH.saturating_sub(below.saturating_sub(1) + shape.multi as u32);
+                diff.upserted.push(workspace::entry_for(path, path.is_dir(), levels, &listing));
+                if let Some(dir) = path.parent() {
+                    diff.order.entry(dir.to_string_lossy().to_string()).or_insert_with(|| {
+                        workspace::ordered_children(dir, &listing)
+                            .unwrap_or_default()
+                            .iter()
+                            .map(|(p, _)| p.file_name().unwrap_or_default().to_string_lossy().to_string())
+                            .collect()
+                    });
+                }
+            }
+            Err(_) => {
+                cache.forget(path);
+                diff.removed.push(path.to_string_lossy().to_string());
+            }
+        }
+    }
+    diff
+}
+
+/// Watch `path`, or every folder in `roots` for a multi-root workspace,
+/// for the calling window. Events name the root they happened under and
+/// go to that window only; tree diffs are listed with `options`, as the
+/// window's tree was.
 #[tauri::command]
-pub fn start_watcher(app: AppHandle, path: String) -> Result<(), String> {
+pub fn start_watcher(
+    window: WebviewWindow,
+    path: String,
+    roots: Option<Vec<String>>,
+    options: Option<ListOptions>,
+) -> Result<(), String> {
+    let app = window.app_handle();
+    let label = window.label().to_string();
     let state = app.state::<WatcherState>();
-    let mut guard = state.watcher.lock().map_err(|e| e.to_string())?;
+    let mut guard = state.watchers.lock().map_err(|e| e.to_string())?;
 
-    *guard = None;
+    guard.remove(&label);
 
     let app_handle = app.clone();
-    let watch_path = path.clone();
+    let event_label = label.clone();
+    let shape = TreeShape {
+        options: options.unwrap_or_default(),
+        multi: roots.as_ref().is_some_and(|r| !r.is_empty()),
+    };
+    let roots = roots.filter(|r| !r.is_empty()).unwrap_or_else(|| vec![path]);
+    let watch_roots = roots.clone();
+    let rules: Mutex<HashMap<String, IgnoreRules>> = Mutex::new(
+        roots
+            .iter()
+            .map(|r| (r.clone(), IgnoreRules::new(Path::new(r))))
+            .collect(),
+    );
 
     let mut watcher = RecommendedWatcher::new(
         move |res: Result<Event, notify::Error>| {
             if let Ok(event) = res {
-                let paths = &event.paths;
-
-                if paths.iter().all(|p| is_ignored_path(p) || is_suppressed(&app_handle, p)) {
-                    return;
-                }
-
-                match event.kind {
-                    EventKind::Create(_) | EventKind::Remove(_) => {
-                        let _ = app_handle.emit("tree-changed", &watch_path);
+                // Nested roots report a change once, under the innermost root
+                let mut by_root: Vec<(&str, Vec<PathBuf>)> = Vec::new();
+                for p in &event.paths {
+                    let Some(root) = workspace_file::root_for(&watch_roots, p) else {
+                        continue;
+                    };
+                    match by_root.iter_mut().find(|(r, _)| *r == root) {
+                        Some((_, paths)) => paths.push(p.clone()),
+                        None => by_root.push((root, vec![p.clone()])),
                     }
-                    EventKind::Modify(_) => {
-                        for p in paths {
-                            if !is_ignored_path(p) && !is_suppressed(&app_handle, p) {
-                                let _ = app_handle.emit(
-                                    "file-changed",
-                                    p.to_string_lossy().to_string(),
-                                );
-                            }
-                        }
-                        let _ = app_handle.emit("tree-changed", &watch_path);
-                    }
-                    _ => {}
+                }
+                for (root, paths) in by_root {
+                    handle_event(&app_handle, &event_label, &event, root, &paths, &rules, &shape);
                 }
             }
         },
@@ -97,18 +222,77 @@ pub fn start_watcher(app: AppHandle, path: String) -> Result<(), String> {
     )
     .map_err(|e| format!("Failed to create watcher: {}", e))?;
 
-    watcher
-        .watch(Path::new(&path), RecursiveMode::Recursive)
-        .map_err(|e| format!("Failed to start watching: {}", e))?;
+    for root in &roots {
+        watcher
+            .watch(Path::new(root), RecursiveMode::Recursive)
+            .map_err(|e| format!("Failed to start watching {}: {}", root, e))?;
+    }
 
-    *guard = Some(watcher);
+    guard.insert(label, WindowWatcher { _watcher: watcher, roots });
     Ok(())
 }
 
+/// Emit what one event means for the tree under `root` to the window
+/// `label`.
+fn handle_event(
+    app_handle: &AppHandle,
+    label: &str,
+    event: &Event,
+    root: &str,
+    paths: &[PathBuf],
+    rules: &Mutex<HashMap<String, IgnoreRules>>,
+    shape: &TreeShape,
+) {
+    let mut rules = rules.lock().unwrap();
+
+    // Edited ignore rules can show or hide anything
+    if paths.iter().any(|p| ignore_rules::is_ignore_file(p)) {
+        rules.insert(root.to_string(), IgnoreRules::new(Path::new(root)));
+        let _ = app_handle.emit_to(label, "tree-changed", root);
+        return;
+    }
+
+    let Some(rules) = rules.get(root) else {
+        return;
+    };
+    let ignored = |p: &Path| is_ignored_path(p) || rules.is_ignored(p, p.is_dir());
+    if paths.iter().all(|p| ignored(p) || is_suppressed(app_handle, label, p)) {
+        return;
+    }
+
+    // The backend dropped events; only a full re-read is safe
+    if event.need_rescan() {
+        let _ = app_handle.emit_to(label, "tree-changed", root);
+        return;
+    }
+
+    match event.kind {
+        EventKind::Create(_) | EventKind::Remove(_) | EventKind::Modify(ModifyKind::Name(_)) => {
+            let diff = tree_diff(app_handle, root, paths, rules, shape);
+            if !diff.upserted.is_empty() || !diff.removed.is_empty() {
+                let _ = app_handle.emit_to(label, "tree-diff", diff);
+            }
+        }
+        EventKind::Modify(_) => {
+            for p in paths {
+                if !ignored(p) && !is_suppressed(app_handle, label, p) {
+                    let _ = app_handle.emit_to(
+                        label,
+                        "file-changed",
+                        p.to_string_lossy().to_string(),
+                    );
+                }
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Stop the calling window's watcher.
 #[tauri::command]
-pub fn stop_watcher(app: AppHandle) -> Result<(), String> {
-    let state = app.state::<WatcherState>();
-    let mut guard = state.watcher.lock().map_err(|e| e.to_string())?;
-    *guard = None;
+pub fn stop_watcher(window: WebviewWindow) -> Result<(), String> {
+    let state = window.state::<WatcherState>();
+    let mut guard = state.watchers.lock().map_err(|e| e.to_string())?;
+    guard.remove(window.label());
     Ok(())
 }
@@ -0,0 +1,98 @@
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+use tauri::{AppHandle, Emitter, Manager, WebviewUrl, WebviewWindow, WebviewWindowBuilder};
+use super::{settings_watcher, watcher, workspace_file};
+
+/// Label of the window configured in `tauri.conf.json`.
+pub const MAIN_WINDOW: &str = "main";
+
+/// Windows opened later are labelled `window-<n>`.
+static NEXT_WINDOW: AtomicUsize = AtomicUsize::new(1);
+
+struct OpenFileState {
+    // Window label -> file that window should open once it has loaded
+    pending: Mutex<HashMap<String, String>>,
+}
+
+pub fn init(app: &tauri::App) {
+    app.manage(OpenFileState {
+        pending: Mutex::new(HashMap::new()),
+    });
+}
+
+/// Have window `label` open `path` when it asks, on load.
+pub fn queue_open(app: &AppHandle, label: &str, path: String) {
+    let state = app.state::<OpenFileState>();
+    state.pending.lock().unwrap().insert(label.to_string(), path);
+}
+
+pub fn take_pending(window: &WebviewWindow) -> Option<String> {
+    let state = window.state::<OpenFileState>();
+    let path = state.pending.lock().unwrap().remove(window.label());
+    path
+}
+
+/// The focused window, or failing that the main one, or any.
+pub fn active_window(app: &AppHandle) -> Option<WebviewWindow> {
+    let windows = app.webview_windows();
+    windows
+        .values()
+        .find(|w| w.is_focused().unwrap_or(false))
+        .or_else(|| windows.get(MAIN_WINDOW))
+        .or_else(|| windows.values().next())
+        .cloned()
+}
+
+/// The window whose workspace holds `path` (the innermost, if workspaces
+/// nest), or else the active one.
+fn window_for(app: &AppHandle, path: &str) -> Option<WebviewWindow> {
+    let owner = watcher::watched_roots(app)
+        .into_iter()
+        .filter_map(|(label, roots)| {
+            workspace_file::root_for(&roots, Path::new(path)).map(|root| (root.len(), label))
+        })
+        .max_by_key(|(len, _)| *len)
+        .and_then(|(_, label)| app.get_webview_window(&label));
+    owner.or_else(|| active_window(app))
+}
+
+/// Send a file opened from the OS to the window it belongs in, and bring
+/// that window forward. Returns the window's label.
+pub fn route_open(app: &AppHandle, path: &str) -> Option<String> {
+    let window = window_for(app, path)?;
+    let _ = app.emit_to(window.label(), "open-file", path);
+    let _ = window.unminimize();
+    let _ = window.set_focus();
+    Some(window.label().to_string())
+}
+
+/// Open another window, with no workspace. `path` is a file for it to open
+/// once it has loaded.
+pub fn open_window(app: &AppHandle, path: Option<String>) -> Result<(), String> {
+    let label = format!("window-{}", NEXT_WINDOW.fetch_add(1, Ordering::Relaxed));
+    if let Some(path) = path {
+        queue_open(app, &label, path);
+    }
+    WebviewWindowBuilder::new(app, &label, WebviewUrl::default())
+        .title("Gutter")
+        .inner_size(1200.0, 800.0)
+        .build()
+        .map_err(|e| format!("Failed to open window: {}", e))?;
+    Ok(())
+}
+
+/// Drop everything kept for a window that has closed.
+pub fn forget(app: &AppHandle, label: &str) {
+    app.state::<OpenFileState>().pending.lock().unwrap().remove(label);
+    watcher::forget(app, label);
+    settings_watcher::forget(app, label);
+}
+
+// Async: sync commands run on the main thread, and building a window there
+// deadlocks on Windows
+#[tauri::command]
+pub async fn new_window(app: AppHandle, path: Option<String>) -> Result<(), String> {
+    open_window(&app, path)
+}
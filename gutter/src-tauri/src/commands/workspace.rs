@@ -1,70 +1,346 @@
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
 use std::fs;
-use std::path::Path;
+use std::path::{Path, PathBuf};
+use std::time::UNIX_EPOCH;
+use tauri::State;
+use super::comment_storage::{self, CommentStore};
+use super::file_meta::{self, FileMeta, MetaCache, SortKey};
+use super::ignore_rules::IgnoreRules;
+use super::ordering::{self, FolderOrder};
+use super::settings;
+use super::workspace_file::WorkspaceRoot;
+
+/// Default depth limit for full-tree reads.
+pub const MAX_DEPTH: u32 = 10;
 
 #[derive(Serialize, Clone)]
 pub struct FileEntry {
     pub name: String,
     pub path: String,
     pub is_dir: bool,
+    /// `None` for files, and for directories whose children weren't loaded
     pub children: Option<Vec<FileEntry>>,
+    /// Number of visible entries in a directory, set even when its children
+    /// aren't loaded so the tree can show an expander
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub child_count: Option<usize>,
+    /// Only when the listing asked for metadata
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub meta: Option<FileMeta>,
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub is_symlink: bool,
+    /// Top-level folder of a multi-root workspace; `name` is the root's label
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub is_root: bool,
+    /// Why a directory wasn't expanded: unreadable, or a symlink loop
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
 }
 
-#[tauri::command]
-pub fn read_directory(path: String) -> Result<Vec<FileEntry>, String> {
-    read_dir_recursive(&path, 0)
+/// Optional listing behavior, shared by `read_directory` and
+/// `list_directory`.
+#[derive(Deserialize, Default, Clone)]
+#[serde(rename_all = "camelCase", default)]
+pub struct ListOptions {
+    /// Attach `FileMeta` to every entry
+    pub metadata: bool,
+    pub sort: SortKey,
+    pub descending: bool,
 }
 
-fn read_dir_recursive(path: &str, depth: u32) -> Result<Vec<FileEntry>, String> {
-    if depth > 10 {
-        return Ok(vec![]);
+/// Everything a tree read needs to filter, annotate and order entries.
+pub struct Listing<'a> {
+    pub root: PathBuf,
+    pub rules: &'a IgnoreRules,
+    pub cache: &'a MetaCache,
+    pub store: Box<dyn CommentStore>,
+    pub order: FolderOrder,
+    pub options: ListOptions,
+    /// Descend into symlinked folders (the `followSymlinks` setting)
+    pub follow_symlinks: bool,
+}
+
+impl<'a> Listing<'a> {
+    pub fn new(root: &Path, rules: &'a IgnoreRules, cache: &'a MetaCache, options: ListOptions) -> Self {
+        Listing {
+            root: root.to_path_buf(),
+            rules,
+            cache,
+            store: comment_storage::store_for(Some(&root.to_string_lossy())),
+            order: ordering::load_order(root),
+            options,
+            follow_symlinks: settings::follow_symlinks(Some(root)),
+        }
     }
 
-    let entries = fs::read_dir(path)
-        .map_err(|e| format!("Failed to read directory: {}", e))?;
+    fn meta(&self, path: &Path) -> Option<FileMeta> {
+        self.cache.get(path, self.store.as_ref())
+    }
+}
 
-    let mut result: Vec<FileEntry> = Vec::new();
+/// One page of a single directory level.
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DirectoryPage {
+    pub path: String,
+    pub entries: Vec<FileEntry>,
+    pub offset: usize,
+    /// Visible entries in the directory, across all pages
+    pub total: usize,
+    pub has_more: bool,
+}
+
+/// The tree under `path`. With `roots`, the tree of a multi-root workspace
+/// instead: one top-level entry per root, each read with its own ignore
+/// rules and ordering.
+#[tauri::command]
+pub fn read_directory(
+    cache: State<'_, MetaCache>,
+    path: String,
+    max_depth: Option<u32>,
+    options: Option<ListOptions>,
+    roots: Option<Vec<WorkspaceRoot>>,
+) -> Result<Vec<FileEntry>, String> {
+    let options = options.unwrap_or_default();
+    let depth = max_depth.unwrap_or(MAX_DEPTH).min(MAX_DEPTH);
+    let Some(roots) = roots.filter(|r| !r.is_empty()) else {
+        let root = Path::new(&path);
+        let rules = IgnoreRules::new(root);
+        let listing = Listing::new(root, &rules, &cache, options);
+        return read_level(root, depth, &listing);
+    };
+    Ok(roots
+        .iter()
+        .map(|root| {
+            let dir = Path::new(&root.path);
+            let rules = IgnoreRules::new(dir);
+            let listing = Listing::new(dir, &rules, &cache, options.clone());
+            let mut entry = entry_for(dir, true, depth, &listing);
+            entry.name = root.name.clone();
+            entry.is_root = true;
+            entry
+        })
+        .collect())
+}
 
+/// List one level of `path`, `limit` entries from `offset`, in tree order.
+/// Directories carry a child count; with `depth` > 0 their children are
+/// loaded that many levels down. Ignore rules are read from `workspace`
+/// (default: `path`) down.
+#[tauri::command]
+pub fn list_directory(
+    cache: State<'_, MetaCache>,
+    path: String,
+    offset: Option<usize>,
+    limit: Option<usize>,
+    depth: Option<u32>,
+    workspace: Option<String>,
+    options: Option<ListOptions>,
+) -> Result<DirectoryPage, String> {
+    let dir = Path::new(&path);
+    let root = Path::new(workspace.as_deref().unwrap_or(&path));
+    let rules = IgnoreRules::new(root);
+    let listing = Listing::new(root, &rules, &cache, options.unwrap_or_default());
+    let paths = ordered_children(dir, &listing)?;
+
+    let total = paths.len();
+    let offset = offset.unwrap_or(0).min(total);
+    let end = limit.map_or(total, |l| offset.saturating_add(l).min(total));
+    let depth = depth.unwrap_or(0).min(MAX_DEPTH);
+    let entries = paths[offset..end]
+        .iter()
+        .map(|(p, is_dir)| entry_for(p, *is_dir, depth, &listing))
+        .collect();
+
+    Ok(DirectoryPage {
+        path,
+        entries,
+        offset,
+        total,
+        has_more: end < total,
+    })
+}
+
+/// Entries hidden from the tree: dotfiles and comment sidecars.
+pub fn is_hidden_name(name: &str) -> bool {
+    name.starts_with('.') || name.ends_with(".comments.json") || name.ends_with(".comments.md")
+}
+
+/// Visible children of `dir` with whether each is a directory, unsorted.
+/// Skips hidden names and anything the ignore rules match.
+fn visible_children(dir: &Path, rules: &IgnoreRules) -> Result<Vec<(PathBuf, bool)>, String> {
+    let entries = fs::read_dir(dir)
+        .map_err(|e| format!("Failed to read directory: {}", e))?;
+    let mut result = Vec::new();
     for entry in entries {
         let entry = entry.map_err(|e| format!("Failed to read entry: {}", e))?;
-        let path_buf = entry.path();
-        let name = entry.file_name().to_string_lossy().to_string();
-
-        // Skip hidden files, comments files
-        if name.starts_with('.') {
+        if is_hidden_name(&entry.file_name().to_string_lossy()) {
             continue;
         }
-        if name.ends_with(".comments.json") || name.ends_with(".comments.md") {
+        let path = entry.path();
+        let is_dir = path.is_dir();
+        if rules.is_ignored_entry(&path, is_dir) {
             continue;
         }
+        result.push((path, is_dir));
+    }
+    Ok(result)
+}
 
-        let is_dir = path_buf.is_dir();
-        let path_str = path_buf.to_string_lossy().to_string();
+/// Identity of a directory, so a loop is caught whatever path reaches it.
+#[cfg(unix)]
+type DirId = (u64, u64);
+#[cfg(not(unix))]
+type DirId = PathBuf;
 
-        let children = if is_dir {
-            Some(read_dir_recursive(&path_str, depth + 1).unwrap_or_default())
-        } else {
-            None
-        };
+#[cfg(unix)]
+fn dir_id(path: &Path) -> Option<DirId> {
+    use std::os::unix::fs::MetadataExt;
+    fs::metadata(path).ok().map(|m| (m.dev(), m.ino()))
+}
 
-        result.push(FileEntry {
-            name,
-            path: path_str,
-            is_dir,
-            children,
-        });
-    }
+#[cfg(not(unix))]
+fn dir_id(path: &Path) -> Option<DirId> {
+    fs::canonicalize(path).ok()
+}
+
+fn is_symlink(path: &Path) -> bool {
+    fs::symlink_metadata(path)
+        .map(|m| m.file_type().is_symlink())
+        .unwrap_or(false)
+}
 
-    // Sort: dirs first, then alphabetically
-    result.sort_by(|a, b| {
-        match (a.is_dir, b.is_dir) {
-            (true, false) => std::cmp::Ordering::Less,
-            (false, true) => std::cmp::Ordering::Greater,
-            _ => a.name.to_lowercase().cmp(&b.name.to_lowercase()),
+/// Whether `dir` is one of the folders it's reached through under `root`,
+/// i.e. descending into it would loop.
+fn is_loop(root: &Path, dir: &Path) -> bool {
+    let Some(id) = dir_id(dir) else {
+        return false;
+    };
+    dir.ancestors()
+        .skip(1)
+        .take_while(|a| a.starts_with(root))
+        .any(|a| dir_id(a).as_ref() == Some(&id))
+}
+
+/// Whether `path` is reached through a symlinked folder below `root`.
+pub fn behind_symlink(root: &Path, path: &Path) -> bool {
+    path.ancestors()
+        .skip(1)
+        .take_while(|a| *a != root && a.starts_with(root))
+        .any(is_symlink)
+}
+
+/// Primary sort value of one entry.
+enum SortValue {
+    Text(String),
+    Time(Option<u64>),
+}
+
+fn file_time(path: &Path, created: bool) -> Option<u64> {
+    let stat = fs::metadata(path).ok()?;
+    let time = if created { stat.created() } else { stat.modified() };
+    Some(time.ok()?.duration_since(UNIX_EPOCH).ok()?.as_millis() as u64)
+}
+
+/// Dirs first, then by name.
+fn sort_by_name(paths: &mut [(PathBuf, bool)]) {
+    paths.sort_by_cached_key(|(p, is_dir)| {
+        (!is_dir, p.file_name().unwrap_or_default().to_string_lossy().to_lowercase())
+    });
+}
+
+/// Visible children of `dir` in the listing's order.
+pub fn ordered_children(dir: &Path, listing: &Listing) -> Result<Vec<(PathBuf, bool)>, String> {
+    let mut paths = visible_children(dir, listing.rules)?;
+    match listing.options.sort {
+        SortKey::Manual => {
+            sort_by_name(&mut paths);
+            listing.order.apply(&listing.root, dir, &mut paths);
         }
+        _ => sort_paths(&mut paths, listing),
+    }
+    Ok(paths)
+}
+
+/// Dirs first, then by the listing's sort key, ties broken by name.
+fn sort_paths(paths: &mut Vec<(PathBuf, bool)>, listing: &Listing) {
+    let name = |p: &Path| p.file_name().unwrap_or_default().to_string_lossy().to_lowercase();
+    let sort = listing.options.sort;
+    let mut keyed: Vec<(SortValue, String, (PathBuf, bool))> = paths
+        .drain(..)
+        .map(|(path, is_dir)| {
+            let value = match sort {
+                SortKey::Manual | SortKey::Name | SortKey::Natural => SortValue::Text(name(&path)),
+                SortKey::Modified => SortValue::Time(file_time(&path, false)),
+                SortKey::Created => SortValue::Time(file_time(&path, true)),
+                SortKey::Title => SortValue::Text(
+                    listing
+                        .meta(&path)
+                        .and_then(|m| m.title)
+                        .map(|t| t.to_lowercase())
+                        .unwrap_or_else(|| name(&path)),
+                ),
+            };
+            (value, name(&path), (path, is_dir))
+        })
+        .collect();
+    keyed.sort_by(|(va, na, (_, a_dir)), (vb, nb, (_, b_dir))| {
+        let primary = match (va, vb) {
+            (SortValue::Text(a), SortValue::Text(b)) if sort == SortKey::Natural => file_meta::natural_cmp(a, b),
+            (SortValue::Text(a), SortValue::Text(b)) => a.cmp(b),
+            (SortValue::Time(a), SortValue::Time(b)) => a.cmp(b),
+            _ => Ordering::Equal,
+        };
+        let primary = if listing.options.descending { primary.reverse() } else { primary };
+        b_dir.cmp(a_dir).then(primary).then_with(|| na.cmp(nb))
     });
+    paths.extend(keyed.into_iter().map(|(_, _, entry)| entry));
+}
 
-    Ok(result)
+/// Tree entry for `path`. Directories get their child count, plus their
+/// children `levels` deep. Symlinked folders are only expanded when the
+/// listing follows symlinks, and never into a loop.
+pub fn entry_for(path: &Path, is_dir: bool, levels: u32, listing: &Listing) -> FileEntry {
+    let symlink = is_symlink(path);
+    let mut error = None;
+    let (children, child_count) = if !is_dir || (symlink && !listing.follow_symlinks) {
+        (None, None)
+    } else if is_loop(&listing.root, path) {
+        error = Some("Symlink loop".to_string());
+        (None, None)
+    } else {
+        let listed = if levels > 0 {
+            read_level(path, levels - 1, listing).map(|c| (c.len(), Some(c)))
+        } else {
+            visible_children(path, listing.rules).map(|c| (c.len(), None))
+        };
+        match listed {
+            Ok((count, children)) => (children, Some(count)),
+            Err(e) => {
+                log::warn!("Skipping {}: {}", path.display(), e);
+                error = Some(e);
+                (None, None)
+            }
+        }
+    };
+    FileEntry {
+        name: path.file_name().unwrap_or_default().to_string_lossy().to_string(),
+        path: path.to_string_lossy().to_string(),
+        is_dir,
+        children,
+        child_count,
+        meta: if listing.options.metadata { listing.meta(path) } else { None },
+        is_symlink: symlink,
+        is_root: false,
+        error,
+    }
+}
+
+/// Sorted entries of `path`, with directories loaded `levels` deep.
+fn read_level(path: &Path, levels: u32, listing: &Listing) -> Result<Vec<FileEntry>, String> {
+    let paths = ordered_children(path, listing)?;
+    Ok(paths.iter().map(|(p, is_dir)| entry_for(p, *is_dir, levels, listing)).collect())
 }
 
 #[tauri::command]
@@ -73,3 +349,52 @@ pub fn get_parent_dir(path: String) -> Option<String> {
         .parent()
         .map(|p| p.to_string_lossy().to_string())
 }
+
+/// All markdown files under `root` in tree order (manual order where set),
+/// skipping the same hidden, comment and ignored files the tree does.
+pub fn collect_markdown_files(root: &Path) -> Vec<PathBuf> {
+    collect_markdown_files_in(root, root)
+}
+
+/// Markdown files under `dir`, a folder inside `workspace`, ordered and
+/// filtered by the workspace's settings the way its tree shows them.
+pub fn collect_markdown_files_in(workspace: &Path, dir: &Path) -> Vec<PathBuf> {
+    let rules = IgnoreRules::new(workspace);
+    let order = ordering::load_order(workspace);
+    let follow = settings::follow_symlinks(Some(workspace));
+    let mut files = Vec::new();
+    collect_markdown_recursive(workspace, dir, 0, &rules, &order, follow, &mut files);
+    files
+}
+
+fn collect_markdown_recursive(
+    root: &Path,
+    dir: &Path,
+    depth: u32,
+    rules: &IgnoreRules,
+    order: &FolderOrder,
+    follow: bool,
+    files: &mut Vec<PathBuf>,
+) {
+    if depth > MAX_DEPTH {
+        return;
+    }
+    let mut paths = match visible_children(dir, rules) {
+        Ok(paths) => paths,
+        Err(e) => {
+            log::warn!("Skipping {}: {}", dir.display(), e);
+            return;
+        }
+    };
+    sort_by_name(&mut paths);
+    order.apply(root, dir, &mut paths);
+    for (path, is_dir) in paths {
+        if is_dir {
+            if (follow || !is_symlink(&path)) && !is_loop(root, &path) {
+                collect_markdown_recursive(root, &path, depth + 1, rules, order, follow, files);
+            }
+        } else if file_meta::is_markdown(&path) {
+            files.push(path);
+        }
+    }
+}
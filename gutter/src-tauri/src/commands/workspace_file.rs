@@ -0,0 +1,122 @@
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use tauri::WebviewWindow;
+use super::watcher;
+
+/// One folder of a multi-root workspace.
+#[derive(Serialize, Deserialize, Clone)]
+pub struct WorkspaceRoot {
+    /// Absolute once loaded; relative paths in the file are resolved
+    /// against the file's folder
+    pub path: String,
+    /// Label for the root in the tree; defaults to the folder name
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub name: String,
+}
+
+/// On-disk shape of a workspace definition file, e.g.
+/// `team.gutter-workspace`.
+#[derive(Serialize, Deserialize, Default)]
+struct WorkspaceFile {
+    #[serde(default)]
+    roots: Vec<WorkspaceRoot>,
+    #[serde(flatten)]
+    extra: serde_json::Map<String, serde_json::Value>,
+}
+
+fn read_file(path: &Path) -> Result<WorkspaceFile, String> {
+    let text = fs::read_to_string(path).map_err(|e| format!("Failed to read workspace file: {}", e))?;
+    serde_json::from_str(&text).map_err(|e| format!("Failed to parse workspace file: {}", e))
+}
+
+fn folder_name(path: &Path) -> String {
+    path.file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_else(|| path.to_string_lossy().to_string())
+}
+
+fn resolve(base: &Path, stored: &str) -> PathBuf {
+    let dir = base.join(stored);
+    // Plain `C:\...` paths on Windows, not `\\?\C:\...`, so they match
+    // the paths the rest of the app builds
+    dunce::canonicalize(&dir).unwrap_or(dir)
+}
+
+/// Roots listed in the workspace file at `path`, with absolute paths and
+/// names filled in. Duplicate roots are dropped.
+pub fn load_roots(path: &Path) -> Result<Vec<WorkspaceRoot>, String> {
+    let base = path.parent().unwrap_or(Path::new(""));
+    let mut roots: Vec<WorkspaceRoot> = Vec::new();
+    for root in read_file(path)?.roots {
+        let dir = resolve(base, &root.path);
+        let dir_str = dir.to_string_lossy().to_string();
+        if roots.iter().any(|r| r.path == dir_str) {
+            continue;
+        }
+        let name = if root.name.is_empty() { folder_name(&dir) } else { root.name };
+        roots.push(WorkspaceRoot { path: dir_str, name });
+    }
+    Ok(roots)
+}
+
+/// The root containing `path`; the innermost one when roots nest.
+pub fn root_for<'a>(roots: &'a [String], path: &Path) -> Option<&'a str> {
+    roots
+        .iter()
+        .filter(|r| path.starts_with(r))
+        .max_by_key(|r| r.len())
+        .map(String::as_str)
+}
+
+/// The workspace `path` belongs to: of the roots `window` has open, the one
+/// holding it, so a note outside the first root uses its own root's
+/// comment storage and settings. Paths outside every root keep
+/// `workspace`; without one there is nothing to resolve.
+pub fn owning_root(window: &WebviewWindow, workspace: Option<String>, path: &str) -> Option<String> {
+    let workspace = workspace?;
+    let roots = watcher::window_roots(window);
+    Some(root_for(&roots, Path::new(path)).map_or(workspace, str::to_string))
+}
+
+#[tauri::command]
+pub fn open_workspace_file(path: String) -> Result<Vec<WorkspaceRoot>, String> {
+    let roots = load_roots(Path::new(&path))?;
+    if roots.is_empty() {
+        return Err("Workspace file lists no roots".to_string());
+    }
+    Ok(roots)
+}
+
+/// Write `roots` to the workspace file at `path`, keeping keys this
+/// version doesn't know. Roots already in the file keep the path as
+/// written there; new ones under the file's folder are stored relative to
+/// it so the file can move with them.
+#[tauri::command]
+pub fn save_workspace_file(path: String, roots: Vec<WorkspaceRoot>) -> Result<(), String> {
+    let file_path = Path::new(&path);
+    let base = file_path.parent().unwrap_or(Path::new(""));
+    let mut file = read_file(file_path).unwrap_or_default();
+    let previous: Vec<(PathBuf, String)> = file
+        .roots
+        .iter()
+        .map(|r| (resolve(base, &r.path), r.path.clone()))
+        .collect();
+    file.roots = roots
+        .into_iter()
+        .map(|root| {
+            let dir = PathBuf::from(&root.path);
+            let stored = match (previous.iter().find(|(p, _)| *p == dir), dir.strip_prefix(base)) {
+                (Some((_, written)), _) => written.clone(),
+                (None, Ok(rel)) if rel.as_os_str().is_empty() => ".".to_string(),
+                (None, Ok(rel)) => rel.to_string_lossy().to_string(),
+                (None, Err(_)) => root.path.clone(),
+            };
+            // Don't pin a name that would be derived anyway
+            let name = if root.name == folder_name(&dir) { String::new() } else { root.name };
+            WorkspaceRoot { path: stored, name }
+        })
+        .collect();
+    let json = serde_json::to_string_pretty(&file).map_err(|e| format!("Failed to serialize: {}", e))?;
+    fs::write(file_path, json).map_err(|e| format!("Failed to write workspace file: {}", e))
+}
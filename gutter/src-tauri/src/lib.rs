@@ -1,12 +1,7 @@
 mod commands;
 mod menu;
 
-use std::sync::Mutex;
-use tauri::{Emitter, Manager, RunEvent, WindowEvent};
-
-pub struct OpenFileState {
-    pub path: Mutex<Option<String>>,
-}
+use tauri::{Manager, RunEvent, WindowEvent};
 
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
@@ -14,15 +9,13 @@ pub fn run() {
         .plugin(tauri_plugin_dialog::init())
         .plugin(tauri_plugin_fs::init())
         .plugin(tauri_plugin_single_instance::init(|app, args, _cwd| {
-            // This handles opening a file when an instance is already running (Windows/Linux)
+            // This handles opening a file when an instance is already running (Windows/Linux).
+            // It goes to the window whose workspace holds it.
             let path = args.iter().find(|arg| arg.ends_with(".md") || arg.ends_with(".markdown"));
             if let Some(p) = path {
-                let _ = app.emit("open-file", p);
+                commands::windows::route_open(app, p);
             }
         }))
-        .manage(OpenFileState {
-            path: Mutex::new(None),
-        })
         .setup(|app| {
             if cfg!(debug_assertions) {
                 app.handle().plugin(
@@ -32,16 +25,28 @@ pub fn run() {
                 )?;
             }
 
+            commands::windows::init(app);
+
             // Handle CLI args on Windows/Linux at startup
             let args: Vec<String> = std::env::args().collect();
             let path = args.iter().find(|arg| arg.ends_with(".md") || arg.ends_with(".markdown"));
             if let Some(p) = path {
-                let state = app.state::<OpenFileState>();
-                *state.path.lock().unwrap() = Some(p.clone());
+                commands::windows::queue_open(app.handle(), commands::windows::MAIN_WINDOW, p.clone());
             }
 
+            // Before anything reads settings, so they're found in their new home
+            if let Err(e) = commands::app_dirs::migrate_legacy_dir() {
+                log::warn!("{}", e);
+            }
+
+            // The menu lists recent entries, so they're loaded first
+            commands::recent::init(app);
             menu::setup_menu(app)?;
             commands::watcher::init(app);
+            commands::file_meta::init(app);
+            commands::mentions::init(app);
+            commands::settings_watcher::init(app);
+            commands::session::init(app);
             Ok(())
         })
         .invoke_handler(tauri::generate_handler![
@@ -63,12 +68,57 @@ pub fn run() {
             commands::comments::delete_comments,
             commands::comments::write_companion,
             commands::comments::delete_companion,
+            commands::comments::update_thread_workflow,
+            commands::comments::query_comments,
+            commands::comments::get_comment_storage,
+            commands::comments::migrate_comment_storage,
+            commands::comment_export::export_comments_report,
+            commands::critic_markup::export_critic_markup,
+            commands::critic_markup::import_critic_markup,
+            commands::suggestions::list_suggestions,
+            commands::suggestions::accept_suggestion,
+            commands::suggestions::reject_suggestion,
+            commands::suggestions::accept_all_suggestions,
+            commands::suggestions::reject_all_suggestions,
+            commands::anchoring::find_anchor_candidates,
+            commands::anchoring::reattach_comment,
+            commands::review_package::export_review_package,
+            commands::review_package::import_review_package,
+            commands::docx_export::export_docx,
+            commands::docx_import::import_docx,
+            commands::mentions::mention_index,
+            commands::mentions::mentions_for,
+            commands::mentions::mentions_for_me,
             commands::workspace::read_directory,
+            commands::workspace::list_directory,
+            commands::ordering::get_folder_order,
+            commands::ordering::set_folder_order,
+            commands::ordering::move_entry,
             commands::workspace::get_parent_dir,
+            commands::workspace_file::open_workspace_file,
+            commands::workspace_file::save_workspace_file,
             commands::settings::read_settings,
             commands::settings::write_settings,
+            commands::settings::read_workspace_settings,
+            commands::settings::write_workspace_settings,
+            commands::settings::read_merged_settings,
+            commands::settings::settings_sources,
+            commands::settings_watcher::watch_settings,
+            commands::keybindings::set_keybindings,
+            menu::set_menu_state,
+            commands::recent::list_recent,
+            commands::recent::add_recent,
+            commands::recent::pin_recent,
+            commands::recent::remove_recent,
+            commands::recent::clear_recent,
+            commands::session::update_session,
+            commands::session::load_session,
+            commands::recovery::journal_buffer,
+            commands::recovery::discard_buffer,
+            commands::recovery::list_recoverable,
             commands::watcher::start_watcher,
             commands::watcher::stop_watcher,
+            commands::windows::new_window,
             commands::export::export_html,
             commands::search::search_workspace,
             commands::templates::init_default_templates,
@@ -93,15 +143,28 @@ pub fn run() {
                     if let Some(url) = urls.first() {
                         if let Ok(path) = url.to_file_path() {
                             let path_str = path.to_string_lossy().to_string();
-                            let state = app_handle.state::<OpenFileState>();
-                            *state.path.lock().unwrap() = Some(path_str.clone());
-                            let _ = app_handle.emit("open-file", path_str);
+                            // Also queued, in case the window hasn't loaded yet
+                            if let Some(label) = commands::windows::route_open(app_handle, &path_str) {
+                                commands::windows::queue_open(app_handle, &label, path_str);
+                            }
                         }
                     }
                 }
-                RunEvent::WindowEvent { event: WindowEvent::Destroyed, .. } => {
-                    // Stop the watcher so its background thread shuts down cleanly
-                    let _ = commands::watcher::stop_watcher(app_handle.clone());
+                RunEvent::WindowEvent { label, event: WindowEvent::Destroyed, .. } => {
+                    // Stop the window's watchers so their background threads shut down cleanly
+                    commands::windows::forget(app_handle, &label);
+                    if app_handle.webview_windows().keys().any(|l| *l != label) {
+                        return;
+                    }
+                    // Last window gone. Save the session before the forced exit below skips it
+                    if let Err(e) = commands::session::flush(app_handle) {
+                        log::warn!("{}", e);
+                    }
+                    // Every window's close guard has had its dirty buffers
+                    // saved or discarded, so there's nothing to recover
+                    if let Err(e) = commands::recovery::clear() {
+                        log::warn!("{}", e);
+                    }
                     // Force-exit to avoid macOS 26 WebKit crash: after the window
                     // is deallocated, pending run-loop callbacks
                     // (WebPageProxy::dispatchSetObscuredContentInsets) access freed
@@ -1,50 +1,195 @@
+use serde::Deserialize;
+use std::collections::HashMap;
 use tauri::{
-    menu::{MenuBuilder, MenuItem, SubmenuBuilder},
-    App, Emitter,
+    menu::{CheckMenuItem, MenuBuilder, MenuItem, MenuItemKind, PredefinedMenuItem, Submenu, SubmenuBuilder},
+    App, AppHandle, Emitter, Manager,
 };
 
+use crate::commands::keybindings::{self, Keybindings};
+use crate::commands::recent::{self, RecentEntry, RecentKind};
+use crate::commands::{settings, windows};
+
+/// Menu ids of Open Recent entries are this prefix and the entry's path.
+const RECENT_PREFIX: &str = "recent:";
+
+/// The File > Open Recent submenu, refilled whenever the list changes.
+struct OpenRecentMenu(Submenu<tauri::Wry>);
+
+/// Home-relative so entries stay short.
+fn recent_label(path: &str) -> String {
+    let home = dirs::home_dir();
+    match home.as_deref().and_then(|h| std::path::Path::new(path).strip_prefix(h).ok()) {
+        Some(rel) => format!("~/{}", rel.to_string_lossy()),
+        None => path.to_string(),
+    }
+}
+
+/// Refill File > Open Recent from `entries`: workspaces, then files, then
+/// Clear Recently Opened.
+pub fn rebuild_open_recent(app: &AppHandle, entries: &[RecentEntry]) -> tauri::Result<()> {
+    let Some(menu) = app.try_state::<OpenRecentMenu>() else {
+        return Ok(());
+    };
+    let submenu = &menu.0;
+    for item in submenu.items()? {
+        submenu.remove(&item)?;
+    }
+
+    let workspaces = entries.iter().filter(|e| e.kind == RecentKind::Workspace);
+    let files = entries.iter().filter(|e| e.kind == RecentKind::File);
+    let mut any = false;
+    for group in [workspaces.collect::<Vec<_>>(), files.collect::<Vec<_>>()] {
+        if group.is_empty() {
+            continue;
+        }
+        if any {
+            submenu.append(&PredefinedMenuItem::separator(app)?)?;
+        }
+        for entry in group {
+            let label = recent_label(&entry.path);
+            let label = if entry.pinned { format!("{} (pinned)", label) } else { label };
+            let id = format!("{}{}", RECENT_PREFIX, entry.path);
+            submenu.append(&MenuItem::with_id(app, id, label, true, None::<&str>)?)?;
+        }
+        any = true;
+    }
+
+    if any {
+        submenu.append(&PredefinedMenuItem::separator(app)?)?;
+    } else {
+        submenu.append(&MenuItem::new(app, "No Recent Items", false, None::<&str>)?)?;
+    }
+    submenu.append(&MenuItem::with_id(
+        app,
+        "clear_recent",
+        "Clear Recently Opened",
+        any,
+        None::<&str>,
+    )?)?;
+    Ok(())
+}
+
+/// Items whose shortcut, enabled or checked state changes at runtime, by id.
+struct MenuItems(HashMap<String, MenuItemKind<tauri::Wry>>);
+
+/// A plain menu item with its configured shortcut, recorded in `items`.
+fn item(
+    app: &App,
+    items: &mut HashMap<String, MenuItemKind<tauri::Wry>>,
+    keys: &Keybindings,
+    id: &str,
+    label: &str,
+) -> tauri::Result<MenuItem<tauri::Wry>> {
+    let item = MenuItem::with_id(app, id, label, true, shortcut(keys, id))?;
+    items.insert(id.to_string(), MenuItemKind::MenuItem(item.clone()));
+    Ok(item)
+}
+
+/// A checkable item for a panel or mode that can be on or off.
+fn check_item(
+    app: &App,
+    items: &mut HashMap<String, MenuItemKind<tauri::Wry>>,
+    keys: &Keybindings,
+    id: &str,
+    label: &str,
+) -> tauri::Result<CheckMenuItem<tauri::Wry>> {
+    let item = CheckMenuItem::with_id(app, id, label, true, false, shortcut(keys, id))?;
+    items.insert(id.to_string(), MenuItemKind::Check(item.clone()));
+    Ok(item)
+}
+
+fn shortcut(keys: &Keybindings, id: &str) -> Option<String> {
+    keys.commands
+        .iter()
+        .find(|c| c.id == id)
+        .and_then(|c| c.shortcut.clone())
+}
+
+/// Give each menu item in `shortcuts` its new shortcut, or none.
+pub fn set_accelerators(app: &AppHandle, shortcuts: &HashMap<&str, Option<&str>>) -> tauri::Result<()> {
+    let Some(items) = app.try_state::<MenuItems>() else {
+        return Ok(());
+    };
+    for (id, item) in &items.0 {
+        let Some(shortcut) = shortcuts.get(id.as_str()) else {
+            continue;
+        };
+        match item {
+            MenuItemKind::MenuItem(i) => i.set_accelerator(*shortcut)?,
+            MenuItemKind::Check(i) => i.set_accelerator(*shortcut)?,
+            _ => {}
+        }
+    }
+    Ok(())
+}
+
+/// How one menu item should look; fields left out stay as they are.
+#[derive(Deserialize)]
+pub struct MenuItemState {
+    pub id: String,
+    pub enabled: Option<bool>,
+    pub checked: Option<bool>,
+}
+
+/// Enable, disable, check or uncheck menu items to match the editor.
+#[tauri::command]
+pub fn set_menu_state(app: AppHandle, items: Vec<MenuItemState>) -> Result<(), String> {
+    let Some(menu) = app.try_state::<MenuItems>() else {
+        return Ok(());
+    };
+    for state in items {
+        let Some(item) = menu.0.get(&state.id) else {
+            return Err(format!("Unknown menu item: {}", state.id));
+        };
+        let result = match item {
+            MenuItemKind::MenuItem(i) => state.enabled.map_or(Ok(()), |e| i.set_enabled(e)),
+            MenuItemKind::Check(i) => state
+                .enabled
+                .map_or(Ok(()), |e| i.set_enabled(e))
+                .and_then(|_| state.checked.map_or(Ok(()), |c| i.set_checked(c))),
+            _ => Ok(()),
+        };
+        result.map_err(|e| format!("Failed to update menu item {}: {}", state.id, e))?;
+    }
+    Ok(())
+}
+
 pub fn setup_menu(app: &App) -> Result<(), Box<dyn std::error::Error>> {
+    // Shortcuts start out as configured; the editor re-applies them with
+    // its workspace's settings once it loads
+    let keys = keybindings::resolve(&settings::load(None).keybindings);
+    let items = &mut HashMap::new();
+
     // --- Preferences ---
-    let preferences = MenuItem::with_id(
-        app,
-        "preferences",
-        "Preferences",
-        true,
-        Some("CmdOrCtrl+,"),
-    )?;
+    let preferences = item(app, items, &keys, "preferences", "Preferences")?;
 
     // --- File menu ---
-    let new_file = MenuItem::with_id(app, "new_file", "New File", true, Some("CmdOrCtrl+N"))?;
-    let open = MenuItem::with_id(app, "open", "Open File", true, Some("CmdOrCtrl+O"))?;
-    let open_folder = MenuItem::with_id(
+    let new_file = item(app, items, &keys, "new_file", "New File")?;
+    let new_window = item(app, items, &keys, "new_window", "New Window")?;
+    let open = item(app, items, &keys, "open", "Open File")?;
+    let open_folder = item(app, items, &keys, "open_folder", "Open Folder")?;
+    let open_workspace = item(app, items, &keys, "open_workspace", "Open Workspace...")?;
+    let add_folder_to_workspace = item(
         app,
-        "open_folder",
-        "Open Folder",
-        true,
-        None::<&str>,
+        items,
+        &keys,
+        "add_folder_to_workspace",
+        "Add Folder to Workspace...",
     )?;
-    let save = MenuItem::with_id(app, "save", "Save", true, Some("CmdOrCtrl+S"))?;
-    let new_from_template = MenuItem::with_id(
-        app,
-        "new_from_template",
-        "New from Template...",
-        true,
-        None::<&str>,
-    )?;
-    let save_as_template = MenuItem::with_id(
-        app,
-        "save_as_template",
-        "Save as Template...",
-        true,
-        None::<&str>,
-    )?;
-    let export =
-        MenuItem::with_id(app, "export", "Export", true, Some("CmdOrCtrl+Shift+E"))?;
+    let open_recent = SubmenuBuilder::with_id(app, "open_recent", "Open Recent").build()?;
+    let save = item(app, items, &keys, "save", "Save")?;
+    let new_from_template = item(app, items, &keys, "new_from_template", "New from Template...")?;
+    let save_as_template = item(app, items, &keys, "save_as_template", "Save as Template...")?;
+    let export = item(app, items, &keys, "export", "Export")?;
 
     let file_menu = SubmenuBuilder::new(app, "File")
         .item(&new_file)
+        .item(&new_window)
         .item(&open)
         .item(&open_folder)
+        .item(&open_workspace)
+        .item(&open_recent)
+        .item(&add_folder_to_workspace)
         .item(&save)
         .separator()
         .item(&new_from_template)
@@ -67,57 +212,14 @@ pub fn setup_menu(app: &App) -> Result<(), Box<dyn std::error::Error>> {
         .build()?;
 
     // --- View menu ---
-    let toggle_tree = MenuItem::with_id(
-        app,
-        "toggle_tree",
-        "Toggle File Tree",
-        true,
-        Some("CmdOrCtrl+\\"),
-    )?;
-    let toggle_comments = MenuItem::with_id(
-        app,
-        "toggle_comments",
-        "Toggle Comments",
-        true,
-        Some("CmdOrCtrl+Shift+C"),
-    )?;
-    let toggle_outline =
-        MenuItem::with_id(app, "toggle_outline", "Toggle Outline", true, None::<&str>)?;
-    let toggle_source = MenuItem::with_id(
-        app,
-        "toggle_source",
-        "Toggle Source Mode",
-        true,
-        Some("CmdOrCtrl+/"),
-    )?;
-    let toggle_reading = MenuItem::with_id(
-        app,
-        "toggle_reading",
-        "Reading Mode",
-        true,
-        Some("CmdOrCtrl+Shift+R"),
-    )?;
-    let toggle_history = MenuItem::with_id(
-        app,
-        "toggle_history",
-        "Version History",
-        true,
-        Some("CmdOrCtrl+Shift+H"),
-    )?;
-    let toggle_tags = MenuItem::with_id(
-        app,
-        "toggle_tags",
-        "Tag Browser",
-        true,
-        Some("CmdOrCtrl+Shift+T"),
-    )?;
-    let cycle_theme = MenuItem::with_id(
-        app,
-        "cycle_theme",
-        "Cycle Theme",
-        true,
-        Some("CmdOrCtrl+Shift+D"),
-    )?;
+    let toggle_tree = check_item(app, items, &keys, "toggle_tree", "Toggle File Tree")?;
+    let toggle_comments = check_item(app, items, &keys, "toggle_comments", "Toggle Comments")?;
+    let toggle_outline = check_item(app, items, &keys, "toggle_outline", "Toggle Outline")?;
+    let toggle_source = check_item(app, items, &keys, "toggle_source", "Toggle Source Mode")?;
+    let toggle_reading = check_item(app, items, &keys, "toggle_reading", "Reading Mode")?;
+    let toggle_history = check_item(app, items, &keys, "toggle_history", "Version History")?;
+    let toggle_tags = check_item(app, items, &keys, "toggle_tags", "Tag Browser")?;
+    let cycle_theme = item(app, items, &keys, "cycle_theme", "Cycle Theme")?;
 
     let view_menu = SubmenuBuilder::new(app, "View")
         .item(&toggle_tree)
@@ -133,22 +235,10 @@ pub fn setup_menu(app: &App) -> Result<(), Box<dyn std::error::Error>> {
         .build()?;
 
     // --- Find menu ---
-    let search = MenuItem::with_id(app, "search", "Search", true, Some("CmdOrCtrl+K"))?;
-    let quick_open = MenuItem::with_id(
-        app,
-        "quick_open",
-        "Quick Open File",
-        true,
-        Some("CmdOrCtrl+P"),
-    )?;
-    let find = MenuItem::with_id(app, "find", "Find", true, Some("CmdOrCtrl+F"))?;
-    let replace = MenuItem::with_id(
-        app,
-        "replace",
-        "Find && Replace",
-        true,
-        Some("CmdOrCtrl+H"),
-    )?;
+    let search = item(app, items, &keys, "search", "Search")?;
+    let quick_open = item(app, items, &keys, "quick_open", "Quick Open File")?;
+    let find = item(app, items, &keys, "find", "Find")?;
+    let replace = item(app, items, &keys, "replace", "Find && Replace")?;
 
     let find_menu = SubmenuBuilder::new(app, "Find")
         .item(&search)
@@ -159,27 +249,9 @@ pub fn setup_menu(app: &App) -> Result<(), Box<dyn std::error::Error>> {
         .build()?;
 
     // --- Comments menu ---
-    let new_comment = MenuItem::with_id(
-        app,
-        "new_comment",
-        "New Comment",
-        true,
-        Some("CmdOrCtrl+Shift+M"),
-    )?;
-    let next_comment = MenuItem::with_id(
-        app,
-        "next_comment",
-        "Next Comment",
-        true,
-        Some("CmdOrCtrl+Shift+N"),
-    )?;
-    let prev_comment = MenuItem::with_id(
-        app,
-        "prev_comment",
-        "Previous Comment",
-        true,
-        Some("CmdOrCtrl+Shift+P"),
-    )?;
+    let new_comment = item(app, items, &keys, "new_comment", "New Comment")?;
+    let next_comment = item(app, items, &keys, "next_comment", "Next Comment")?;
+    let prev_comment = item(app, items, &keys, "prev_comment", "Previous Comment")?;
 
     let comments_menu = SubmenuBuilder::new(app, "Comments")
         .item(&new_comment)
@@ -227,14 +299,44 @@ pub fn setup_menu(app: &App) -> Result<(), Box<dyn std::error::Error>> {
 
     app.set_menu(menu)?;
 
+    app.manage(OpenRecentMenu(open_recent));
+    app.manage(MenuItems(std::mem::take(items)));
+    rebuild_open_recent(app.handle(), &recent::current(app.handle()))?;
+
     // Handle custom menu item clicks by emitting events to the frontend.
     // Predefined items (copy, paste, undo, etc.) are handled natively by the OS.
+    // The menu is shared, so events go to the focused window.
     app.on_menu_event(move |app_handle, event| {
+        let Some(target) = windows::active_window(app_handle) else {
+            return;
+        };
+        if let Some(path) = event.id().as_ref().strip_prefix(RECENT_PREFIX) {
+            let entry = recent::current(app_handle).into_iter().find(|e| e.path == path);
+            if let Some(entry) = entry {
+                let _ = app_handle.emit_to(target.label(), "menu:open-recent", entry);
+            }
+            return;
+        }
         let event_name = match event.id().as_ref() {
+            "clear_recent" => {
+                let _ = recent::clear_recent(app_handle.clone(), None);
+                return;
+            }
+            "new_window" => {
+                let app_handle = app_handle.clone();
+                tauri::async_runtime::spawn(async move {
+                    if let Err(e) = windows::open_window(&app_handle, None) {
+                        log::warn!("{}", e);
+                    }
+                });
+                return;
+            }
             "preferences" => "menu:preferences",
             "new_file" => "menu:new-file",
             "open" => "menu:open",
             "open_folder" => "menu:open-folder",
+            "open_workspace" => "menu:open-workspace",
+            "add_folder_to_workspace" => "menu:add-folder-to-workspace",
             "save" => "menu:save",
             "new_from_template" => "menu:new-from-template",
             "save_as_template" => "menu:save-as-template",
@@ -256,7 +358,7 @@ pub fn setup_menu(app: &App) -> Result<(), Box<dyn std::error::Error>> {
             "prev_comment" => "menu:prev-comment",
             _ => return,
         };
-        let _ = app_handle.emit(event_name, ());
+        let _ = app_handle.emit_to(target.label(), event_name, ());
     });
 
     Ok(())
@@ -19,18 +19,22 @@ import { DocumentOutline } from "./components/DocumentOutline";
 import { WelcomeScreen } from "./components/WelcomeScreen";
 import { BacklinksPanel } from "./components/BacklinksPanel";
 import { ExportDialog } from "./components/ExportDialog";
+import { RecoveryDialog } from "./components/RecoveryDialog";
 import { TemplatePicker } from "./components/TemplatePicker";
 import { PreferencesDialog } from "./components/PreferencesDialog";
 import { useEditorStore } from "./stores/editorStore";
 import { useWorkspaceStore } from "./stores/workspaceStore";
-import { useSettingsStore } from "./stores/settingsStore";
+import { useSettingsStore, PANEL_WIDTH_MAX } from "./stores/settingsStore";
 import { modLabel } from "./utils/platform";
 import { useThemeApplication } from "./hooks/useThemeApplication";
 import { useFileWatcher } from "./hooks/useFileWatcher";
 import { useSaveHandler } from "./hooks/useSaveHandler";
 import { useTabLifecycle } from "./hooks/useTabLifecycle";
 import { useWindowLifecycle } from "./hooks/useWindowLifecycle";
+import { useSessionPersistence } from "./hooks/useSessionPersistence";
+import { useRecoveryJournal } from "./hooks/useRecoveryJournal";
 import { useKeyboardShortcuts } from "./hooks/useKeyboardShortcuts";
+import { useMenuState } from "./hooks/useMenuState";
 import { useMenuBarListeners } from "./hooks/useMenuBarListeners";
 import { useCommands } from "./hooks/useCommands";
 
@@ -87,7 +91,7 @@ function App() {
 
   // Tab lifecycle
   const {
-    handleOpenFile, handleFileTreeOpen, handleNewFile,
+    handleOpenFile, handleFileTreeOpen, handleOpenRecent, handleNewFile,
     handleSwitchTab, handleCloseTab,
     handleEditorUpdate, handleSourceChange,
     switchToSource, switchToWysiwyg,
@@ -96,6 +100,9 @@ function App() {
     templatePicker, setTemplatePicker,
   } = useTabLifecycle(markdownRef, tabContentCache, handleSave, setShowReloadPrompt, lastSaveTimeRef);
 
+  // Unsaved buffers journaled for crash recovery
+  const { recoverable, recoverBuffer, discardRecovery, dismissRecovery } = useRecoveryJournal(markdownRef, tabContentCache, handleSwitchTab);
+
   // Commands for command palette
   const commands = useCommands({
     handleNewFile, handleOpenFile, handleSave,
@@ -114,7 +121,7 @@ function App() {
 
   // Native menu bar event listeners
   useMenuBarListeners({
-    handleNewFile, handleOpenFile, handleSave,
+    handleNewFile, handleOpenFile, handleSave, handleOpenRecent,
     switchToSource, switchToWysiwyg,
     toggleFileTree, toggleComments, toggleHistory, toggleTags, toggleOutline,
     toggleReadingMode, cycleTheme, navigateComment,
@@ -129,20 +136,26 @@ function App() {
     {
       handleNewFile, handleOpenFile, handleSave,
       switchToSource, switchToWysiwyg,
-      toggleFileTree, toggleComments, toggleHistory, toggleTags,
+      toggleFileTree, toggleComments, toggleHistory, toggleTags, toggleOutline,
       toggleReadingMode, cycleTheme, navigateComment,
       setUnifiedSearchMode, setFindReplaceMode,
       setShowExport, setShowPreferences,
       createComment: () => editorInstanceRef.current?.createComment(),
     },
-    !!(unifiedSearchMode || showExport || showPreferences || templatePicker),
+    !!(unifiedSearchMode || showExport || showPreferences || templatePicker || recoverable.length > 0),
   );
 
+  // Keybindings and enabled/checked menu items
+  useMenuState();
+
   // Theme, CSS variables, and document title
   useThemeApplication();
 
+  // Open tabs, cursor and scroll positions and panels, per workspace
+  const saveSession = useSessionPersistence(editorInstanceRef, handleFileTreeOpen);
+
   // Window lifecycle: close guard, drag-drop, settings, tags, version preview clear
-  useWindowLifecycle(editorInstanceRef, handleFileTreeOpen, setVersionPreview);
+  useWindowLifecycle(editorInstanceRef, handleFileTreeOpen, setVersionPreview, saveSession);
 
   return (
     <div className="h-screen w-screen flex flex-col bg-[var(--editor-bg)] text-[var(--editor-text)] transition-colors">
@@ -160,7 +173,7 @@ function App() {
               side="left"
               currentWidth={panelWidths.fileTree}
               minWidth={160}
-              maxWidth={Math.floor(window.innerWidth * 0.5)}
+              maxWidth={Math.min(PANEL_WIDTH_MAX, Math.floor(window.innerWidth * 0.5))}
               onResize={(w) => setPanelWidth("fileTree", w)}
               onDoubleClick={() => setPanelWidth("fileTree", 224)}
             />
@@ -272,7 +285,7 @@ function App() {
               <WelcomeScreen
                 onNewFile={handleNewFile}
                 onOpenFile={handleOpenFile}
-                onOpenRecent={handleFileTreeOpen}
+                onOpenRecent={handleOpenRecent}
               />
             ) : isReadingMode ? (
               <ReadingMode content={markdownRef.current} />
@@ -300,7 +313,7 @@ function App() {
               side="right"
               currentWidth={panelWidths.comments}
               minWidth={220}
-              maxWidth={Math.floor(window.innerWidth * 0.5)}
+              maxWidth={Math.min(PANEL_WIDTH_MAX, Math.floor(window.innerWidth * 0.5))}
               onResize={(w) => setPanelWidth("comments", w)}
               onDoubleClick={() => setPanelWidth("comments", 288)}
             />
@@ -308,7 +321,7 @@ function App() {
               className="border-l border-[var(--editor-border)] shrink-0 overflow-auto sidebar-panel"
               style={{ width: panelWidths.comments }}
             >
-              <CommentsPanel />
+              <CommentsPanel onReload={reloadFromDisk} />
               <div className="border-t border-[var(--editor-border)]">
                 <BacklinksPanel onOpenFile={handleFileTreeOpen} />
               </div>
@@ -323,7 +336,7 @@ function App() {
               side="right"
               currentWidth={panelWidths.history}
               minWidth={220}
-              maxWidth={Math.floor(window.innerWidth * 0.5)}
+              maxWidth={Math.min(PANEL_WIDTH_MAX, Math.floor(window.innerWidth * 0.5))}
               onResize={(w) => setPanelWidth("history", w)}
               onDoubleClick={() => setPanelWidth("history", 288)}
             />
@@ -343,7 +356,7 @@ function App() {
               side="right"
               currentWidth={panelWidths.tags}
               minWidth={220}
-              maxWidth={Math.floor(window.innerWidth * 0.5)}
+              maxWidth={Math.min(PANEL_WIDTH_MAX, Math.floor(window.innerWidth * 0.5))}
               onResize={(w) => setPanelWidth("tags", w)}
               onDoubleClick={() => setPanelWidth("tags", 288)}
             />
@@ -366,6 +379,15 @@ function App() {
         />
       )}
 
+      {recoverable.length > 0 && (
+        <RecoveryDialog
+          buffers={recoverable}
+          onRecover={recoverBuffer}
+          onDiscard={discardRecovery}
+          onClose={dismissRecovery}
+        />
+      )}
+
       {showPreferences && (
         <PreferencesDialog
           onClose={() => setShowPreferences(false)}
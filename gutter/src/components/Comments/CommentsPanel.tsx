@@ -1,5 +1,9 @@
+import { invoke } from "@tauri-apps/api/core";
 import { useCommentStore } from "../../stores/commentStore";
 import { useEditorStore } from "../../stores/editorStore";
+import { useWorkspaceStore } from "../../stores/workspaceStore";
+import { useToastStore } from "../../stores/toastStore";
+import type { AnchorCandidate } from "../../types/comments";
 import { modLabel } from "../../utils/platform";
 import { Thread } from "./Thread";
 import { useState, useCallback, useMemo } from "react";
@@ -7,8 +11,13 @@ import { MessageSquare } from "../Icons";
 
 type FilterMode = "all" | "open" | "resolved";
 
-export function CommentsPanel() {
-  const { threads, getThreadIds } = useCommentStore();
+interface CommentsPanelProps {
+  /** Load the note again after the backend rewrote it */
+  onReload: () => Promise<void>;
+}
+
+export function CommentsPanel({ onReload }: CommentsPanelProps) {
+  const { threads, getThreadIds, anchorCandidates, setAnchorCandidates } = useCommentStore();
   const { activeCommentId, setActiveCommentId, commentTexts } = useEditorStore();
   const [filter, setFilter] = useState<FilterMode>("open");
 
@@ -46,6 +55,33 @@ export function CommentsPanel() {
     navigator.clipboard.writeText(md).catch(console.error);
   }, [threadIds, threads, totalCount, resolvedCount, openCount]);
 
+  // Candidates are offsets into the note as saved, so unsaved edits would
+  // put the highlight in the wrong place
+  const handleReattach = useCallback(
+    async (candidate: AnchorCandidate) => {
+      const { filePath, isDirty } = useEditorStore.getState();
+      if (!filePath) return;
+      if (isDirty) {
+        useToastStore.getState().addToast("Save the note before re-attaching a comment", "error");
+        return;
+      }
+      try {
+        await invoke("reattach_comment", {
+          path: filePath,
+          threadId: candidate.threadId,
+          start: candidate.start,
+          end: candidate.end,
+          workspace: useWorkspaceStore.getState().workspacePath,
+        });
+        setAnchorCandidates(anchorCandidates.filter((c) => c.threadId !== candidate.threadId));
+        await onReload();
+      } catch (e) {
+        useToastStore.getState().addToast(`Couldn't re-attach comment: ${e}`, "error");
+      }
+    },
+    [anchorCandidates, setAnchorCandidates, onReload],
+  );
+
   return (
     <div className="h-full flex flex-col bg-[var(--surface-secondary)]">
       <div className="flex items-center justify-between px-3 py-2 border-b border-[var(--editor-border)]">
@@ -105,6 +141,8 @@ export function CommentsPanel() {
             thread={threads[id]}
             isActive={activeCommentId === id}
             quotedText={commentTexts[id]}
+            candidates={anchorCandidates.filter((c) => c.threadId === id)}
+            onReattach={handleReattach}
             onClick={() => {
               setActiveCommentId(id);
               window.dispatchEvent(
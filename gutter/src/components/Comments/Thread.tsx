@@ -1,5 +1,5 @@
 import { useState, useEffect } from "react";
-import type { CommentThread } from "../../types/comments";
+import type { AnchorCandidate, CommentThread } from "../../types/comments";
 import { useCommentStore } from "../../stores/commentStore";
 import { useSettingsStore } from "../../stores/settingsStore";
 import { ReplyInput } from "./ReplyInput";
@@ -10,10 +10,13 @@ interface ThreadProps {
   thread: CommentThread;
   isActive: boolean;
   quotedText?: string;
+  // Places the thread could go back to, when its marker was lost
+  candidates?: AnchorCandidate[];
+  onReattach?: (candidate: AnchorCandidate) => void;
   onClick: () => void;
 }
 
-export function Thread({ commentId, thread, isActive, quotedText, onClick }: ThreadProps) {
+export function Thread({ commentId, thread, isActive, quotedText, candidates, onReattach, onClick }: ThreadProps) {
   const { resolveThread, deleteThread } = useCommentStore();
   const [expanded, setExpanded] = useState(!thread.resolved);
   const [showDeleteConfirm, setShowDeleteConfirm] = useState(false);
@@ -114,6 +117,29 @@ export function Thread({ commentId, thread, isActive, quotedText, onClick }: Thr
         </div>
       )}
 
+      {/* Lost marker: offer the likely new positions */}
+      {!quotedText && candidates && candidates.length > 0 && (
+        <div className="mx-3 mb-1.5 text-[11px] text-[var(--text-muted)]">
+          <div className="mb-1">Highlight lost. Re-attach to:</div>
+          {candidates.map((c) => (
+            <button
+              key={`${c.start}-${c.end}`}
+              onClick={(e) => {
+                e.stopPropagation();
+                onReattach?.(c);
+              }}
+              className="flex w-full items-baseline gap-1.5 rounded px-1.5 py-0.5 text-left hover:bg-[var(--surface-hover)] transition-colors"
+              title={c.text}
+            >
+              <span className="flex-1 truncate italic text-[12px] text-[var(--text-secondary)]">
+                "{c.text.length > 60 ? c.text.slice(0, 60) + "..." : c.text}"
+              </span>
+              <span className="shrink-0">{Math.round(c.confidence * 100)}%</span>
+            </button>
+          ))}
+        </div>
+      )}
+
       {/* Resolution status */}
       {thread.resolved && (
         <div className="px-3 pb-1 text-[11px] text-[var(--status-success)]">
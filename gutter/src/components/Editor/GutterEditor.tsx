@@ -31,6 +31,7 @@ import { invoke, convertFileSrc } from "@tauri-apps/api/core";
 import { useEditorStore } from "../../stores/editorStore";
 import { useCommentStore } from "../../stores/commentStore";
 import { useSettingsStore } from "../../stores/settingsStore";
+import { useWorkspaceStore } from "../../stores/workspaceStore";
 import { ContextMenu, type ContextMenuItem } from "../ContextMenu";
 import { Frontmatter } from "./extensions/Frontmatter";
 import { WikiLink } from "./extensions/WikiLink";
@@ -43,7 +44,7 @@ import TaskList from "@tiptap/extension-task-list";
 import TaskItem from "@tiptap/extension-task-item";
 import { createFindReplacePlugin } from "../FindReplace";
 import { modKey, modLabel } from "../../utils/platform";
-import { parentDir, joinPath } from "../../utils/path";
+import { parentDir, resolveAttachment } from "../../utils/path";
 import "../../styles/editor.css";
 
 const FindReplaceExtension = Extension.create({
@@ -161,14 +162,15 @@ export const GutterEditor = forwardRef<GutterEditorHandle, GutterEditorProps>(
       const buffer = await file.arrayBuffer();
       const data = Array.from(new Uint8Array(buffer));
       try {
-        await invoke<string>("save_image", {
+        const link = await invoke<string>("save_image", {
           dirPath,
           filename,
           data,
+          workspace: useWorkspaceStore.getState().workspacePath,
         });
-        const absolutePath = joinPath(dirPath, "assets", filename);
-        const displaySrc = convertFileSrc(absolutePath);
-        editorRef.current?.chain().focus().setImage({ src: displaySrc }).run();
+        const absolutePath = resolveAttachment(dirPath, link);
+        const image = { src: convertFileSrc(absolutePath), originalSrc: link };
+        editorRef.current?.chain().focus().setImage(image).run();
       } catch (e) {
         console.error("Failed to save image:", e);
         const { addToast } = await import("../../stores/toastStore").then(m => m.useToastStore.getState());
@@ -799,19 +801,24 @@ export const GutterEditor = forwardRef<GutterEditorHandle, GutterEditorProps>(
                   const ext = path.split(".").pop() || "png";
                   const filename = `image-${Date.now()}.${ext}`;
                             try {
-                              await invoke("copy_image", { source: path, dirPath, filename });
+                              const link = await invoke<string>("copy_image", {
+                                source: path,
+                                dirPath,
+                                filename,
+                                workspace: useWorkspaceStore.getState().workspacePath,
+                              });
                   
-                    const absolutePath = joinPath(dirPath, "assets", filename);
-                    const displaySrc = convertFileSrc(absolutePath);
+                    const absolutePath = resolveAttachment(dirPath, link);
+                    const image = { src: convertFileSrc(absolutePath), originalSrc: link };
         
           const posData = editor.view.posAtCoords({ left: clientX, top: clientY });
           if (posData) {
             editor.chain().focus().insertContentAt(posData.pos, {
               type: "image",
-              attrs: { src: displaySrc },
+              attrs: image,
             }).run();
           } else {
-            editor.chain().focus().setImage({ src: displaySrc }).run();
+            editor.chain().focus().setImage(image).run();
           }
         } catch (err) {
           console.error("Failed to insert image:", err);
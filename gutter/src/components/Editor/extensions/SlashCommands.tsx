@@ -4,7 +4,8 @@ import { Plugin, PluginKey } from "@tiptap/pm/state";
 import { open } from "@tauri-apps/plugin-dialog";
 import { invoke, convertFileSrc } from "@tauri-apps/api/core";
 import { useEditorStore } from "../../../stores/editorStore";
-import { parentDir, joinPath } from "../../../utils/path";
+import { useWorkspaceStore } from "../../../stores/workspaceStore";
+import { parentDir, resolveAttachment } from "../../../utils/path";
 
 interface SlashCommandItem {
   title: string;
@@ -136,10 +137,15 @@ const SLASH_ITEMS: SlashCommandItem[] = [
         const filename = `image-${Date.now()}.${ext}`;
         const dirPath = parentDir(filePath);
         try {
-          await invoke("copy_image", { source, dirPath, filename });
-          const absolutePath = joinPath(dirPath, "assets", filename);
-          const displaySrc = convertFileSrc(absolutePath);
-          editor.chain().focus().setImage({ src: displaySrc }).run();
+          const link = await invoke<string>("copy_image", {
+            source,
+            dirPath,
+            filename,
+            workspace: useWorkspaceStore.getState().workspacePath,
+          });
+          const absolutePath = resolveAttachment(dirPath, link);
+          const image = { src: convertFileSrc(absolutePath), originalSrc: link };
+          editor.chain().focus().setImage(image).run();
         } catch (e) {
           console.error("Failed to insert image:", e);
           const { useToastStore } = await import("../../../stores/toastStore");
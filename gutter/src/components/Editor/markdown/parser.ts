@@ -106,12 +106,13 @@ function isAbsoluteSrc(src: string): boolean {
 
 /**
  * Search the workspace file tree for a file matching the given name.
- * Uses the same "shortest path wins" strategy as wiki link resolution.
+ * Uses the same "shortest path wins" strategy as wiki link resolution,
+ * preferring the root that holds `dirPath`.
  */
-function resolveFileInWorkspace(target: string): string | null {
+function resolveFileInWorkspace(target: string, dirPath: string): string | null {
   const { fileTree } = useWorkspaceStore.getState();
   if (!fileTree.length) return null;
-  return resolveFileInTree(target, fileTree);
+  return resolveFileInTree(target, fileTree, joinPath(dirPath, target));
 }
 
 /** Walk the doc tree and convert relative image src to Tauri asset URLs */
@@ -124,7 +125,7 @@ function resolveImagePaths(node: JSONContent, dirPath: string) {
       node.attrs.src = src;
       node.attrs.wikiEmbed = true;
       // Wiki embeds resolve by searching the workspace (like wiki links)
-      const resolved = resolveFileInWorkspace(src);
+      const resolved = resolveFileInWorkspace(src, dirPath);
       if (resolved) {
         node.attrs.originalSrc = src;
         const resolvedNorm = resolved.replace(/\\/g, "/");
@@ -146,7 +147,7 @@ function resolveImagePaths(node: JSONContent, dirPath: string) {
       let searchTarget = decoded;
       // Strip leading ./ for workspace search — ./assets/img.png → assets/img.png
       if (searchTarget.startsWith("./")) searchTarget = searchTarget.slice(2);
-      const workspaceResolved = resolveFileInWorkspace(searchTarget);
+      const workspaceResolved = resolveFileInWorkspace(searchTarget, dirPath);
       if (workspaceResolved) {
         const resolvedNorm = workspaceResolved.replace(/\\/g, "/");
         node.attrs.filePath = resolvedNorm;
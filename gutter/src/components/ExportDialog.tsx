@@ -1,6 +1,16 @@
 import { useState, useCallback } from "react";
 import { invoke } from "@tauri-apps/api/core";
 import { save } from "@tauri-apps/plugin-dialog";
+import { useEditorStore } from "../stores/editorStore";
+import { useWorkspaceStore } from "../stores/workspaceStore";
+import { useToastStore } from "../stores/toastStore";
+
+/** What `export_docx` couldn't carry over */
+interface DocxExportReport {
+  images: number;
+  /** Images kept as their alt text and HTML blocks left out */
+  replaced: string[];
+}
 
 interface ExportDialogProps {
   markdown: string;
@@ -93,6 +103,34 @@ export function ExportDialog({ markdown, onClose }: ExportDialogProps) {
     onClose();
   }, [getContent, onClose]);
 
+  const handleExportDocx = useCallback(async () => {
+    setExporting(true);
+    try {
+      const path = await save({
+        filters: [{ name: "Word Document", extensions: ["docx"] }],
+      });
+      if (path) {
+        const savePath = typeof path === "string" ? path : (path as { path: string }).path;
+        // Threads come from the note's sidecar; without markers none are anchored
+        const report = await invoke<DocxExportReport>("export_docx", {
+          content: getContent(),
+          path: savePath,
+          source: useEditorStore.getState().filePath,
+          workspace: useWorkspaceStore.getState().workspacePath,
+        });
+        if (report.replaced.length > 0) {
+          useToastStore
+            .getState()
+            .addToast(`Not carried over to Word — ${report.replaced.join(", ")}`, "error", 8000);
+        }
+      }
+    } catch (e) {
+      console.error("Export failed:", e);
+    }
+    setExporting(false);
+    onClose();
+  }, [getContent, onClose]);
+
   const handleCopyRichText = useCallback(async () => {
     try {
       const html = markdownToHtml(getContent());
@@ -162,6 +200,13 @@ export function ExportDialog({ markdown, onClose }: ExportDialogProps) {
           >
             Export as Markdown
           </button>
+          <button
+            className="w-full px-4 py-2 rounded-lg border border-[var(--editor-border)] text-[var(--text-primary)] text-[13px] font-medium hover:bg-[var(--surface-hover)] transition-colors disabled:opacity-50"
+            onClick={handleExportDocx}
+            disabled={exporting}
+          >
+            Export as Word
+          </button>
           <button
             className="w-full px-4 py-2 rounded-lg border border-[var(--editor-border)] text-[var(--text-primary)] text-[13px] font-medium hover:bg-[var(--surface-hover)] transition-colors"
             onClick={handleCopyRichText}
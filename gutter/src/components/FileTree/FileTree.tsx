@@ -1,5 +1,5 @@
 import { useState, useCallback, useRef, useEffect, memo, useMemo } from "react";
-import { useWorkspaceStore, type FileEntry } from "../../stores/workspaceStore";
+import { useWorkspaceStore, rootFor, type FileEntry } from "../../stores/workspaceStore";
 import { useTagStore, getFilesForTags } from "../../stores/tagStore";
 import { useToastStore } from "../../stores/toastStore";
 import { open, ask } from "@tauri-apps/plugin-dialog";
@@ -19,6 +19,12 @@ import {
   X,
 } from "../Icons";
 
+/** The workspace root `path` belongs to, whose manual order it's in */
+function workspaceFor(path: string): string | null {
+  const { roots, workspacePath } = useWorkspaceStore.getState();
+  return rootFor(roots, path)?.path ?? workspacePath;
+}
+
 /** Flatten visible (expanded) file entries in display order — files only */
 function flattenVisibleFiles(
   entries: FileEntry[],
@@ -173,7 +179,7 @@ export function FileTree({ onFileOpen }: FileTreeProps) {
   const handleDeletePath = useCallback(
     async (path: string) => {
       try {
-        await invoke("delete_path", { path });
+        await invoke("delete_path", { path, workspace: workspaceFor(path) });
         if (workspacePath) {
           await loadFileTree(workspacePath);
         }
@@ -238,7 +244,7 @@ export function FileTree({ onFileOpen }: FileTreeProps) {
       if (!confirmed) return;
       for (const p of paths) {
         try {
-          await invoke("delete_path", { path: p });
+          await invoke("delete_path", { path: p, workspace: workspaceFor(p) });
         } catch (e) {
           useToastStore.getState().addToast(`Failed to delete ${pathFileName(p)}`, "error");
           console.error("Failed to delete:", e);
@@ -259,7 +265,7 @@ export function FileTree({ onFileOpen }: FileTreeProps) {
       parts[parts.length - 1] = newName.trim();
       const newPath = parts.join("/");
       try {
-        await invoke("rename_path", { oldPath, newPath });
+        await invoke("rename_path", { oldPath, newPath, workspace: workspaceFor(oldPath) });
         if (workspacePath) {
           await loadFileTree(workspacePath);
         }
@@ -340,7 +346,7 @@ export function FileTree({ onFileOpen }: FileTreeProps) {
               if (fName) {
                 const newPath = joinPath(dropTarget, fName);
                 try {
-                  await invoke("rename_path", { oldPath: path, newPath: newPath });
+                  await invoke("rename_path", { oldPath: path, newPath: newPath, workspace: workspaceFor(path) });
                 } catch (err) {
                   console.error("Bulk move failed for:", path, err);
                 }
@@ -360,7 +366,11 @@ export function FileTree({ onFileOpen }: FileTreeProps) {
             if (fName) {
               const newPath = joinPath(dropTarget, fName);
               try {
-                await invoke("rename_path", { oldPath: d.sourcePath, newPath: newPath });
+                await invoke("rename_path", {
+                  oldPath: d.sourcePath,
+                  newPath: newPath,
+                  workspace: workspaceFor(d.sourcePath),
+                });
               } catch (err) {
                 useToastStore.getState().addToast("Failed to move file", "error");
                 console.error("Move failed:", err);
@@ -644,6 +654,12 @@ const FileTreeNode = memo(function FileTreeNode({
     }
   }, [expanded, entry.is_dir, entry.path, expandedPathsRef]);
 
+  // Folders past the tree read's depth limit come with a count only
+  const unloaded = entry.is_dir && entry.children === null && !entry.error && (entry.child_count ?? 0) > 0;
+  useEffect(() => {
+    if (expanded && unloaded) useWorkspaceStore.getState().loadChildren(entry.path);
+  }, [expanded, unloaded, entry.path]);
+
   const handleMouseDown = (e: React.MouseEvent) => {
     // Only left click, not during rename
     if (e.button !== 0 || renaming) return;
@@ -715,6 +731,15 @@ const FileTreeNode = memo(function FileTreeNode({
       );
     }
     items.push(
+      {
+        label: "Move Up",
+        action: () => useWorkspaceStore.getState().moveEntry(entry.path, -1),
+      },
+      {
+        label: "Move Down",
+        action: () => useWorkspaceStore.getState().moveEntry(entry.path, 1),
+      },
+      { label: "", action: () => {}, separator: true },
       {
         label: "Rename",
         action: () => setRenaming(true),
@@ -791,7 +816,10 @@ const FileTreeNode = memo(function FileTreeNode({
               onCancel={() => setRenaming(false)}
             />
           ) : (
-            <span className="font-medium text-[var(--text-primary)] truncate">
+            <span
+              className={`font-medium truncate ${entry.error ? "text-[var(--text-muted)]" : "text-[var(--text-primary)]"} ${entry.is_symlink ? "italic" : ""}`}
+              title={entry.error ?? (entry.is_symlink ? "Symlink" : undefined)}
+            >
               {entry.name}
             </span>
           )}
@@ -911,11 +939,8 @@ const FileTreeNode = memo(function FileTreeNode({
         />
       ) : (
         <span
-          className={
-            isMd
-              ? "text-[var(--text-primary)] truncate"
-              : "text-[var(--text-tertiary)] truncate"
-          }
+          className={`${isMd ? "text-[var(--text-primary)]" : "text-[var(--text-tertiary)]"} truncate ${entry.is_symlink ? "italic" : ""}`}
+          title={entry.is_symlink ? "Symlink" : undefined}
         >
           {entry.name}
         </span>
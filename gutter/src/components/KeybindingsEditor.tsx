@@ -0,0 +1,95 @@
+import { useState } from "react";
+import { useSettingsStore } from "../stores/settingsStore";
+import { useKeybindingStore } from "../stores/keybindingStore";
+import { formatShortcut, shortcutFromEvent } from "../utils/keybindings";
+
+/**
+ * Lists every bindable command with its shortcut. Click a shortcut and
+ * press keys to rebind it (Escape cancels); commands whose keys collide
+ * are flagged.
+ */
+export function KeybindingsEditor() {
+  const commands = useKeybindingStore((s) => s.commands);
+  const conflicts = useKeybindingStore((s) => s.conflicts);
+  const keybindings = useSettingsStore((s) => s.keybindings);
+  const setKeybinding = useSettingsStore((s) => s.setKeybinding);
+  const [recording, setRecording] = useState<string | null>(null);
+
+  const conflictFor = (id: string) => conflicts.find((c) => c.commands.includes(id));
+
+  const handleKeyDown = (id: string, e: React.KeyboardEvent) => {
+    // Keep the keys from the dialog's Escape handler and the shortcuts
+    e.preventDefault();
+    e.stopPropagation();
+    if (e.key === "Escape" && !e.metaKey && !e.ctrlKey && !e.altKey && !e.shiftKey) {
+      setRecording(null);
+      return;
+    }
+    const shortcut = shortcutFromEvent(e.nativeEvent);
+    if (!shortcut) return;
+    setKeybinding(id, shortcut);
+    setRecording(null);
+  };
+
+  return (
+    <div className="max-h-64 overflow-auto -mx-1 px-1">
+      {commands.map((command) => {
+        const conflict = conflictFor(command.id);
+        const wanted = keybindings[command.id] ?? command.default;
+        const lost = conflict && conflict.kept !== command.id;
+        return (
+          <div key={command.id} className="flex items-center justify-between gap-2 py-1">
+            <span
+              className={`text-[13px] truncate ${lost ? "text-red-500" : "text-[var(--text-secondary)]"}`}
+              title={
+                conflict
+                  ? `${formatShortcut(conflict.shortcut)} is wanted by ${conflict.commands.length} commands`
+                  : undefined
+              }
+            >
+              {command.label}
+            </span>
+            <div className="flex items-center gap-1 shrink-0">
+              <button
+                className={`min-w-[7rem] px-2 py-0.5 rounded-md border text-[12px] font-mono text-center transition-colors ${
+                  recording === command.id
+                    ? "border-[var(--accent)] text-[var(--accent)]"
+                    : lost
+                      ? "border-red-500/60 text-red-500"
+                      : "border-[var(--editor-border)] text-[var(--text-primary)] hover:bg-[var(--surface-hover)]"
+                }`}
+                onClick={() => setRecording(recording === command.id ? null : command.id)}
+                onKeyDown={(e) => recording === command.id && handleKeyDown(command.id, e)}
+                onBlur={() => recording === command.id && setRecording(null)}
+              >
+                {recording === command.id
+                  ? "Press keys…"
+                  : lost && wanted
+                    ? formatShortcut(wanted)
+                    : command.shortcut
+                      ? formatShortcut(command.shortcut)
+                      : "—"}
+              </button>
+              <button
+                className="w-5 h-5 flex items-center justify-center rounded text-[12px] text-[var(--text-muted)] hover:text-[var(--text-primary)] hover:bg-[var(--surface-hover)] disabled:opacity-30 disabled:pointer-events-none"
+                title="Remove shortcut"
+                disabled={!command.shortcut && !lost}
+                onClick={() => setKeybinding(command.id, "")}
+              >
+                ×
+              </button>
+              <button
+                className="w-5 h-5 flex items-center justify-center rounded text-[12px] text-[var(--text-muted)] hover:text-[var(--text-primary)] hover:bg-[var(--surface-hover)] disabled:opacity-30 disabled:pointer-events-none"
+                title="Reset to default"
+                disabled={!command.custom}
+                onClick={() => setKeybinding(command.id, null)}
+              >
+                ↺
+              </button>
+            </div>
+          </div>
+        );
+      })}
+    </div>
+  );
+}
@@ -1,5 +1,7 @@
 import { useEffect, useRef, type ReactNode } from "react";
-import { useSettingsStore } from "../stores/settingsStore";
+import { useSettingsStore, describeIssue, type Settings } from "../stores/settingsStore";
+import { useWorkspaceStore } from "../stores/workspaceStore";
+import { KeybindingsEditor } from "./KeybindingsEditor";
 import type { Editor } from "@tiptap/react";
 
 interface PreferencesDialogProps {
@@ -102,10 +104,22 @@ function Stepper({
 }
 
 /* ── Row layout ── */
-function Row({ label, children }: { label: string; children: ReactNode }) {
+function Row({ label, setting, children }: { label: string; setting?: keyof Settings; children: ReactNode }) {
+  // Values from the workspace's .gutter/config.json are saved back there
+  const fromWorkspace = useSettingsStore((s) => setting !== undefined && s.workspaceKeys.includes(setting));
   return (
     <div className="flex items-center justify-between py-1.5">
-      <span className="text-[13px] text-[var(--text-secondary)]">{label}</span>
+      <span className="text-[13px] text-[var(--text-secondary)]">
+        {label}
+        {fromWorkspace && (
+          <span
+            className="ml-1.5 text-[10px] uppercase tracking-wide text-[var(--text-muted)]"
+            title="Set for this workspace in .gutter/config.json"
+          >
+            Workspace
+          </span>
+        )}
+      </span>
       {children}
     </div>
   );
@@ -140,6 +154,9 @@ export function PreferencesDialog({ onClose, editorRef }: PreferencesDialogProps
     setDefaultAuthor,
     accentColor,
     setAccentColor,
+    followSymlinks,
+    setFollowSymlinks,
+    settingsIssues,
   } = useSettingsStore();
 
   const authorRef = useRef<HTMLInputElement>(null);
@@ -168,6 +185,14 @@ export function PreferencesDialog({ onClose, editorRef }: PreferencesDialogProps
     }
   };
 
+  const handleFollowSymlinksToggle = async (enabled: boolean) => {
+    setFollowSymlinks(enabled);
+    // The backend reads the setting on each listing, so save before reloading
+    await useSettingsStore.getState().saveSettings();
+    const { workspacePath, loadFileTree } = useWorkspaceStore.getState();
+    if (workspacePath) loadFileTree(workspacePath);
+  };
+
   const handleAuthorBlur = () => {
     const val = authorRef.current?.value.trim();
     if (val !== undefined && val !== defaultAuthor) {
@@ -197,10 +222,19 @@ export function PreferencesDialog({ onClose, editorRef }: PreferencesDialogProps
           Preferences
         </h2>
 
+        {settingsIssues.length > 0 && (
+          <div className="mb-3 rounded-md border border-[var(--editor-border)] px-2.5 py-2 text-[12px] text-[var(--text-secondary)]">
+            <div className="font-medium text-[var(--text-primary)]">Ignored values (defaults in use)</div>
+            {settingsIssues.map((issue) => (
+              <div key={`${issue.layer}:${issue.key}`}>{describeIssue(issue)}</div>
+            ))}
+          </div>
+        )}
+
         {/* ── Appearance ── */}
         <SectionHeader>Appearance</SectionHeader>
 
-        <Row label="Theme">
+        <Row label="Theme" setting="theme">
           <SegmentedControl
             options={[
               { label: "Light", value: "light" },
@@ -212,7 +246,7 @@ export function PreferencesDialog({ onClose, editorRef }: PreferencesDialogProps
           />
         </Row>
 
-        <Row label="Accent color">
+        <Row label="Accent color" setting="accentColor">
           <div className="flex items-center gap-1.5">
             {[
               { name: "indigo",  color: "#6366f1" },
@@ -254,7 +288,7 @@ export function PreferencesDialog({ onClose, editorRef }: PreferencesDialogProps
           </div>
         </Row>
 
-        <Row label="Editor font size">
+        <Row label="Editor font size" setting="fontSize">
           <Stepper
             value={fontSize}
             min={12}
@@ -264,7 +298,7 @@ export function PreferencesDialog({ onClose, editorRef }: PreferencesDialogProps
           />
         </Row>
 
-        <Row label="Editor font">
+        <Row label="Editor font" setting="fontFamily">
           <SegmentedControl
             options={[
               { label: "Serif", value: "serif" },
@@ -276,7 +310,7 @@ export function PreferencesDialog({ onClose, editorRef }: PreferencesDialogProps
           />
         </Row>
 
-        <Row label="Editor width">
+        <Row label="Editor width" setting="editorWidth">
           <SegmentedControl
             options={[
               { label: "Narrow", value: "narrow" },
@@ -289,7 +323,7 @@ export function PreferencesDialog({ onClose, editorRef }: PreferencesDialogProps
           />
         </Row>
 
-        <Row label="Line height">
+        <Row label="Line height" setting="lineHeight">
           <SegmentedControl
             options={[
               { label: "Compact", value: "compact" },
@@ -304,11 +338,11 @@ export function PreferencesDialog({ onClose, editorRef }: PreferencesDialogProps
         {/* ── Editor ── */}
         <SectionHeader>Editor</SectionHeader>
 
-        <Row label="Spell check">
+        <Row label="Spell check" setting="spellCheckEnabled">
           <Toggle checked={spellCheckEnabled} onChange={handleSpellCheckToggle} />
         </Row>
 
-        <Row label="Auto-save">
+        <Row label="Auto-save" setting="autoSaveInterval">
           <SegmentedControl
             options={autoSaveOptions}
             value={String(autoSaveInterval)}
@@ -316,10 +350,14 @@ export function PreferencesDialog({ onClose, editorRef }: PreferencesDialogProps
           />
         </Row>
 
+        <Row label="Follow symlinks" setting="followSymlinks">
+          <Toggle checked={followSymlinks} onChange={handleFollowSymlinksToggle} />
+        </Row>
+
         {/* ── Comments ── */}
         <SectionHeader>Comments</SectionHeader>
 
-        <Row label="Default author">
+        <Row label="Default author" setting="defaultAuthor">
           <input
             ref={authorRef}
             type="text"
@@ -335,6 +373,11 @@ export function PreferencesDialog({ onClose, editorRef }: PreferencesDialogProps
           />
         </Row>
 
+        {/* ── Keyboard Shortcuts ── */}
+        <SectionHeader>Keyboard Shortcuts</SectionHeader>
+
+        <KeybindingsEditor />
+
         <button
           className="mt-4 w-full text-[12px] text-[var(--text-muted)] hover:text-[var(--text-secondary)] transition-colors"
           onClick={onClose}
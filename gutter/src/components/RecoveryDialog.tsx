@@ -0,0 +1,87 @@
+import type { RecoverableBuffer } from "../hooks/useRecoveryJournal";
+import { fileName as pathFileName } from "../utils/path";
+
+interface RecoveryDialogProps {
+  buffers: RecoverableBuffer[];
+  onRecover: (buffer: RecoverableBuffer) => void;
+  onDiscard: (buffer: RecoverableBuffer) => void;
+  onClose: () => void;
+}
+
+function lineClass(line: string): string {
+  if (line.startsWith("@@")) return "text-[var(--text-muted)]";
+  if (line.startsWith("+")) return "text-green-600 bg-green-500/10";
+  if (line.startsWith("-")) return "text-red-600 bg-red-500/10";
+  return "text-[var(--text-secondary)]";
+}
+
+export function RecoveryDialog({ buffers, onRecover, onDiscard, onClose }: RecoveryDialogProps) {
+  return (
+    <div className="fixed inset-0 bg-black/40 backdrop-blur-sm flex items-center justify-center z-[200]">
+      <div
+        className="w-[560px] max-h-[80vh] flex flex-col bg-[var(--glass-bg)] backdrop-blur-[20px] rounded-xl border border-[var(--glass-border)] p-5"
+        style={{ boxShadow: "var(--shadow-xl)" }}
+      >
+        <h2 className="text-[15px] font-semibold text-[var(--text-primary)] mb-1">
+          Recover Unsaved Changes
+        </h2>
+        <p className="text-[13px] text-[var(--text-secondary)] mb-4">
+          Gutter closed before these changes were saved.
+        </p>
+
+        <div className="flex flex-col gap-3 overflow-auto min-h-0">
+          {buffers.map((buffer) => (
+            <div
+              key={buffer.path}
+              className="rounded-lg border border-[var(--editor-border)] p-3"
+            >
+              <div className="flex items-baseline justify-between gap-2 mb-2">
+                <div className="min-w-0">
+                  <div className="text-[13px] font-medium text-[var(--text-primary)] truncate">
+                    {buffer.onDisk ? pathFileName(buffer.path) : "Untitled"}
+                  </div>
+                  <div className="text-[11px] text-[var(--text-muted)] truncate" title={buffer.path}>
+                    {buffer.onDisk ? buffer.path : "Not on disk"} · {new Date(buffer.updated).toLocaleString()}
+                  </div>
+                  {buffer.changedOnDisk != null && (
+                    <div className="text-[11px] text-amber-600">
+                      File changed on disk since · {new Date(buffer.changedOnDisk).toLocaleString()}
+                    </div>
+                  )}
+                </div>
+                <div className="flex gap-2 shrink-0">
+                  <button
+                    className="px-3 py-1 rounded-lg bg-[var(--accent)] text-white text-[12px] font-medium hover:opacity-90 transition-opacity"
+                    onClick={() => onRecover(buffer)}
+                  >
+                    Recover
+                  </button>
+                  <button
+                    className="px-3 py-1 rounded-lg border border-[var(--editor-border)] text-[var(--text-primary)] text-[12px] font-medium hover:bg-[var(--surface-hover)] transition-colors"
+                    onClick={() => onDiscard(buffer)}
+                  >
+                    Discard
+                  </button>
+                </div>
+              </div>
+              <pre className="max-h-48 overflow-auto rounded bg-[var(--surface-hover)] p-2 text-[11px] leading-snug font-mono">
+                {buffer.diff.split("\n").filter(Boolean).map((line, i) => (
+                  <div key={i} className={lineClass(line)}>
+                    {line}
+                  </div>
+                ))}
+              </pre>
+            </div>
+          ))}
+        </div>
+
+        <button
+          className="mt-3 w-full text-[12px] text-[var(--text-muted)] hover:text-[var(--text-secondary)]"
+          onClick={onClose}
+        >
+          Decide later
+        </button>
+      </div>
+    </div>
+  );
+}
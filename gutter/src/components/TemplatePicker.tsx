@@ -36,7 +36,7 @@ export function TemplatePicker({
     (async () => {
       try {
         await invoke("init_default_templates");
-        const names = await invoke<string[]>("list_templates");
+        const names = await invoke<string[]>("list_templates", { workspace: workspacePath });
         setTemplates(names);
         if (mode === "new" && names.length > 0) {
           setSelectedTemplate(names[0]);
@@ -45,7 +45,7 @@ export function TemplatePicker({
         console.error("Failed to load templates:", e);
       }
     })();
-  }, [mode]);
+  }, [mode, workspacePath]);
 
   // Load preview when selection changes
   useEffect(() => {
@@ -57,6 +57,7 @@ export function TemplatePicker({
       try {
         const content = await invoke<string>("read_template", {
           name: selectedTemplate,
+          workspace: workspacePath,
         });
         setPreview(content);
         // Pre-fill filename
@@ -67,7 +68,7 @@ export function TemplatePicker({
         console.error("Failed to read template:", e);
       }
     })();
-  }, [selectedTemplate]);
+  }, [selectedTemplate, workspacePath]);
 
   // Focus input on mount
   useEffect(() => {
@@ -105,6 +106,7 @@ export function TemplatePicker({
     try {
       const content = await invoke<string>("read_template", {
         name: selectedTemplate,
+        workspace: workspacePath,
       });
       // Replace {{date}} with today's date
       const today = new Date().toISOString().slice(0, 10);
@@ -143,6 +145,7 @@ export function TemplatePicker({
       await invoke("save_template", {
         name: templateName.trim(),
         content: currentContent,
+        workspace: workspacePath,
       });
       useToastStore
         .getState()
@@ -153,12 +156,12 @@ export function TemplatePicker({
       console.error("Failed to save template:", e);
     }
     setLoading(false);
-  }, [templateName, currentContent, onClose]);
+  }, [templateName, currentContent, workspacePath, onClose]);
 
   const handleDelete = useCallback(
     async (name: string) => {
       try {
-        await invoke("delete_template", { name });
+        await invoke("delete_template", { name, workspace: workspacePath });
         setTemplates((prev) => prev.filter((t) => t !== name));
         if (selectedTemplate === name) {
           setSelectedTemplate(null);
@@ -171,7 +174,7 @@ export function TemplatePicker({
         useToastStore.getState().addToast("Failed to delete template", "error");
       }
     },
-    [selectedTemplate],
+    [selectedTemplate, workspacePath],
   );
 
   const existingMatch = templates.find(
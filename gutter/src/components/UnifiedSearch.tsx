@@ -1,7 +1,7 @@
 import { useState, useEffect, useRef, useCallback, useMemo } from "react";
 import { invoke } from "@tauri-apps/api/core";
-import { useWorkspaceStore, type FileEntry } from "../stores/workspaceStore";
-import { useSettingsStore } from "../stores/settingsStore";
+import { useWorkspaceStore, rootFor, type FileEntry } from "../stores/workspaceStore";
+import { useRecentStore, recentPaths } from "../stores/recentStore";
 import { useTagStore, getAllTags } from "../stores/tagStore";
 import { useEditorStore } from "../stores/editorStore";
 import { fileName as pathFileName } from "../utils/path";
@@ -22,6 +22,7 @@ interface UnifiedSearchProps {
 
 interface HeadingResult {
   type: "Heading";
+  root: string;
   path: string;
   text: string;
   level: number;
@@ -30,6 +31,7 @@ interface HeadingResult {
 
 interface ContentResult {
   type: "Content";
+  root: string;
   path: string;
   line: number;
   snippet: string;
@@ -93,8 +95,9 @@ export function UnifiedSearch({ commands, onOpenFile, onClose, filterMode }: Uni
   const listRef = useRef<HTMLDivElement>(null);
   const debounceRef = useRef<ReturnType<typeof setTimeout>>(undefined);
 
-  const { fileTree, workspacePath } = useWorkspaceStore();
-  const { recentFiles } = useSettingsStore();
+  const { fileTree, workspacePath, roots } = useWorkspaceStore();
+  const recentEntries = useRecentStore((s) => s.entries);
+  const recentFiles = useMemo(() => recentPaths(recentEntries, "file"), [recentEntries]);
 
   const allFiles = useMemo(() => flattenTree(fileTree), [fileTree]);
 
@@ -157,6 +160,7 @@ export function UnifiedSearch({ commands, onOpenFile, onClose, filterMode }: Uni
         const results = await invoke<ServerResult[]>("search_workspace", {
           workspace: workspacePath,
           query,
+          roots: roots?.map((r) => r.path),
         });
         setServerResults(results);
       } catch {
@@ -165,7 +169,7 @@ export function UnifiedSearch({ commands, onOpenFile, onClose, filterMode }: Uni
     }, 150);
 
     return () => clearTimeout(debounceRef.current);
-  }, [query, workspacePath, filterMode]);
+  }, [query, workspacePath, roots, filterMode]);
 
   // Build flat list of all rows for keyboard navigation
   const allRows = useMemo((): ResultRow[] => {
@@ -207,12 +211,17 @@ export function UnifiedSearch({ commands, onOpenFile, onClose, filterMode }: Uni
 
   const getRelativePath = useCallback(
     (fullPath: string) => {
+      // Multi-root paths are shown under their root's label
+      const root = rootFor(roots, fullPath);
+      if (root) {
+        return `${root.name}/${fullPath.slice(root.path.length + 1)}`;
+      }
       if (workspacePath && fullPath.startsWith(workspacePath)) {
         return fullPath.slice(workspacePath.length + 1);
       }
       return fullPath;
     },
-    [workspacePath],
+    [workspacePath, roots],
   );
 
   const executeRow = useCallback(
@@ -1,12 +1,13 @@
 import { useState } from "react";
-import { useSettingsStore } from "../stores/settingsStore";
+import { useRecentStore, type RecentEntry } from "../stores/recentStore";
+import { Pin, X } from "./Icons";
 import { modLabel } from "../utils/platform";
 import { fileName as pathFileName } from "../utils/path";
 
 interface WelcomeScreenProps {
   onNewFile: () => void;
   onOpenFile: () => void;
-  onOpenRecent: (path: string) => void;
+  onOpenRecent: (entry: RecentEntry) => void;
 }
 
 const mod = modLabel();
@@ -60,7 +61,8 @@ const allShortcuts = [
 ];
 
 export function WelcomeScreen({ onNewFile, onOpenFile, onOpenRecent }: WelcomeScreenProps) {
-  const { recentFiles } = useSettingsStore();
+  const { entries, pinRecent, removeRecent } = useRecentStore();
+  const recent = entries.slice(0, 8);
   const [showAllShortcuts, setShowAllShortcuts] = useState(false);
 
   return (
@@ -88,26 +90,44 @@ export function WelcomeScreen({ onNewFile, onOpenFile, onOpenRecent }: WelcomeSc
           </button>
         </div>
 
-        {recentFiles.length > 0 && (
+        {recent.length > 0 && (
           <div className="mb-10 text-left">
             <h3 className="text-[11px] font-semibold uppercase tracking-wider text-[var(--text-muted)] mb-2">
-              Recent Files
+              Recent
             </h3>
             <div className="space-y-1">
-              {recentFiles.slice(0, 8).map((path) => {
-                const name = pathFileName(path) || path;
+              {recent.map((entry) => {
+                const name = pathFileName(entry.path) || entry.path;
                 return (
-                  <button
-                    key={path}
-                    className="w-full text-left px-3 py-1.5 rounded-md text-[13px] text-[var(--text-secondary)] hover:bg-[var(--surface-hover)] hover:text-[var(--text-primary)] transition-colors truncate"
-                    onClick={() => onOpenRecent(path)}
-                    title={path}
-                  >
-                    {name}
-                    <span className="ml-2 text-[11px] text-[var(--text-muted)]">
-                      {path}
-                    </span>
-                  </button>
+                  <div key={entry.path} className="group flex items-center rounded-md hover:bg-[var(--surface-hover)] transition-colors">
+                    <button
+                      className="flex-1 min-w-0 text-left px-3 py-1.5 text-[13px] text-[var(--text-secondary)] hover:text-[var(--text-primary)] truncate"
+                      onClick={() => onOpenRecent(entry)}
+                      title={entry.path}
+                    >
+                      {name}
+                      {entry.kind === "workspace" && (
+                        <span className="ml-2 text-[11px] text-[var(--text-muted)]">workspace</span>
+                      )}
+                      <span className="ml-2 text-[11px] text-[var(--text-muted)]">
+                        {entry.path}
+                      </span>
+                    </button>
+                    <button
+                      className={`p-1 text-[var(--text-muted)] hover:text-[var(--text-primary)] ${entry.pinned ? "" : "opacity-0 group-hover:opacity-100"}`}
+                      onClick={() => pinRecent(entry.path, !entry.pinned)}
+                      title={entry.pinned ? "Unpin" : "Pin"}
+                    >
+                      <Pin size={12} />
+                    </button>
+                    <button
+                      className="p-1 mr-1 text-[var(--text-muted)] hover:text-[var(--text-primary)] opacity-0 group-hover:opacity-100"
+                      onClick={() => removeRecent(entry.path)}
+                      title="Remove from list"
+                    >
+                      <X size={12} />
+                    </button>
+                  </div>
                 );
               })}
             </div>
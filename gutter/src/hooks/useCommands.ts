@@ -1,9 +1,11 @@
 import { useMemo } from "react";
+import { invoke } from "@tauri-apps/api/core";
 import { open } from "@tauri-apps/plugin-dialog";
 import { useEditorStore } from "../stores/editorStore";
 import { useWorkspaceStore } from "../stores/workspaceStore";
 import { useToastStore } from "../stores/toastStore";
-import { modLabel } from "../utils/platform";
+import { useKeybindingStore, shortcutFor } from "../stores/keybindingStore";
+import { formatShortcut } from "../utils/keybindings";
 import { parentDir } from "../utils/path";
 
 export interface Command {
@@ -42,35 +44,41 @@ export interface CommandDeps {
 export function useCommands(deps: CommandDeps): Command[] {
   const isSourceMode = useEditorStore((s) => s.isSourceMode);
   const isReadingMode = useEditorStore((s) => s.isReadingMode);
+  const bindings = useKeybindingStore((s) => s.commands);
 
   return useMemo(() => {
-    const mod = modLabel();
+    // Shortcuts as currently bound, which may differ from the defaults
+    const key = (id: string) => {
+      const shortcut = shortcutFor(bindings, id);
+      return shortcut ? formatShortcut(shortcut) : undefined;
+    };
     return [
-      { name: "New File", shortcut: `${mod}+N`, action: deps.handleNewFile },
-      { name: "Search", shortcut: `${mod}+K`, action: () => deps.setUnifiedSearchMode("all") },
-      { name: "Open File", shortcut: `${mod}+O`, action: deps.handleOpenFile },
-      { name: "Save File", shortcut: `${mod}+S`, action: deps.handleSave },
-      { name: "Toggle Source Mode", shortcut: `${mod}+/`, action: isSourceMode ? deps.switchToWysiwyg : deps.switchToSource },
-      { name: "Toggle Reading Mode", shortcut: `${mod}+Shift+R`, action: () => {
+      { name: "New File", shortcut: key("new_file"), action: deps.handleNewFile },
+      { name: "New Window", shortcut: key("new_window"), action: () => invoke("new_window", { path: null }) },
+      { name: "Search", shortcut: key("search"), action: () => deps.setUnifiedSearchMode("all") },
+      { name: "Open File", shortcut: key("open"), action: deps.handleOpenFile },
+      { name: "Save File", shortcut: key("save"), action: deps.handleSave },
+      { name: "Toggle Source Mode", shortcut: key("toggle_source"), action: isSourceMode ? deps.switchToWysiwyg : deps.switchToSource },
+      { name: "Toggle Reading Mode", shortcut: key("toggle_reading"), action: () => {
         if (isSourceMode && !isReadingMode) deps.switchToWysiwyg();
         deps.toggleReadingMode();
       }},
-      { name: "Toggle File Tree", shortcut: `${mod}+\\`, action: deps.toggleFileTree },
-      { name: "Toggle Comments Panel", shortcut: `${mod}+Shift+C`, action: deps.toggleComments },
-      { name: "Version History", shortcut: `${mod}+Shift+H`, action: deps.toggleHistory },
-      { name: "Tag Browser", shortcut: `${mod}+Shift+T`, action: deps.toggleTags },
-      { name: "Toggle Dark/Light Mode", shortcut: `${mod}+Shift+D`, action: () => deps.cycleTheme() },
-      { name: "Toggle Document Outline", action: () => deps.toggleOutline() },
-      { name: "Quick Open File", shortcut: `${mod}+P`, action: () => deps.setUnifiedSearchMode("files") },
-      { name: "Find", shortcut: `${mod}+F`, action: () => deps.setFindReplaceMode("find") },
-      { name: "Find and Replace", shortcut: `${mod}+H`, action: () => deps.setFindReplaceMode("replace") },
-      { name: "Export", shortcut: `${mod}+Shift+E`, action: () => deps.setShowExport(true) },
-      { name: "Preferences", shortcut: `${mod}+,`, action: () => deps.setShowPreferences(true) },
+      { name: "Toggle File Tree", shortcut: key("toggle_tree"), action: deps.toggleFileTree },
+      { name: "Toggle Comments Panel", shortcut: key("toggle_comments"), action: deps.toggleComments },
+      { name: "Version History", shortcut: key("toggle_history"), action: deps.toggleHistory },
+      { name: "Tag Browser", shortcut: key("toggle_tags"), action: deps.toggleTags },
+      { name: "Toggle Dark/Light Mode", shortcut: key("cycle_theme"), action: () => deps.cycleTheme() },
+      { name: "Toggle Document Outline", shortcut: key("toggle_outline"), action: () => deps.toggleOutline() },
+      { name: "Quick Open File", shortcut: key("quick_open"), action: () => deps.setUnifiedSearchMode("files") },
+      { name: "Find", shortcut: key("find"), action: () => deps.setFindReplaceMode("find") },
+      { name: "Find and Replace", shortcut: key("replace"), action: () => deps.setFindReplaceMode("replace") },
+      { name: "Export", shortcut: key("export"), action: () => deps.setShowExport(true) },
+      { name: "Preferences", shortcut: key("preferences"), action: () => deps.setShowPreferences(true) },
       { name: "Toggle Spell Check", action: () => deps.toggleSpellCheck() },
-      { name: "New Comment", shortcut: `${mod}+Shift+M`, action: () => deps.createComment() },
-      { name: "Next Comment", shortcut: `${mod}+Shift+N`, action: () => deps.navigateComment("next") },
-      { name: "Previous Comment", action: () => deps.navigateComment("prev") },
-      { name: "New from Template", action: async () => {
+      { name: "New Comment", shortcut: key("new_comment"), action: () => deps.createComment() },
+      { name: "Next Comment", shortcut: key("next_comment"), action: () => deps.navigateComment("next") },
+      { name: "Previous Comment", shortcut: key("prev_comment"), action: () => deps.navigateComment("prev") },
+      { name: "New from Template", shortcut: key("new_from_template"), action: async () => {
         const currentPath = useEditorStore.getState().filePath;
         const ws = useWorkspaceStore.getState().workspacePath;
         let folder = currentPath ? parentDir(currentPath) : ws;
@@ -81,7 +89,7 @@ export function useCommands(deps: CommandDeps): Command[] {
         }
         deps.setTemplatePicker({ mode: "new", targetFolder: folder });
       }},
-      { name: "Save as Template", action: () => {
+      { name: "Save as Template", shortcut: key("save_as_template"), action: () => {
         if (!deps.getMarkdown()) { useToastStore.getState().addToast("No content to save as template", "error"); return; }
         const currentPath = useEditorStore.getState().filePath;
         const ws = useWorkspaceStore.getState().workspacePath;
@@ -89,5 +97,5 @@ export function useCommands(deps: CommandDeps): Command[] {
         deps.setTemplatePicker({ mode: "save", targetFolder: folder });
       }},
     ];
-  }, [deps, isSourceMode, isReadingMode]);
+  }, [deps, isSourceMode, isReadingMode, bindings]);
 }
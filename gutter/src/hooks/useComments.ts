@@ -2,24 +2,34 @@ import { useCallback } from "react";
 import { invoke } from "@tauri-apps/api/core";
 import { useCommentStore } from "../stores/commentStore";
 import { useEditorStore } from "../stores/editorStore";
+import { useWorkspaceStore } from "../stores/workspaceStore";
 import { fileName as pathFileName } from "../utils/path";
-import type { CommentsFile } from "../types/comments";
+import type { AnchorCandidate, CommentsFile } from "../types/comments";
 
 export function useComments() {
-  const { loadComments, getCommentsFile, setFilePath } =
+  const { loadComments, getCommentsFile, setFilePath, setAnchorCandidates } =
     useCommentStore();
   const editorFilePath = useEditorStore((s) => s.filePath);
 
   const loadCommentsFromFile = useCallback(
     async (mdPath: string) => {
       setFilePath(mdPath);
+      setAnchorCandidates([]);
+      const workspace = useWorkspaceStore.getState().workspacePath;
       try {
         const content = await invoke<string>("read_comments", {
           path: mdPath,
+          workspace,
         });
         if (content) {
           const data: CommentsFile = JSON.parse(content);
           loadComments(data);
+          // Threads whose markers were mangled by an external edit
+          const candidates = await invoke<AnchorCandidate[]>(
+            "find_anchor_candidates",
+            { path: mdPath, workspace },
+          ).catch(() => []);
+          setAnchorCandidates(candidates);
         } else {
           loadComments({ version: 1, comments: {} });
         }
@@ -27,22 +37,24 @@ export function useComments() {
         loadComments({ version: 1, comments: {} });
       }
     },
-    [loadComments, setFilePath],
+    [loadComments, setFilePath, setAnchorCandidates],
   );
 
   const saveComments = useCallback(async () => {
     if (!editorFilePath) return;
     const data = getCommentsFile();
-    const hasComments = Object.keys(data.comments).length > 0;
+    const hasComments = hasEntries(data);
+    const workspace = useWorkspaceStore.getState().workspacePath;
 
     if (hasComments) {
       const json = JSON.stringify(data, null, 2);
       await invoke("write_comments", {
         path: editorFilePath,
         content: json,
+        workspace,
       });
     } else {
-      await invoke("delete_comments", { path: editorFilePath });
+      await invoke("delete_comments", { path: editorFilePath, workspace });
     }
   }, [editorFilePath, getCommentsFile]);
 
@@ -50,10 +62,11 @@ export function useComments() {
     async (markdownContent: string) => {
       if (!editorFilePath) return;
       const data = getCommentsFile();
-      const hasComments = Object.keys(data.comments).length > 0;
+      const hasComments = hasEntries(data);
+      const workspace = useWorkspaceStore.getState().workspacePath;
 
       if (!hasComments) {
-        await invoke("delete_companion", { path: editorFilePath });
+        await invoke("delete_companion", { path: editorFilePath, workspace });
         return;
       }
 
@@ -65,6 +78,7 @@ export function useComments() {
       await invoke("write_companion", {
         path: editorFilePath,
         content: companion,
+        workspace,
       });
     },
     [editorFilePath, getCommentsFile],
@@ -73,7 +87,16 @@ export function useComments() {
   return { loadCommentsFromFile, saveComments, generateCompanion };
 }
 
+function hasEntries(data: CommentsFile): boolean {
+  return (
+    Object.keys(data.comments).length > 0 ||
+    Object.keys(data.suggestions ?? {}).length > 0
+  );
+}
+
 const MARKER_RE = /<mark>([\s\S]*?)<\/mark><sup>\[c(\d+)\]<\/sup>/g;
+const SUGGESTION_RE =
+  /(?:<del>([\s\S]*?)<\/del>)?(?:<ins>([\s\S]*?)<\/ins>)?<sup>\[s(\d+)\]<\/sup>/g;
 
 export function buildCompanionMarkdown(
   filePath: string,
@@ -144,8 +167,11 @@ export function buildCompanionMarkdown(
 
     if (thread.resolved) {
       resolvedCount++;
+      // Threads imported from Word carry no resolver
       lines.push(
-        `✅ *Resolved by ${thread.resolvedBy} — ${thread.resolvedAt ? formatDate(thread.resolvedAt) : ""}*`,
+        thread.resolvedBy
+          ? `✅ *Resolved by ${thread.resolvedBy} — ${thread.resolvedAt ? formatDate(thread.resolvedAt) : ""}*`
+          : "✅ *Resolved*",
       );
     } else {
       openCount++;
@@ -156,11 +182,51 @@ export function buildCompanionMarkdown(
     lines.push("---");
   }
 
+  // Pending suggestions in document order, then any whose marker is gone
+  const suggestions = commentsFile.suggestions ?? {};
+  const suggestionIds: string[] = [];
+  const sre = new RegExp(SUGGESTION_RE.source, "g");
+  while ((match = sre.exec(markdownContent)) !== null) {
+    const id = `s${match[3]}`;
+    if (suggestions[id] && !suggestionIds.includes(id)) suggestionIds.push(id);
+  }
+  for (const id of Object.keys(suggestions)) {
+    if (!suggestionIds.includes(id)) suggestionIds.push(id);
+  }
+
+  if (suggestionIds.length > 0) {
+    lines.push("");
+    lines.push("## Suggestions");
+
+    const clip = (text: string) =>
+      text.length > 80 ? text.slice(0, 80) + "..." : text;
+
+    for (const id of suggestionIds) {
+      const s = suggestions[id];
+      let change: string;
+      if (s.kind === "insert") {
+        change = `insert "${clip(s.insertedText ?? "")}"`;
+      } else if (s.kind === "delete") {
+        change = `delete "${clip(s.deletedText ?? "")}"`;
+      } else {
+        change = `replace "${clip(s.deletedText ?? "")}" with "${clip(s.insertedText ?? "")}"`;
+      }
+      lines.push("");
+      lines.push(`> **[${id}]** ${change}`);
+      lines.push("");
+      lines.push(`**${s.author}** — ${formatDate(s.timestamp)}`);
+      lines.push("");
+      lines.push("---");
+    }
+  }
+
   const total = resolvedCount + openCount;
   lines.push("");
-  lines.push(
-    `*${total} comment${total !== 1 ? "s" : ""} (${resolvedCount} resolved, ${openCount} open)*`,
-  );
+  let summary = `${total} comment${total !== 1 ? "s" : ""} (${resolvedCount} resolved, ${openCount} open)`;
+  if (suggestionIds.length > 0) {
+    summary += `, ${suggestionIds.length} suggestion${suggestionIds.length !== 1 ? "s" : ""}`;
+  }
+  lines.push(`*${summary}*`);
   lines.push("");
 
   return lines.join("\n");
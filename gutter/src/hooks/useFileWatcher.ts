@@ -1,13 +1,13 @@
 import { useCallback, useEffect, useState } from "react";
 import { invoke } from "@tauri-apps/api/core";
-import { listen } from "@tauri-apps/api/event";
+import { getCurrentWindow } from "@tauri-apps/api/window";
 import { useEditorStore } from "../stores/editorStore";
-import { useWorkspaceStore } from "../stores/workspaceStore";
+import { useWorkspaceStore, type TreeDiff } from "../stores/workspaceStore";
 import { hashContent } from "../utils/hash";
 
 /**
  * Manages the file system watcher: starts/stops with workspace, listens for
- * tree-changed and file-changed events, and exposes a reload prompt.
+ * tree-diff, tree-changed and file-changed events, and exposes a reload prompt.
  */
 export function useFileWatcher(
   markdownRef: React.MutableRefObject<string>,
@@ -15,6 +15,7 @@ export function useFileWatcher(
 ) {
   const [showReloadPrompt, setShowReloadPrompt] = useState(false);
   const workspacePath = useWorkspaceStore((s) => s.workspacePath);
+  const roots = useWorkspaceStore((s) => s.roots);
   const loadFileTree = useWorkspaceStore((s) => s.loadFileTree);
   const filePath = useEditorStore((s) => s.filePath);
 
@@ -23,10 +24,22 @@ export function useFileWatcher(
 
   useEffect(() => {
     if (!watchPath) return;
-    invoke("start_watcher", { path: watchPath }).catch(console.error);
+    invoke("start_watcher", {
+      path: watchPath,
+      roots: roots?.map((r) => r.path),
+      options: useWorkspaceStore.getState().listOptions,
+    }).catch(console.error);
+    // The watcher reports to this window only
+    const win = getCurrentWindow();
+
+    // Structural changes patch the tree in place
+    const unlistenDiff = win.listen<TreeDiff>("tree-diff", (event) => {
+      useWorkspaceStore.getState().applyTreeDiff(event.payload);
+    });
 
+    // Only sent when the watcher lost track; re-read everything
     let debounceTimer: ReturnType<typeof setTimeout>;
-    const unlistenTree = listen<string>("tree-changed", () => {
+    const unlistenTree = win.listen<string>("tree-changed", () => {
       clearTimeout(debounceTimer);
       debounceTimer = setTimeout(() => {
         if (workspacePath) loadFileTree(workspacePath);
@@ -35,7 +48,7 @@ export function useFileWatcher(
 
     const fileChangeDebounces = new Map<string, ReturnType<typeof setTimeout>>();
 
-    const unlistenFile = listen<string>("file-changed", (event) => {
+    const unlistenFile = win.listen<string>("file-changed", (event) => {
       const changedPath = event.payload;
       const { openTabs } = useWorkspaceStore.getState();
 
@@ -90,13 +103,14 @@ export function useFileWatcher(
 
     return () => {
       invoke("stop_watcher").catch(console.error);
+      unlistenDiff.then((fn) => fn());
       unlistenTree.then((fn) => fn());
       unlistenFile.then((fn) => fn());
       clearTimeout(debounceTimer);
       fileChangeDebounces.forEach((t) => clearTimeout(t));
       fileChangeDebounces.clear();
     };
-  }, [watchPath, workspacePath, loadFileTree, markdownRef, lastSaveTimeRef]);
+  }, [watchPath, workspacePath, roots, loadFileTree, markdownRef, lastSaveTimeRef]);
 
   const reloadFromDisk = useCallback(async () => {
     const path = useEditorStore.getState().filePath;
@@ -1,6 +1,7 @@
 import { useEffect } from "react";
 import { useEditorStore } from "../stores/editorStore";
-import { modKey } from "../utils/platform";
+import { useKeybindingStore } from "../stores/keybindingStore";
+import { matchesShortcut } from "../utils/keybindings";
 
 export interface KeyboardShortcutActions {
   handleNewFile: () => void;
@@ -12,6 +13,7 @@ export interface KeyboardShortcutActions {
   toggleComments: () => void;
   toggleHistory: () => void;
   toggleTags: () => void;
+  toggleOutline: () => void;
   toggleReadingMode: () => void;
   cycleTheme: () => void;
   navigateComment: (direction: "next" | "prev") => void;
@@ -22,95 +24,85 @@ export interface KeyboardShortcutActions {
   createComment: () => void;
 }
 
+/** What each command id does; `when` can turn a shortcut down so the key
+ * reaches the editor instead */
+function shortcutHandlers(actions: KeyboardShortcutActions): Record<string, { run: () => void; when?: () => boolean }> {
+  return {
+    new_file: { run: actions.handleNewFile },
+    open: { run: actions.handleOpenFile },
+    save: { run: actions.handleSave },
+    toggle_source: {
+      run: () => {
+        if (useEditorStore.getState().isSourceMode) {
+          actions.switchToWysiwyg();
+        } else {
+          actions.switchToSource();
+        }
+      },
+    },
+    toggle_tree: { run: actions.toggleFileTree },
+    toggle_comments: { run: actions.toggleComments },
+    toggle_history: { run: actions.toggleHistory },
+    toggle_tags: { run: actions.toggleTags },
+    toggle_outline: { run: actions.toggleOutline },
+    cycle_theme: { run: actions.cycleTheme },
+    search: {
+      run: () => actions.setUnifiedSearchMode("all"),
+      // Don't hijack Cmd+K when focus is inside the ProseMirror editor (it inserts a link)
+      when: () => !document.activeElement?.closest(".ProseMirror"),
+    },
+    quick_open: { run: () => actions.setUnifiedSearchMode("files") },
+    command_palette: { run: () => actions.setUnifiedSearchMode("commands") },
+    new_comment: { run: actions.createComment },
+    next_comment: { run: () => actions.navigateComment("next") },
+    prev_comment: { run: () => actions.navigateComment("prev") },
+    export: { run: () => actions.setShowExport(true) },
+    preferences: { run: () => actions.setShowPreferences(true) },
+    toggle_reading: {
+      run: () => {
+        const state = useEditorStore.getState();
+        if (state.isSourceMode && !state.isReadingMode) actions.switchToWysiwyg();
+        actions.toggleReadingMode();
+      },
+    },
+    find: { run: () => actions.setFindReplaceMode("find") },
+    replace: { run: () => actions.setFindReplaceMode("replace") },
+  };
+}
+
 /**
- * Global keyboard shortcut handler with modal guard.
+ * Global keyboard shortcut handler with modal guard. Shortcuts come from
+ * the keybindings in effect, so they follow the user's `keybindings`.
  */
 export function useKeyboardShortcuts(
   actions: KeyboardShortcutActions,
   modalOpen: boolean,
 ) {
+  const commands = useKeybindingStore((s) => s.commands);
+
   useEffect(() => {
+    const handlers = shortcutHandlers(actions);
+
     const handleKeyDown = (e: KeyboardEvent) => {
-      // Skip all shortcuts (except Escape) when a modal/dialog is open
-      if (e.key !== "Escape" && modalOpen) {
+      if (e.key === "Escape") {
+        if (useEditorStore.getState().isReadingMode) {
+          e.preventDefault();
+          actions.toggleReadingMode();
+        }
         return;
       }
+      // Skip all shortcuts (except Escape) when a modal/dialog is open
+      if (modalOpen) return;
 
-      if (modKey(e) && !e.shiftKey && e.key === "n") {
-        e.preventDefault();
-        actions.handleNewFile();
-      } else if (modKey(e) && e.key === "o") {
-        e.preventDefault();
-        actions.handleOpenFile();
-      } else if (modKey(e) && e.key === "s") {
-        e.preventDefault();
-        actions.handleSave();
-      } else if (modKey(e) && e.key === "/") {
-        e.preventDefault();
-        if (useEditorStore.getState().isSourceMode) {
-          actions.switchToWysiwyg();
-        } else {
-          actions.switchToSource();
-        }
-      } else if (modKey(e) && e.key === "\\") {
-        e.preventDefault();
-        actions.toggleFileTree();
-      } else if (modKey(e) && e.shiftKey && e.key === "C") {
-        e.preventDefault();
-        actions.toggleComments();
-      } else if (modKey(e) && e.shiftKey && e.key === "H") {
-        e.preventDefault();
-        actions.toggleHistory();
-      } else if (modKey(e) && e.shiftKey && e.key === "T") {
-        e.preventDefault();
-        actions.toggleTags();
-      } else if (modKey(e) && e.shiftKey && e.key === "D") {
-        e.preventDefault();
-        actions.cycleTheme();
-      } else if (modKey(e) && !e.shiftKey && e.key === "k") {
-        // Don't hijack Cmd+K when focus is inside the ProseMirror editor (it inserts a link)
-        if (document.activeElement?.closest(".ProseMirror")) return;
-        e.preventDefault();
-        actions.setUnifiedSearchMode("all");
-      } else if (modKey(e) && !e.shiftKey && e.key === "p") {
-        e.preventDefault();
-        actions.setUnifiedSearchMode("files");
-      } else if (modKey(e) && e.shiftKey && e.key === "P") {
-        e.preventDefault();
-        actions.setUnifiedSearchMode("commands");
-      } else if (modKey(e) && e.key === ".") {
-        e.preventDefault();
-        actions.setUnifiedSearchMode("commands");
-      } else if (modKey(e) && e.shiftKey && e.key === "M") {
-        e.preventDefault();
-        actions.createComment();
-      } else if (modKey(e) && e.shiftKey && e.key === "N") {
-        e.preventDefault();
-        actions.navigateComment("next");
-      } else if (modKey(e) && e.shiftKey && e.key === "E") {
-        e.preventDefault();
-        actions.setShowExport(true);
-      } else if (modKey(e) && e.key === ",") {
-        e.preventDefault();
-        actions.setShowPreferences(true);
-      } else if (modKey(e) && e.shiftKey && e.key === "R") {
-        e.preventDefault();
-        const state = useEditorStore.getState();
-        if (state.isSourceMode && !state.isReadingMode) actions.switchToWysiwyg();
-        actions.toggleReadingMode();
-      } else if (e.key === "Escape" && useEditorStore.getState().isReadingMode) {
-        e.preventDefault();
-        actions.toggleReadingMode();
-      } else if (modKey(e) && e.key === "f" && !e.shiftKey) {
-        e.preventDefault();
-        actions.setFindReplaceMode("find");
-      } else if (modKey(e) && e.key === "h" && !e.shiftKey) {
-        e.preventDefault();
-        actions.setFindReplaceMode("replace");
-      }
+      const command = commands.find((c) => c.shortcut && handlers[c.id] && matchesShortcut(e, c.shortcut));
+      if (!command) return;
+      const handler = handlers[command.id];
+      if (handler.when && !handler.when()) return;
+      e.preventDefault();
+      handler.run();
     };
 
     window.addEventListener("keydown", handleKeyDown);
     return () => window.removeEventListener("keydown", handleKeyDown);
-  }, [actions, modalOpen]);
+  }, [actions, modalOpen, commands]);
 }
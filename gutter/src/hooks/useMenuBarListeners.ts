@@ -1,14 +1,16 @@
 import { useEffect } from "react";
-import { listen } from "@tauri-apps/api/event";
-import { open } from "@tauri-apps/plugin-dialog";
+import { getCurrentWindow } from "@tauri-apps/api/window";
+import { open, save } from "@tauri-apps/plugin-dialog";
 import { useEditorStore } from "../stores/editorStore";
 import { useWorkspaceStore } from "../stores/workspaceStore";
+import type { RecentEntry } from "../stores/recentStore";
 import { parentDir } from "../utils/path";
 
 export interface MenuBarActions {
   handleNewFile: () => void;
   handleOpenFile: () => void;
   handleSave: () => void;
+  handleOpenRecent: (entry: RecentEntry) => void;
   switchToSource: () => void;
   switchToWysiwyg: () => void;
   toggleFileTree: () => void;
@@ -35,45 +37,72 @@ export function useMenuBarListeners(actions: MenuBarActions) {
   const loadFileTree = useWorkspaceStore((s) => s.loadFileTree);
 
   useEffect(() => {
+    // Menu events go to the focused window only
+    const win = getCurrentWindow();
     const unlisteners = [
-      listen("menu:new-file", () => actions.handleNewFile()),
-      listen("menu:open", () => actions.handleOpenFile()),
-      listen("menu:open-folder", async () => {
+      win.listen("menu:new-file", () => actions.handleNewFile()),
+      win.listen("menu:open", () => actions.handleOpenFile()),
+      win.listen("menu:open-folder", async () => {
         const selected = await open({ directory: true });
         if (selected) {
           const path = typeof selected === "string" ? selected : (selected as { path: string }).path;
           await loadFileTree(path);
         }
       }),
-      listen("menu:save", () => actions.handleSave()),
-      listen("menu:export", () => actions.setShowExport(true)),
-      listen("menu:preferences", () => actions.setShowPreferences(true)),
-      listen("menu:toggle-tree", () => actions.toggleFileTree()),
-      listen("menu:toggle-comments", () => actions.toggleComments()),
-      listen("menu:toggle-history", () => actions.toggleHistory()),
-      listen("menu:toggle-tags", () => actions.toggleTags()),
-      listen("menu:toggle-outline", () => actions.toggleOutline()),
-      listen("menu:toggle-source", () => {
+      win.listen("menu:open-workspace", async () => {
+        const selected = await open({
+          filters: [{ name: "Gutter Workspace", extensions: ["gutter-workspace"] }],
+        });
+        if (selected) {
+          const path = typeof selected === "string" ? selected : (selected as { path: string }).path;
+          await useWorkspaceStore.getState().openWorkspaceFile(path);
+        }
+      }),
+      win.listen<RecentEntry>("menu:open-recent", (event) => actions.handleOpenRecent(event.payload)),
+      win.listen("menu:add-folder-to-workspace", async () => {
+        const selected = await open({ directory: true });
+        if (!selected) return;
+        const folder = typeof selected === "string" ? selected : (selected as { path: string }).path;
+        // Without an open workspace file, the current folder and the new
+        // one go into a new file
+        let file = useWorkspaceStore.getState().workspaceFile;
+        if (!file) {
+          file = await save({
+            defaultPath: "workspace.gutter-workspace",
+            filters: [{ name: "Gutter Workspace", extensions: ["gutter-workspace"] }],
+          });
+        }
+        if (file) await useWorkspaceStore.getState().addWorkspaceRoot(file, folder);
+      }),
+      win.listen("menu:save", () => actions.handleSave()),
+      win.listen("menu:export", () => actions.setShowExport(true)),
+      win.listen("menu:preferences", () => actions.setShowPreferences(true)),
+      win.listen("menu:toggle-tree", () => actions.toggleFileTree()),
+      win.listen("menu:toggle-comments", () => actions.toggleComments()),
+      win.listen("menu:toggle-history", () => actions.toggleHistory()),
+      win.listen("menu:toggle-tags", () => actions.toggleTags()),
+      win.listen("menu:toggle-outline", () => actions.toggleOutline()),
+      win.listen("menu:toggle-source", () => {
         if (useEditorStore.getState().isSourceMode) {
           actions.switchToWysiwyg();
         } else {
           actions.switchToSource();
         }
       }),
-      listen("menu:toggle-reading", () => {
+      win.listen("menu:toggle-reading", () => {
         const state = useEditorStore.getState();
         if (state.isSourceMode && !state.isReadingMode) actions.switchToWysiwyg();
         actions.toggleReadingMode();
       }),
-      listen("menu:cycle-theme", () => actions.cycleTheme()),
-      listen("menu:search", () => actions.setUnifiedSearchMode("all")),
-      listen("menu:quick-open", () => actions.setUnifiedSearchMode("files")),
-      listen("menu:find", () => actions.setFindReplaceMode("find")),
-      listen("menu:replace", () => actions.setFindReplaceMode("replace")),
-      listen("menu:new-comment", () => actions.createComment()),
-      listen("menu:next-comment", () => actions.navigateComment("next")),
-      listen("menu:prev-comment", () => actions.navigateComment("prev")),
-      listen("menu:new-from-template", async () => {
+      win.listen("menu:cycle-theme", () => actions.cycleTheme()),
+      win.listen("menu:search", () => actions.setUnifiedSearchMode("all")),
+      win.listen("menu:quick-open", () => actions.setUnifiedSearchMode("files")),
+      win.listen("menu:find", () => actions.setFindReplaceMode("find")),
+      win.listen("menu:replace", () => actions.setFindReplaceMode("replace")),
+      win.listen("menu:new-comment", () => actions.createComment()),
+      win.listen("menu:next-comment", () => actions.navigateComment("next")),
+      win.listen("menu:prev-comment", () => actions.navigateComment("prev")),
+      win.listen("menu:new-from-template", async () => {
         const currentPath = useEditorStore.getState().filePath;
         const ws = useWorkspaceStore.getState().workspacePath;
         let folder = currentPath ? parentDir(currentPath) : ws;
@@ -84,7 +113,7 @@ export function useMenuBarListeners(actions: MenuBarActions) {
         }
         actions.setTemplatePicker({ mode: "new", targetFolder: folder });
       }),
-      listen("menu:save-as-template", () => {
+      win.listen("menu:save-as-template", () => {
         if (!actions.getMarkdown()) return;
         const currentPath = useEditorStore.getState().filePath;
         const ws = useWorkspaceStore.getState().workspacePath;
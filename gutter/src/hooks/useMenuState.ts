@@ -0,0 +1,94 @@
+import { useEffect } from "react";
+import { invoke } from "@tauri-apps/api/core";
+import { getCurrentWindow } from "@tauri-apps/api/window";
+import { useEditorStore } from "../stores/editorStore";
+import { useWorkspaceStore } from "../stores/workspaceStore";
+import { useSettingsStore } from "../stores/settingsStore";
+import { useKeybindingStore } from "../stores/keybindingStore";
+import { useToastStore } from "../stores/toastStore";
+import { formatShortcut } from "../utils/keybindings";
+
+/** Menu items that act on the open document */
+const DOCUMENT_ITEMS = [
+  "save", "export", "save_as_template", "toggle_source", "toggle_reading", "find", "replace",
+];
+
+/** Menu items that only work in the rich-text editor */
+const WYSIWYG_ITEMS = ["new_comment", "next_comment", "prev_comment"];
+
+interface MenuItemState {
+  id: string;
+  enabled?: boolean;
+  checked?: boolean;
+}
+
+/**
+ * Keeps the native menu in step with the focused window: its keybindings,
+ * items that need a document greyed out without one, and View toggles
+ * checked to match the panels.
+ */
+export function useMenuState() {
+  const loaded = useSettingsStore((s) => s.loaded);
+  const keybindings = useSettingsStore((s) => s.keybindings);
+  const applyKeybindings = useKeybindingStore((s) => s.applyKeybindings);
+  const activeTabPath = useWorkspaceStore((s) => s.activeTabPath);
+  const isSourceMode = useEditorStore((s) => s.isSourceMode);
+  const isReadingMode = useEditorStore((s) => s.isReadingMode);
+  const showFileTree = useEditorStore((s) => s.showFileTree);
+  const showComments = useEditorStore((s) => s.showComments);
+  const showHistory = useEditorStore((s) => s.showHistory);
+  const showTags = useEditorStore((s) => s.showTags);
+  const showOutline = useEditorStore((s) => s.showOutline);
+
+  // Apply keybindings once settings are in, and whenever they change
+  useEffect(() => {
+    if (!loaded) return;
+    applyKeybindings(keybindings).then(() => {
+      const { commands, conflicts } = useKeybindingStore.getState();
+      if (conflicts.length === 0) return;
+      const label = (id: string) => commands.find((c) => c.id === id)?.label ?? id;
+      const detail = conflicts
+        .map((c) => {
+          const outcome = c.kept ? `kept by ${label(c.kept)}` : "left unbound";
+          return `${formatShortcut(c.shortcut)} (${c.commands.map(label).join(", ")}) ${outcome}`;
+        })
+        .join("; ");
+      useToastStore.getState().addToast(`Conflicting shortcuts — ${detail}`, "error", 8000);
+    });
+  }, [loaded, keybindings, applyKeybindings]);
+
+  useEffect(() => {
+    const hasDocument = activeTabPath !== null;
+    const items: MenuItemState[] = [
+      ...DOCUMENT_ITEMS.map((id) => ({ id, enabled: hasDocument })),
+      ...WYSIWYG_ITEMS.map((id) => ({ id, enabled: hasDocument && !isSourceMode && !isReadingMode })),
+      { id: "toggle_tree", checked: showFileTree },
+      { id: "toggle_comments", checked: showComments },
+      { id: "toggle_history", checked: showHistory },
+      { id: "toggle_tags", checked: showTags },
+      { id: "toggle_outline", checked: showOutline },
+      { id: "toggle_source", checked: isSourceMode },
+      { id: "toggle_reading", checked: isReadingMode },
+    ];
+    const push = () =>
+      invoke("set_menu_state", { items }).catch((e) => console.error("Failed to update menu:", e));
+
+    // Every window shares the one menu, so take it over on focus
+    const win = getCurrentWindow();
+    if (document.hasFocus()) push();
+    const unlisten = win.onFocusChanged(({ payload: focused }) => {
+      if (!focused) return;
+      // Workspaces can bind keys differently, so this window's keybindings
+      // go back on the menu along with its state
+      const settings = useSettingsStore.getState();
+      if (settings.loaded) useKeybindingStore.getState().applyKeybindings(settings.keybindings);
+      push();
+    });
+    return () => {
+      unlisten.then((fn) => fn());
+    };
+  }, [
+    activeTabPath, isSourceMode, isReadingMode,
+    showFileTree, showComments, showHistory, showTags, showOutline,
+  ]);
+}
@@ -0,0 +1,135 @@
+import { useCallback, useEffect, useRef, useState } from "react";
+import { invoke } from "@tauri-apps/api/core";
+import { getCurrentWindow } from "@tauri-apps/api/window";
+import { useWorkspaceStore } from "../stores/workspaceStore";
+import { fileName as pathFileName } from "../utils/path";
+
+/** A buffer a crashed run left unsaved (`list_recoverable`) */
+export interface RecoverableBuffer {
+  path: string;
+  content: string;
+  updated: number;
+  onDisk: boolean;
+  /** Set when the file was modified after the buffer was journaled */
+  changedOnDisk: number | null;
+  /** Unified diff from the file on disk to the recovered text */
+  diff: string;
+}
+
+/** How often dirty buffers are streamed to the recovery journal */
+const JOURNAL_INTERVAL = 5000;
+
+/** Untitled buffers are numbered from 1 in each window on each run; tag
+ * them with both so they don't replace one another in the journal */
+const RUN = `${getCurrentWindow().label}-${Date.now()}`;
+
+function journalKey(path: string): string {
+  return path.startsWith("untitled:") ? `${path}@${RUN}` : path;
+}
+
+/**
+ * Streams dirty buffers to the backend's recovery journal, and on launch
+ * offers back whatever an earlier run didn't get to save.
+ */
+export function useRecoveryJournal(
+  markdownRef: React.MutableRefObject<string>,
+  tabContentCache: React.MutableRefObject<Map<string, string>>,
+  handleSwitchTab: (path: string) => Promise<void>,
+) {
+  const [recoverable, setRecoverable] = useState<RecoverableBuffer[]>([]);
+  // Journaling waits until the user has dealt with the last run's buffers,
+  // so it can't overwrite them
+  const [resolved, setResolved] = useState(false);
+  /** Text last journaled, per buffer */
+  const journaled = useRef(new Map<string, string>());
+
+  useEffect(() => {
+    // The journal is shared; only the first window offers what's in it
+    if (getCurrentWindow().label !== "main") {
+      setResolved(true);
+      return;
+    }
+    invoke<RecoverableBuffer[]>("list_recoverable")
+      .then((buffers) => {
+        setRecoverable(buffers);
+        if (buffers.length === 0) setResolved(true);
+      })
+      .catch((e) => {
+        console.error("Failed to check for unsaved buffers:", e);
+        setResolved(true);
+      });
+  }, []);
+
+  useEffect(() => {
+    if (!resolved) return;
+    const journal = () => {
+      const { openTabs, activeTabPath } = useWorkspaceStore.getState();
+      const dirty = new Map<string, string>();
+      for (const tab of openTabs) {
+        if (!tab.isDirty) continue;
+        const content = tab.path === activeTabPath
+          ? markdownRef.current
+          : tabContentCache.current.get(tab.path);
+        if (content !== undefined) dirty.set(journalKey(tab.path), content);
+      }
+      for (const [path, content] of dirty) {
+        if (journaled.current.get(path) === content) continue;
+        journaled.current.set(path, content);
+        invoke("journal_buffer", { path, content }).catch((e) =>
+          console.error("Failed to journal buffer:", e),
+        );
+      }
+      // Saved, closed or renamed since: nothing left to recover
+      for (const path of [...journaled.current.keys()]) {
+        if (dirty.has(path)) continue;
+        journaled.current.delete(path);
+        invoke("discard_buffer", { path }).catch(console.error);
+      }
+    };
+    const timer = setInterval(journal, JOURNAL_INTERVAL);
+    return () => clearInterval(timer);
+  }, [resolved, markdownRef, tabContentCache]);
+
+  const settle = useCallback((buffer: RecoverableBuffer) => {
+    invoke("discard_buffer", { path: buffer.path }).catch(console.error);
+    setRecoverable((list) => {
+      const rest = list.filter((b) => b !== buffer);
+      if (rest.length === 0) setResolved(true);
+      return rest;
+    });
+  }, []);
+
+  /** Open the recovered text in a dirty tab. A file that's gone, or never
+   * had one, comes back as an untitled buffer. */
+  const recoverBuffer = useCallback(
+    async (buffer: RecoverableBuffer) => {
+      const { openTabs, activeTabPath } = useWorkspaceStore.getState();
+      const path = buffer.onDisk ? buffer.path : `untitled:recovered-${buffer.updated}`;
+      const name = buffer.onDisk ? pathFileName(buffer.path) || "Untitled" : "Recovered";
+      tabContentCache.current.set(path, buffer.content);
+      // Switching away from the tab stashes the editor's text; make that
+      // the recovered text too
+      if (activeTabPath === path) markdownRef.current = buffer.content;
+      const tabs = openTabs.filter((t) => t.path !== path);
+      useWorkspaceStore.setState({
+        openTabs: [
+          ...tabs,
+          { path, name, isDirty: true, isPinned: false, diskHash: null, externallyModified: false },
+        ],
+      });
+      await handleSwitchTab(path);
+      settle(buffer);
+    },
+    [handleSwitchTab, settle, tabContentCache, markdownRef],
+  );
+
+  const discardRecovery = useCallback((buffer: RecoverableBuffer) => settle(buffer), [settle]);
+
+  /** Close the prompt; what's left is offered again next launch */
+  const dismissRecovery = useCallback(() => {
+    setRecoverable([]);
+    setResolved(true);
+  }, []);
+
+  return { recoverable, recoverBuffer, discardRecovery, dismissRecovery };
+}
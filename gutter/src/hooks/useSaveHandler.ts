@@ -6,7 +6,7 @@ import { useWorkspaceStore } from "../stores/workspaceStore";
 import { hashContent } from "../utils/hash";
 import { useTagStore } from "../stores/tagStore";
 import { useToastStore } from "../stores/toastStore";
-import { useSettingsStore } from "../stores/settingsStore";
+import { useRecentStore } from "../stores/recentStore";
 import { useFileOps } from "./useFileOps";
 import { useComments } from "./useComments";
 import { fileName as pathFileName } from "../utils/path";
@@ -26,7 +26,7 @@ export function useSaveHandler(
   const setTabDirty = useWorkspaceStore((s) => s.setTabDirty);
   const updateTabPath = useWorkspaceStore((s) => s.updateTabPath);
   const loadFileTree = useWorkspaceStore((s) => s.loadFileTree);
-  const addRecentFile = useSettingsStore((s) => s.addRecentFile);
+  const addRecent = useRecentStore((s) => s.addRecent);
 
   const handleSave = useCallback(async () => {
     const md = markdownRef.current;
@@ -77,7 +77,7 @@ export function useSaveHandler(
       // Move cached content to new path key
       tabContentCache.current.delete(activeTab);
       tabContentCache.current.set(path, md);
-      addRecentFile(path);
+      addRecent(path, "file");
       const ws = useWorkspaceStore.getState().workspacePath;
       if (ws) await loadFileTree(ws);
     }
@@ -96,7 +96,7 @@ export function useSaveHandler(
       // Incrementally update tag index
       useTagStore.getState().updateFileTags(path, md);
     }
-  }, [saveFile, saveComments, generateCompanion, setTabDirty, updateTabPath, addRecentFile, loadFileTree, markdownRef, lastSaveTimeRef, tabContentCache]);
+  }, [saveFile, saveComments, generateCompanion, setTabDirty, updateTabPath, addRecent, loadFileTree, markdownRef, lastSaveTimeRef, tabContentCache]);
 
   const handleHistoryRestore = useCallback((content: string) => {
     markdownRef.current = content;
@@ -0,0 +1,208 @@
+import { useCallback, useEffect, useRef } from "react";
+import { invoke } from "@tauri-apps/api/core";
+import type { Editor } from "@tiptap/react";
+import { useEditorStore } from "../stores/editorStore";
+import { useWorkspaceStore, type OpenTab } from "../stores/workspaceStore";
+import { fileName as pathFileName } from "../utils/path";
+
+/** Where the user was in a tab */
+interface ViewState {
+  cursor?: number;
+  scroll?: number;
+}
+
+interface SessionTab extends ViewState {
+  path: string;
+  pinned?: boolean;
+}
+
+/** Mirrors the backend `Session` */
+interface Session {
+  workspace: string;
+  tabs: SessionTab[];
+  activeTab: string | null;
+  panels: { fileTree: boolean; comments: boolean; outline: boolean; history: boolean; tags: boolean };
+  sourceMode: boolean;
+}
+
+/** How often the session is sent to the backend, which writes it out */
+const SESSION_INTERVAL = 2000;
+
+function scrollContainer(editor: Editor): HTMLElement | null {
+  return editor.view.dom.closest(".overflow-auto");
+}
+
+/**
+ * Saves open tabs, the active tab, cursor and scroll positions and panel
+ * layout per workspace, and restores them when the workspace is reopened.
+ * Returns a function that sends the session right away, for the close
+ * guard.
+ */
+export function useSessionPersistence(
+  editorInstanceRef: React.MutableRefObject<{ getEditor: () => Editor | null } | null>,
+  handleFileTreeOpen: (path: string) => Promise<void>,
+) {
+  const workspacePath = useWorkspaceStore((s) => s.workspacePath);
+  const workspaceFile = useWorkspaceStore((s) => s.workspaceFile);
+  const workspace = workspaceFile ?? workspacePath;
+  const views = useRef(new Map<string, ViewState>());
+  const restoring = useRef(false);
+
+  const captureView = useCallback(
+    (path: string) => {
+      const editor = editorInstanceRef.current?.getEditor();
+      if (!editor || editor.isDestroyed) return;
+      views.current.set(path, {
+        cursor: editor.state.selection.anchor,
+        scroll: scrollContainer(editor)?.scrollTop,
+      });
+    },
+    [editorInstanceRef],
+  );
+
+  const buildSession = useCallback(
+    (workspace: string): Session => {
+      const { openTabs, activeTabPath } = useWorkspaceStore.getState();
+      if (activeTabPath) captureView(activeTabPath);
+      const editor = useEditorStore.getState();
+      const saved = (path: string | null) => path !== null && !path.startsWith("untitled:");
+      return {
+        workspace,
+        tabs: openTabs
+          .filter((t) => saved(t.path))
+          .map((t) => ({ path: t.path, pinned: t.isPinned, ...views.current.get(t.path) })),
+        activeTab: saved(activeTabPath) ? activeTabPath : null,
+        panels: {
+          fileTree: editor.showFileTree,
+          comments: editor.showComments,
+          outline: editor.showOutline,
+          history: editor.showHistory,
+          tags: editor.showTags,
+        },
+        sourceMode: editor.isSourceMode,
+      };
+    },
+    [captureView],
+  );
+
+  const pushSession = useCallback(
+    async (workspace: string) => {
+      if (restoring.current) return;
+      try {
+        await invoke("update_session", { session: buildSession(workspace) });
+      } catch (e) {
+        console.error("Failed to save session:", e);
+      }
+    },
+    [buildSession],
+  );
+
+  const saveSession = useCallback(async () => {
+    if (workspace) await pushSession(workspace);
+  }, [workspace, pushSession]);
+
+  // The restore effect runs once per workspace, so it reads these through refs
+  const pushRef = useRef(pushSession);
+  pushRef.current = pushSession;
+  const openRef = useRef(handleFileTreeOpen);
+  openRef.current = handleFileTreeOpen;
+
+  // Remember where the user was in the tab being left
+  useEffect(() => {
+    return useWorkspaceStore.subscribe((state, prev) => {
+      if (prev.activeTabPath && state.activeTabPath !== prev.activeTabPath) {
+        captureView(prev.activeTabPath);
+      }
+    });
+  }, [captureView]);
+
+  // Put the cursor and scroll back once a tab's editor is mounted
+  useEffect(() => {
+    return useEditorStore.subscribe((state, prev) => {
+      if (state.contentVersion === prev.contentVersion) return;
+      const path = useWorkspaceStore.getState().activeTabPath;
+      const view = path ? views.current.get(path) : undefined;
+      if (!view) return;
+      let frames = 0;
+      const apply = () => {
+        const editor = editorInstanceRef.current?.getEditor();
+        if (!editor || editor.isDestroyed) {
+          if (++frames < 20) requestAnimationFrame(apply);
+          return;
+        }
+        if (view.cursor !== undefined) {
+          const pos = Math.min(view.cursor, editor.state.doc.content.size);
+          editor.commands.setTextSelection(pos);
+        }
+        const container = scrollContainer(editor);
+        if (container && view.scroll !== undefined) container.scrollTop = view.scroll;
+      };
+      requestAnimationFrame(apply);
+    });
+  }, [editorInstanceRef]);
+
+  // Restore the workspace's last session, then keep it up to date
+  useEffect(() => {
+    if (!workspace) return;
+    let cancelled = false;
+    // Nothing is saved until the old session is back, so it can't be
+    // overwritten by the empty one
+    restoring.current = true;
+
+    const restore = async (session: Session) => {
+      try {
+        const { openTabs, activeTabPath } = useWorkspaceStore.getState();
+        const added: OpenTab[] = [];
+        for (const tab of session.tabs) {
+          if (openTabs.some((t) => t.path === tab.path)) continue;
+          if (!(await invoke<boolean>("file_exists", { path: tab.path }))) continue;
+          views.current.set(tab.path, { cursor: tab.cursor, scroll: tab.scroll });
+          added.push({
+            path: tab.path,
+            name: pathFileName(tab.path) || "Untitled",
+            isDirty: false,
+            isPinned: !!tab.pinned,
+            diskHash: null,
+            externallyModified: false,
+          });
+        }
+        if (cancelled) return;
+        const tabs = [...openTabs, ...added];
+        tabs.sort((a, b) => (a.isPinned === b.isPinned ? 0 : a.isPinned ? -1 : 1));
+        useWorkspaceStore.setState({ openTabs: tabs });
+        useEditorStore.setState({
+          showFileTree: session.panels.fileTree,
+          showComments: session.panels.comments,
+          showOutline: session.panels.outline,
+          showHistory: session.panels.history,
+          showTags: session.panels.tags,
+          isSourceMode: session.sourceMode,
+        });
+        // A file opened on launch keeps focus
+        if (!activeTabPath && session.activeTab && added.some((t) => t.path === session.activeTab)) {
+          await openRef.current(session.activeTab);
+        }
+      } catch (e) {
+        console.error("Failed to restore session:", e);
+      }
+    };
+
+    invoke<Session | null>("load_session", { workspace })
+      .then((session) => (session && !cancelled ? restore(session) : undefined))
+      .catch((e) => console.error("Failed to load session:", e))
+      .finally(() => {
+        if (!cancelled) restoring.current = false;
+      });
+
+    const timer = setInterval(() => pushRef.current(workspace), SESSION_INTERVAL);
+    return () => {
+      cancelled = true;
+      clearInterval(timer);
+      // Last state of the workspace being left
+      if (!restoring.current) pushRef.current(workspace);
+      restoring.current = false;
+    };
+  }, [workspace]);
+
+  return saveSession;
+}
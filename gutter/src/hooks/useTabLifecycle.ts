@@ -1,12 +1,12 @@
 import { useCallback, useEffect, useRef, useState } from "react";
 import { invoke } from "@tauri-apps/api/core";
-import { listen } from "@tauri-apps/api/event";
+import { getCurrentWindow } from "@tauri-apps/api/window";
 import { ask } from "@tauri-apps/plugin-dialog";
 import { convertFileSrc } from "@tauri-apps/api/core";
 import { useEditorStore } from "../stores/editorStore";
 import { useWorkspaceStore } from "../stores/workspaceStore";
 import { useCommentStore } from "../stores/commentStore";
-import { useSettingsStore } from "../stores/settingsStore";
+import { useRecentStore, type RecentEntry } from "../stores/recentStore";
 import { useToastStore } from "../stores/toastStore";
 import { useFileOps } from "./useFileOps";
 import { useComments } from "./useComments";
@@ -53,7 +53,7 @@ export function useTabLifecycle(
   const loadFileTree = useWorkspaceStore((s) => s.loadFileTree);
 
   const getThreadIds = useCommentStore((s) => s.getThreadIds);
-  const addRecentFile = useSettingsStore((s) => s.addRecentFile);
+  const addRecent = useRecentStore((s) => s.addRecent);
 
   // Track current markdown for saving
   const handleEditorUpdate = useCallback(
@@ -213,7 +213,7 @@ export function useTabLifecycle(
         markdownRef.current = content;
         const name = pathFileName(path) || "Untitled";
         addTab(path, name);
-        addRecentFile(path);
+        addRecent(path, "file");
         // Set diskHash for read-before-write safety
         useWorkspaceStore.getState().setTabDiskHash(path, hashContent(content));
         // activateTab will set content from cache, so stash it first
@@ -221,7 +221,7 @@ export function useTabLifecycle(
         await activateTab(path);
       }
     }
-  }, [openFile, deactivateCurrentTab, activateTab, addTab, addRecentFile, markdownRef, tabContentCache]);
+  }, [openFile, deactivateCurrentTab, activateTab, addTab, addRecent, markdownRef, tabContentCache]);
 
   // Open specific file (from file tree)
   const handleFileTreeOpen = useCallback(
@@ -230,11 +230,23 @@ export function useTabLifecycle(
       const name = pathFileName(path) || (isImageFile(path) ? "Image" : "Untitled");
       addTab(path, name);
       if (!isImageFile(path)) {
-        addRecentFile(path);
+        addRecent(path, "file");
       }
       await activateTab(path);
     },
-    [deactivateCurrentTab, activateTab, addTab, addRecentFile],
+    [deactivateCurrentTab, activateTab, addTab, addRecent],
+  );
+
+  // Open an entry from Open Recent or the welcome screen
+  const handleOpenRecent = useCallback(
+    async (entry: RecentEntry) => {
+      if (entry.kind === "workspace") {
+        await useWorkspaceStore.getState().openWorkspace(entry.path);
+      } else {
+        await handleFileTreeOpen(entry.path);
+      }
+    },
+    [handleFileTreeOpen],
   );
 
   // New file handler — creates an in-memory untitled buffer, named on save
@@ -260,8 +272,9 @@ export function useTabLifecycle(
       }
     });
 
-    // Listen for files opened while running
-    const unlisten = listen<string>("open-file", (event) => {
+    // Listen for files opened while running; the backend sends each to
+    // the window whose workspace holds it
+    const unlisten = getCurrentWindow().listen<string>("open-file", (event) => {
       handleFileTreeOpen(event.payload);
     });
 
@@ -278,7 +291,7 @@ export function useTabLifecycle(
 
       // Obsidian-style resolution: search workspace tree, shortest path wins
       const { fileTree } = useWorkspaceStore.getState();
-      const found = resolveWikiLink(target, fileTree);
+      const found = resolveWikiLink(target, fileTree, useEditorStore.getState().filePath ?? undefined);
       if (found) {
         handleFileTreeOpen(found);
         return;
@@ -395,6 +408,7 @@ export function useTabLifecycle(
   return {
     handleOpenFile,
     handleFileTreeOpen,
+    handleOpenRecent,
     handleNewFile,
     handleSwitchTab,
     handleCloseTab,
@@ -1,18 +1,20 @@
-import { useEffect } from "react";
+import { useEffect, useRef } from "react";
 import { invoke } from "@tauri-apps/api/core";
 import { ask } from "@tauri-apps/plugin-dialog";
 import { getCurrentWindow } from "@tauri-apps/api/window";
+import { listen } from "@tauri-apps/api/event";
 import { convertFileSrc } from "@tauri-apps/api/core";
 import { useEditorStore } from "../stores/editorStore";
 import { useWorkspaceStore } from "../stores/workspaceStore";
-import { useSettingsStore } from "../stores/settingsStore";
+import { useSettingsStore, type SettingsChanged } from "../stores/settingsStore";
 import { useTagStore } from "../stores/tagStore";
+import { useRecentStore, type RecentEntry } from "../stores/recentStore";
 import { useToastStore } from "../stores/toastStore";
-import { parentDir, joinPath, isImageFile } from "../utils/path";
+import { parentDir, resolveAttachment, isImageFile } from "../utils/path";
 
 /**
- * Window-level lifecycle effects: close guard, drag-drop, settings load,
- * tag scan, and clearing version preview when history panel closes.
+ * Window-level lifecycle effects: close guard, drag-drop, settings load and
+ * live reload, recent list, tag scan, and clearing version preview when history panel closes.
  */
 export function useWindowLifecycle(
   editorInstanceRef: React.MutableRefObject<{
@@ -23,15 +25,47 @@ export function useWindowLifecycle(
   } | null>,
   handleFileTreeOpen: (path: string) => Promise<void>,
   setVersionPreview: (v: { content: string; label: string } | null) => void,
+  saveSession: () => Promise<void>,
 ) {
   const loadSettings = useSettingsStore((s) => s.loadSettings);
   const workspacePath = useWorkspaceStore((s) => s.workspacePath);
+  const workspaceFile = useWorkspaceStore((s) => s.workspaceFile);
   const showHistory = useEditorStore((s) => s.showHistory);
 
-  // Load settings on startup
+  // Load settings on startup, and again with each workspace's overrides
   useEffect(() => {
-    loadSettings();
-  }, [loadSettings]);
+    loadSettings(workspacePath);
+  }, [loadSettings, workspacePath]);
+
+  // Pick up settings edited outside this window: by hand, by another
+  // window or by a dotfiles sync
+  useEffect(() => {
+    invoke("watch_settings", { workspace: workspacePath }).catch(console.error);
+    const unlisten = getCurrentWindow().listen<SettingsChanged>("settings-changed", (event) => {
+      useSettingsStore.getState().applyExternalChange(event.payload);
+    });
+    return () => {
+      unlisten.then((fn) => fn());
+    };
+  }, [workspacePath]);
+
+  // Recently opened list, kept in step with the menu and other windows
+  useEffect(() => {
+    useRecentStore.getState().loadRecent();
+    const unlisten = listen<RecentEntry[]>("recent-changed", (event) => {
+      useRecentStore.getState().setEntries(event.payload);
+    });
+    return () => {
+      unlisten.then((fn) => fn());
+    };
+  }, []);
+
+  // Record each workspace opened, by its workspace file if it has one
+  useEffect(() => {
+    if (workspacePath) {
+      useRecentStore.getState().addRecent(workspaceFile ?? workspacePath, "workspace");
+    }
+  }, [workspacePath, workspaceFile]);
 
   // Scan tags when workspace loads
   useEffect(() => {
@@ -40,9 +74,15 @@ export function useWindowLifecycle(
     }
   }, [workspacePath]);
 
+  // The close guard is registered once; it reads the latest saver
+  const saveSessionRef = useRef(saveSession);
+  saveSessionRef.current = saveSession;
+
   // Prevent closing window with dirty tabs
   useEffect(() => {
     const unlisten = getCurrentWindow().onCloseRequested(async (event) => {
+      // Hand the backend the final session; it's written as the window goes
+      await saveSessionRef.current();
       try {
         const { openTabs: tabs } = useWorkspaceStore.getState();
         const hasDirty = tabs.some((t) => t.isDirty);
@@ -82,12 +122,17 @@ export function useWindowLifecycle(
           const ext = path.split(".").pop() || "png";
           const filename = `image-${Date.now()}.${ext}`;
           try {
-            await invoke("copy_image", { source: path, dirPath, filename });
-            const absolutePath = joinPath(dirPath, "assets", filename);
-            const displaySrc = convertFileSrc(absolutePath);
+            const link = await invoke<string>("copy_image", {
+              source: path,
+              dirPath,
+              filename,
+              workspace: useWorkspaceStore.getState().workspacePath,
+            });
+            const absolutePath = resolveAttachment(dirPath, link);
+            const image = { src: convertFileSrc(absolutePath), originalSrc: link };
             const editor = editorInstanceRef.current?.getEditor();
             if (editor) {
-              editor.chain().focus().setImage({ src: displaySrc }).run();
+              editor.chain().focus().setImage(image).run();
             }
           } catch (e) {
             console.error("Failed to insert dropped image:", e);
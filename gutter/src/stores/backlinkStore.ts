@@ -1,5 +1,6 @@
 import { create } from "zustand";
 import { invoke } from "@tauri-apps/api/core";
+import { useWorkspaceStore } from "./workspaceStore";
 
 interface BacklinkEntry {
   sourcePath: string;
@@ -23,7 +24,7 @@ export const useBacklinkStore = create<BacklinkState>((set) => ({
     try {
       const tree = await invoke<
         { name: string; path: string; is_dir: boolean; children: unknown[] | null }[]
-      >("read_directory", { path: workspacePath });
+      >("read_directory", { path: workspacePath, roots: useWorkspaceStore.getState().roots });
 
       const mdFiles: { name: string; path: string }[] = [];
       const collectFiles = (
@@ -1,5 +1,10 @@
 import { create } from "zustand";
-import type { CommentThread, CommentsFile } from "../types/comments";
+import type {
+  AnchorCandidate,
+  CommentThread,
+  CommentsFile,
+  Suggestion,
+} from "../types/comments";
 
 function generateId(): string {
   return "m_" + Math.random().toString(36).substring(2, 10);
@@ -7,9 +12,13 @@ function generateId(): string {
 
 interface CommentState {
   threads: Record<string, CommentThread>;
+  suggestions: Record<string, Suggestion>;
+  // Where threads whose markers were lost might belong, best first
+  anchorCandidates: AnchorCandidate[];
   filePath: string | null;
 
   setFilePath: (path: string | null) => void;
+  setAnchorCandidates: (candidates: AnchorCandidate[]) => void;
   loadComments: (data: CommentsFile) => void;
   getCommentsFile: () => CommentsFile;
   getThreadIds: () => string[];
@@ -25,19 +34,26 @@ interface CommentState {
 
 export const useCommentStore = create<CommentState>((set, get) => ({
   threads: {},
+  suggestions: {},
+  anchorCandidates: [],
   filePath: null,
 
   setFilePath: (path) => set({ filePath: path }),
 
+  setAnchorCandidates: (candidates) => set({ anchorCandidates: candidates }),
+
   loadComments: (data) => {
-    set({ threads: data.comments || {} });
+    set({
+      threads: data.comments || {},
+      suggestions: data.suggestions || {},
+    });
   },
 
   getCommentsFile: (): CommentsFile => {
-    return {
-      version: 1,
-      comments: get().threads,
-    };
+    const { threads, suggestions } = get();
+    // Always sent, even empty: a missing key tells the backend this editor
+    // doesn't know about suggestions, and it would keep the stored ones
+    return { version: 1, comments: threads, suggestions };
   },
 
   getThreadIds: (): string[] => {
@@ -149,5 +165,6 @@ export const useCommentStore = create<CommentState>((set, get) => ({
     });
   },
 
-  clearAll: () => set({ threads: {}, filePath: null }),
+  clearAll: () =>
+    set({ threads: {}, suggestions: {}, anchorCandidates: [], filePath: null }),
 }));
@@ -0,0 +1,50 @@
+import { create } from "zustand";
+import { invoke } from "@tauri-apps/api/core";
+
+/** A bindable command and the shortcut it ended up with (`set_keybindings`) */
+export interface BoundCommand {
+  id: string;
+  label: string;
+  default: string | null;
+  /** `null` when unbound, by choice or because of a conflict */
+  shortcut: string | null;
+  /** Whether `shortcut` comes from the `keybindings` setting */
+  custom: boolean;
+}
+
+/** Several commands wanting the same keys */
+export interface KeybindingConflict {
+  shortcut: string;
+  commands: string[];
+  /** The one command that keeps the shortcut, if any */
+  kept: string | null;
+}
+
+interface Keybindings {
+  commands: BoundCommand[];
+  conflicts: KeybindingConflict[];
+}
+
+interface KeybindingState extends Keybindings {
+  /** Send the `keybindings` setting to the backend, which updates the menu
+   * and works out every command's shortcut */
+  applyKeybindings: (bindings: Record<string, string>) => Promise<void>;
+}
+
+export const useKeybindingStore = create<KeybindingState>((set) => ({
+  commands: [],
+  conflicts: [],
+
+  applyKeybindings: async (bindings) => {
+    try {
+      set(await invoke<Keybindings>("set_keybindings", { bindings }));
+    } catch (e) {
+      console.error("Failed to apply keybindings:", e);
+    }
+  },
+}));
+
+/** The shortcut `id` has now, if any */
+export function shortcutFor(commands: BoundCommand[], id: string): string | null {
+  return commands.find((c) => c.id === id)?.shortcut ?? null;
+}
@@ -0,0 +1,63 @@
+import { create } from "zustand";
+import { invoke } from "@tauri-apps/api/core";
+
+export type RecentKind = "file" | "workspace";
+
+/** One entry of the backend's recently-opened registry */
+export interface RecentEntry {
+  path: string;
+  /** `workspace` is a folder or a `.gutter-workspace` file */
+  kind: RecentKind;
+  pinned?: boolean;
+  /** Milliseconds since the epoch */
+  opened: number;
+}
+
+interface RecentState {
+  /** Pinned first, then most recently opened first */
+  entries: RecentEntry[];
+
+  loadRecent: () => Promise<void>;
+  /** Replace the list, e.g. from a `recent-changed` event */
+  setEntries: (entries: RecentEntry[]) => void;
+  addRecent: (path: string, kind: RecentKind) => void;
+  pinRecent: (path: string, pinned: boolean) => void;
+  removeRecent: (path: string) => void;
+  clearRecent: (kind?: RecentKind) => void;
+}
+
+export const useRecentStore = create<RecentState>((set) => {
+  // Every change returns the updated list
+  const run = (command: string, args: Record<string, unknown>) => {
+    invoke<RecentEntry[]>(command, args)
+      .then((entries) => set({ entries }))
+      .catch((e) => console.error(`Failed to ${command}:`, e));
+  };
+
+  return {
+    entries: [],
+
+    loadRecent: async () => {
+      try {
+        set({ entries: await invoke<RecentEntry[]>("list_recent", { kind: null }) });
+      } catch (e) {
+        console.error("Failed to load recent list:", e);
+      }
+    },
+
+    setEntries: (entries) => set({ entries }),
+
+    addRecent: (path, kind) => run("add_recent", { path, kind }),
+
+    pinRecent: (path, pinned) => run("pin_recent", { path, pinned }),
+
+    removeRecent: (path) => run("remove_recent", { path }),
+
+    clearRecent: (kind) => run("clear_recent", { kind: kind ?? null }),
+  };
+});
+
+/** Paths of recent entries of `kind`, in list order */
+export function recentPaths(entries: RecentEntry[], kind: RecentKind): string[] {
+  return entries.filter((e) => e.kind === kind).map((e) => e.path);
+}
@@ -1,23 +1,41 @@
 import { create } from "zustand";
 import { invoke } from "@tauri-apps/api/core";
+import { useToastStore } from "./toastStore";
 
-interface Settings {
+export interface Settings {
   theme: "light" | "dark" | "system";
   fontSize: number;
   fontFamily: "serif" | "sans" | "mono";
   autoSaveInterval: number;
   panelWidths: { fileTree: number; comments: number; history: number; tags: number };
-  recentFiles: string[];
   spellCheckEnabled: boolean;
   defaultAuthor: string;
   editorWidth: "narrow" | "medium" | "wide" | "full";
   lineHeight: "compact" | "comfortable" | "spacious";
   accentColor: string;
+  followSymlinks: boolean;
+  /** Command id -> shortcut, over the defaults; "" unbinds */
+  keybindings: Record<string, string>;
 }
 
+/** Panel widths `write_settings` accepts */
+export const PANEL_WIDTH_MIN = 120;
+export const PANEL_WIDTH_MAX = 2000;
+
+/** Which file a setting's value comes from (`settings_sources`) */
+export type SettingsLayer = "global" | "workspace";
+
 interface SettingsState extends Settings {
   loaded: boolean;
-  loadSettings: () => Promise<void>;
+  /** Workspace whose `.gutter/config.json` is layered over the global file */
+  settingsWorkspace: string | null;
+  /** Keys the workspace overrides; changes to them are saved there */
+  workspaceKeys: (keyof Settings)[];
+  /** Values ignored on the last load, shown in Preferences */
+  settingsIssues: SettingsIssue[];
+  loadSettings: (workspace?: string | null) => Promise<void>;
+  /** Take values edited on disk, unless a save of ours is pending */
+  applyExternalChange: (change: SettingsChanged) => void;
   saveSettings: () => Promise<void>;
   setTheme: (theme: "light" | "dark" | "system") => void;
   cycleTheme: () => void;
@@ -25,15 +43,55 @@ interface SettingsState extends Settings {
   setFontFamily: (family: "serif" | "sans" | "mono") => void;
   setAutoSaveInterval: (ms: number) => void;
   setPanelWidth: (panel: "fileTree" | "comments" | "history" | "tags", width: number) => void;
-  addRecentFile: (path: string) => void;
   setSpellCheckEnabled: (enabled: boolean) => void;
   setDefaultAuthor: (author: string) => void;
   setEditorWidth: (width: "narrow" | "medium" | "wide" | "full") => void;
   setLineHeight: (height: "compact" | "comfortable" | "spacious") => void;
   setAccentColor: (color: string) => void;
+  setFollowSymlinks: (follow: boolean) => void;
+  /** Bind a command to `shortcut`; "" unbinds it, `null` restores its default */
+  setKeybinding: (id: string, shortcut: string | null) => void;
 }
 
 let saveTimer: ReturnType<typeof setTimeout> | null = null;
+/** Saves still being written */
+let saving = 0;
+
+/** Global values of the keys a workspace overrides, so saving doesn't
+ * copy workspace values into the global file */
+let globalValues: Partial<Settings> = {};
+
+/** A value the backend ignored in favor of its default */
+export interface SettingsIssue {
+  layer: SettingsLayer;
+  /** Empty when the whole file couldn't be read */
+  key: string;
+  message: string;
+}
+
+/** `read_settings` result; values are already migrated and validated */
+interface LoadedSettings {
+  settings: Partial<Settings>;
+  global: Partial<Settings>;
+  workspaceKeys: string[];
+  issues: SettingsIssue[];
+}
+
+/** Payload of `settings-changed`, sent when a settings file is edited
+ * outside this window */
+export interface SettingsChanged extends LoadedSettings {
+  workspace: string | null;
+}
+
+/** Drop keys the editor doesn't handle; the backend keeps them on save */
+function known(values: Partial<Settings>): Partial<Settings> {
+  return Object.fromEntries(Object.entries(values).filter(([k]) => k in defaults));
+}
+
+export function describeIssue(issue: SettingsIssue): string {
+  const file = issue.layer === "workspace" ? "workspace settings" : "settings";
+  return issue.key ? `${issue.key} in ${file}: ${issue.message}` : `${file}: ${issue.message}`;
+}
 
 const defaults: Settings = {
   theme: "light",
@@ -41,55 +99,64 @@ const defaults: Settings = {
   fontFamily: "serif",
   autoSaveInterval: 0,
   panelWidths: { fileTree: 224, comments: 288, history: 288, tags: 288 },
-  recentFiles: [],
   spellCheckEnabled: false,
   defaultAuthor: "Author",
   editorWidth: "medium",
   lineHeight: "comfortable",
   accentColor: "teal",
+  followSymlinks: true,
+  keybindings: {},
 };
 
+/** Store state for freshly loaded settings; reports ignored values */
+function applyLoaded(loaded: LoadedSettings, workspace: string | null): Partial<SettingsState> {
+  const workspaceKeys = loaded.workspaceKeys.filter((k): k is keyof Settings => k in defaults);
+  const global = { ...defaults, ...known(loaded.global) };
+  globalValues = Object.fromEntries(workspaceKeys.map((k) => [k, global[k]]));
+  if (loaded.issues.length > 0) {
+    const detail = loaded.issues.map(describeIssue).join("; ");
+    useToastStore.getState().addToast(`Some settings were ignored — ${detail}`, "error", 8000);
+  }
+  return {
+    ...defaults,
+    ...known(loaded.settings),
+    settingsWorkspace: workspace,
+    workspaceKeys,
+    settingsIssues: loaded.issues,
+    loaded: true,
+  };
+}
+
 export const useSettingsStore = create<SettingsState>((set, get) => ({
   ...defaults,
   loaded: false,
+  settingsWorkspace: null,
+  workspaceKeys: [],
+  settingsIssues: [],
 
-  loadSettings: async () => {
+  loadSettings: async (workspace = null) => {
     try {
-      const json = await invoke<string>("read_settings");
-      const parsed = JSON.parse(json) as Partial<Settings>;
-      // Map legacy fontFamily values
-      if (parsed.fontFamily && !["serif", "sans", "mono"].includes(parsed.fontFamily)) {
-        parsed.fontFamily = "serif";
-      }
-      // Merge panelWidths with defaults so old configs get new keys
-      if (parsed.panelWidths) {
-        parsed.panelWidths = { ...defaults.panelWidths, ...parsed.panelWidths };
-      }
-      set({ ...defaults, ...parsed, loaded: true });
+      const loaded = await invoke<LoadedSettings>("read_settings", { workspace });
+      set(applyLoaded(loaded, workspace));
     } catch {
       set({ ...defaults, loaded: true });
     }
   },
 
+  applyExternalChange: (change) => {
+    // A pending save holds newer values than the file; its own write
+    // comes back through here once it lands
+    if (saveTimer || saving > 0) return;
+    if (change.workspace !== get().settingsWorkspace) return;
+    set(applyLoaded(change, change.workspace));
+  },
+
   saveSettings: async () => {
-    const state = get();
-    const data: Settings = {
-      theme: state.theme,
-      fontSize: state.fontSize,
-      fontFamily: state.fontFamily,
-      autoSaveInterval: state.autoSaveInterval,
-      panelWidths: state.panelWidths,
-      recentFiles: state.recentFiles,
-      spellCheckEnabled: state.spellCheckEnabled,
-      defaultAuthor: state.defaultAuthor,
-      editorWidth: state.editorWidth,
-      lineHeight: state.lineHeight,
-      accentColor: state.accentColor,
-    };
+    saving++;
     try {
-      await invoke("write_settings", { content: JSON.stringify(data, null, 2) });
-    } catch (e) {
-      console.error("Failed to save settings:", e);
+      await writeSettings(get());
+    } finally {
+      saving--;
     }
   },
 
@@ -121,21 +188,14 @@ export const useSettingsStore = create<SettingsState>((set, get) => ({
   },
 
   setPanelWidth: (panel, width) => {
+    // Out-of-range widths would make the whole save fail validation
+    const clamped = Math.min(PANEL_WIDTH_MAX, Math.max(PANEL_WIDTH_MIN, Math.round(width)));
     set((s) => ({
-      panelWidths: { ...s.panelWidths, [panel]: width },
+      panelWidths: { ...s.panelWidths, [panel]: clamped },
     }));
     debouncedSave();
   },
 
-  addRecentFile: (path) => {
-    set((s) => {
-      const filtered = s.recentFiles.filter((f) => f !== path);
-      const recentFiles = [path, ...filtered].slice(0, 20);
-      return { recentFiles };
-    });
-    debouncedSave();
-  },
-
   setSpellCheckEnabled: (spellCheckEnabled) => {
     set({ spellCheckEnabled });
     debouncedSave();
@@ -160,6 +220,21 @@ export const useSettingsStore = create<SettingsState>((set, get) => ({
     set({ accentColor });
     debouncedSave();
   },
+
+  setFollowSymlinks: (followSymlinks) => {
+    set({ followSymlinks });
+    debouncedSave();
+  },
+
+  setKeybinding: (id, shortcut) => {
+    set((s) => {
+      const keybindings = { ...s.keybindings };
+      if (shortcut === null) delete keybindings[id];
+      else keybindings[id] = shortcut;
+      return { keybindings };
+    });
+    debouncedSave();
+  },
 }));
 
 function debouncedSave() {
@@ -169,3 +244,44 @@ function debouncedSave() {
     saveTimer = null;
   }, 500);
 }
+
+/** Write each value to the file it belongs in */
+async function writeSettings(state: SettingsState) {
+  const data: Settings = {
+    theme: state.theme,
+    fontSize: state.fontSize,
+    fontFamily: state.fontFamily,
+    autoSaveInterval: state.autoSaveInterval,
+    panelWidths: state.panelWidths,
+    spellCheckEnabled: state.spellCheckEnabled,
+    defaultAuthor: state.defaultAuthor,
+    editorWidth: state.editorWidth,
+    lineHeight: state.lineHeight,
+    accentColor: state.accentColor,
+    followSymlinks: state.followSymlinks,
+    keybindings: state.keybindings,
+  };
+  // Overridden keys go back to the workspace file, next to whatever else
+  // it holds; the global file keeps its own values for them
+  const { settingsWorkspace: workspace, workspaceKeys } = state;
+  if (workspace && workspaceKeys.length > 0) {
+    try {
+      const existing = JSON.parse(await invoke<string>("read_workspace_settings", { workspace }));
+      for (const key of workspaceKeys) existing[key] = data[key];
+      await invoke("write_workspace_settings", {
+        workspace,
+        content: JSON.stringify(existing, null, 2),
+      });
+    } catch (e) {
+      console.error("Failed to save workspace settings:", e);
+      useToastStore.getState().addToast(`Workspace settings weren't saved — ${e}`, "error", 8000);
+    }
+  }
+  const global = { ...data, ...globalValues };
+  try {
+    await invoke("write_settings", { content: JSON.stringify(global, null, 2) });
+  } catch (e) {
+    console.error("Failed to save settings:", e);
+    useToastStore.getState().addToast(`Settings weren't saved — ${e}`, "error", 8000);
+  }
+}
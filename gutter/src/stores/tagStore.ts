@@ -1,5 +1,6 @@
 import { create } from "zustand";
 import { invoke } from "@tauri-apps/api/core";
+import { useWorkspaceStore } from "./workspaceStore";
 import { parse as parseYaml } from "yaml";
 
 export interface TagInfo {
@@ -54,7 +55,7 @@ export const useTagStore = create<TagState>((set, get) => ({
     try {
       const tree = await invoke<
         { name: string; path: string; is_dir: boolean; children: unknown[] | null }[]
-      >("read_directory", { path: workspacePath });
+      >("read_directory", { path: workspacePath, roots: useWorkspaceStore.getState().roots });
 
       const mdFiles: string[] = [];
       const collectFiles = (
@@ -1,11 +1,178 @@
 import { create } from "zustand";
 import { invoke } from "@tauri-apps/api/core";
+import { isUnder, parentDir } from "../utils/path";
 
 export interface FileEntry {
   name: string;
   path: string;
   is_dir: boolean;
   children: FileEntry[] | null;
+  /** Visible entries in a directory, even when `children` isn't loaded */
+  child_count?: number;
+  /** Present when the listing was read with `metadata: true` */
+  meta?: FileMeta;
+  is_symlink?: boolean;
+  /** Top-level folder of a multi-root workspace; `name` is its label */
+  is_root?: boolean;
+  /** Why a directory wasn't expanded: unreadable, or a symlink loop */
+  error?: string;
+}
+
+/** Times are milliseconds since the epoch */
+export interface FileMeta {
+  size: number;
+  created: number | null;
+  modified: number | null;
+  title: string | null;
+  tags: string[];
+  word_count: number;
+  comment_count: number;
+  unresolved_comment_count: number;
+}
+
+/** `manual` (the default) follows `.gutter/order.json`, else name order */
+export type SortKey = "manual" | "name" | "modified" | "created" | "title" | "natural";
+
+/** Options for `read_directory` / `list_directory` */
+export interface ListOptions {
+  metadata?: boolean;
+  sort?: SortKey;
+  descending?: boolean;
+}
+
+/** One page of a directory level (`list_directory`) */
+interface DirectoryPage {
+  path: string;
+  entries: FileEntry[];
+  offset: number;
+  total: number;
+  hasMore: boolean;
+}
+
+/** One folder of a multi-root workspace (`.gutter-workspace` file) */
+export interface WorkspaceRoot {
+  path: string;
+  name: string;
+}
+
+/** The root containing `path`; the innermost one when roots nest */
+export function rootFor(roots: WorkspaceRoot[] | null, path: string): WorkspaceRoot | null {
+  let best: WorkspaceRoot | null = null;
+  for (const root of roots ?? []) {
+    if (path !== root.path && !isUnder(path, root.path)) continue;
+    if (!best || root.path.length > best.path.length) best = root;
+  }
+  return best;
+}
+
+/** Structural change reported by the watcher (`tree-diff` event) */
+export interface TreeDiff {
+  root: string;
+  upserted: FileEntry[];
+  removed: string[];
+  /** Sibling names in tree order for each folder that gained an entry */
+  order?: Record<string, string[]>;
+}
+
+function compareEntries(a: FileEntry, b: FileEntry): number {
+  if (a.is_dir !== b.is_dir) return a.is_dir ? -1 : 1;
+  const an = a.name.toLowerCase();
+  const bn = b.name.toLowerCase();
+  return an < bn ? -1 : an > bn ? 1 : 0;
+}
+
+function withCount(entry: FileEntry, children: FileEntry[]): FileEntry {
+  return { ...entry, children, child_count: children.length };
+}
+
+/** The list containing `path`: the root list or its parent's children */
+export function findSiblings(tree: FileEntry[], path: string): FileEntry[] | null {
+  if (tree.some((e) => e.path === path)) return tree;
+  for (const entry of tree) {
+    if (entry.children && isUnder(path, entry.path)) {
+      return findSiblings(entry.children, path);
+    }
+  }
+  return null;
+}
+
+/**
+ * Patch a tree rooted at `root` with a watcher diff. Upserts whose parent
+ * isn't in the tree are dropped; the parent's own upsert carries them.
+ */
+export function applyTreeDiff(tree: FileEntry[], root: string, diff: TreeDiff): FileEntry[] {
+  const removed = new Set(diff.removed);
+  const prune = (entries: FileEntry[]): FileEntry[] => {
+    let changed = false;
+    const next: FileEntry[] = [];
+    for (const entry of entries) {
+      if (removed.has(entry.path)) {
+        changed = true;
+        continue;
+      }
+      if (entry.children) {
+        const children = prune(entry.children);
+        if (children !== entry.children) {
+          changed = true;
+          next.push(withCount(entry, children));
+          continue;
+        }
+      }
+      next.push(entry);
+    }
+    return changed ? next : entries;
+  };
+
+  // Follow the backend's order (manual or sorted) where it sent one
+  const sorted = (dir: string, entries: FileEntry[]): FileEntry[] => {
+    const names = diff.order?.[dir];
+    if (!names) return entries.sort(compareEntries);
+    const rank = (e: FileEntry) => {
+      const i = names.indexOf(e.name);
+      return i < 0 ? names.length : i;
+    };
+    return entries.sort((a, b) => rank(a) - rank(b) || compareEntries(a, b));
+  };
+
+  const upsert = (entries: FileEntry[], dir: string, item: FileEntry): FileEntry[] => {
+    if (dir === parentDir(item.path)) {
+      const rest = entries.filter((e) => e.path !== item.path);
+      return sorted(dir, [...rest, item]);
+    }
+    let changed = false;
+    const next = entries.map((entry) => {
+      if (!entry.children || !isUnder(item.path, entry.path)) return entry;
+      const children = upsert(entry.children, entry.path, item);
+      if (children === entry.children) return entry;
+      changed = true;
+      return withCount(entry, children);
+    });
+    return changed ? next : entries;
+  };
+
+  let result = prune(tree);
+  for (const item of diff.upserted) {
+    if (!isUnder(item.path, root)) continue;
+    result = upsert(result, root, item);
+  }
+  return result;
+}
+
+/** Fill in the children of the directory at `path` */
+export function setChildren(tree: FileEntry[], path: string, children: FileEntry[]): FileEntry[] {
+  let changed = false;
+  const next = tree.map((entry) => {
+    if (entry.path === path) {
+      changed = true;
+      return withCount(entry, children);
+    }
+    if (!entry.children || !isUnder(path, entry.path)) return entry;
+    const inner = setChildren(entry.children, path, children);
+    if (inner === entry.children) return entry;
+    changed = true;
+    return { ...entry, children: inner };
+  });
+  return changed ? next : tree;
 }
 
 export interface OpenTab {
@@ -18,13 +185,27 @@ export interface OpenTab {
 }
 
 interface WorkspaceState {
+  /** The folder, or the first root of a multi-root workspace */
   workspacePath: string | null;
+  /** Set while a `.gutter-workspace` file is open */
+  workspaceFile: string | null;
+  roots: WorkspaceRoot[] | null;
   fileTree: FileEntry[];
+  /** What the tree is read with; the watcher lists its diffs the same way */
+  listOptions: ListOptions;
   openTabs: OpenTab[];
   activeTabPath: string | null;
 
   setWorkspacePath: (path: string | null) => void;
   loadFileTree: (path: string) => Promise<void>;
+  /** Load a directory the tree read left unexpanded (past its depth limit) */
+  loadChildren: (path: string) => Promise<void>;
+  openWorkspaceFile: (file: string) => Promise<void>;
+  /** Open a folder or a `.gutter-workspace` file */
+  openWorkspace: (path: string) => Promise<void>;
+  addWorkspaceRoot: (file: string, path: string) => Promise<void>;
+  applyTreeDiff: (diff: TreeDiff) => void;
+  moveEntry: (path: string, delta: number) => Promise<void>;
   addTab: (path: string, name: string) => void;
   removeTab: (path: string) => void;
   setActiveTab: (path: string | null) => void;
@@ -40,21 +221,110 @@ interface WorkspaceState {
 
 export const useWorkspaceStore = create<WorkspaceState>((set, get) => ({
   workspacePath: null,
+  workspaceFile: null,
+  roots: null,
   fileTree: [],
+  listOptions: {},
   openTabs: [],
   activeTabPath: null,
 
   setWorkspacePath: (path) => set({ workspacePath: path }),
 
   loadFileTree: async (path: string) => {
+    // Reloading the first root keeps a multi-root workspace; any other
+    // folder replaces it
+    const { roots, listOptions } = get();
+    const multi = roots && roots[0].path === path ? roots : null;
     try {
-      const tree = await invoke<FileEntry[]>("read_directory", { path });
-      set({ fileTree: tree, workspacePath: path });
+      const tree = await invoke<FileEntry[]>("read_directory", { path, roots: multi, options: listOptions });
+      set({ fileTree: tree, workspacePath: path, roots: multi });
+      if (!multi) set({ workspaceFile: null });
     } catch (e) {
       console.error("Failed to load file tree:", e);
     }
   },
 
+  loadChildren: async (path) => {
+    const { roots, workspacePath, listOptions } = get();
+    try {
+      const page = await invoke<DirectoryPage>("list_directory", {
+        path,
+        depth: 1,
+        workspace: rootFor(roots, path)?.path ?? workspacePath,
+        options: listOptions,
+      });
+      set((s) => ({ fileTree: setChildren(s.fileTree, path, page.entries) }));
+    } catch (e) {
+      console.error("Failed to load folder:", e);
+    }
+  },
+
+  openWorkspaceFile: async (file) => {
+    try {
+      const roots = await invoke<WorkspaceRoot[]>("open_workspace_file", { path: file });
+      set({ roots, workspaceFile: file });
+      await get().loadFileTree(roots[0].path);
+    } catch (e) {
+      console.error("Failed to open workspace file:", e);
+    }
+  },
+
+  openWorkspace: async (path) => {
+    if (path.endsWith(".gutter-workspace")) {
+      await get().openWorkspaceFile(path);
+    } else {
+      await get().loadFileTree(path);
+    }
+  },
+
+  addWorkspaceRoot: async (file, path) => {
+    const { roots, workspacePath, workspaceFile } = get();
+    const current = workspaceFile === file && roots
+      ? roots
+      : workspacePath ? [{ path: workspacePath, name: "" }] : [];
+    if (current.some((r) => r.path === path)) return;
+    try {
+      await invoke("save_workspace_file", { path: file, roots: [...current, { path, name: "" }] });
+      await get().openWorkspaceFile(file);
+    } catch (e) {
+      console.error("Failed to add folder to workspace:", e);
+    }
+  },
+
+  applyTreeDiff: (diff) => {
+    const { workspacePath, roots, fileTree } = get();
+    if (!roots) {
+      if (diff.root !== workspacePath) return;
+      set({ fileTree: applyTreeDiff(fileTree, diff.root, diff) });
+      return;
+    }
+    // Each root's entries live under its top-level entry
+    set({
+      fileTree: fileTree.map((entry) => {
+        if (entry.path !== diff.root || !entry.children) return entry;
+        const children = applyTreeDiff(entry.children, diff.root, diff);
+        return children === entry.children ? entry : withCount(entry, children);
+      }),
+    });
+  },
+
+  moveEntry: async (path, delta) => {
+    const { workspacePath, roots, fileTree, listOptions, loadFileTree } = get();
+    if (!workspacePath) return;
+    const siblings = findSiblings(fileTree, path);
+    // Roots keep the order of the workspace file
+    if (!siblings || (roots && siblings === fileTree)) return;
+    const index = siblings.findIndex((e) => e.path === path) + delta;
+    if (index < 0 || index >= siblings.length) return;
+    const workspace = rootFor(roots, path)?.path ?? workspacePath;
+    try {
+      await invoke("move_entry", { workspace, path, index, options: listOptions });
+      await loadFileTree(workspacePath);
+    } catch (e) {
+      console.error("Failed to move entry:", e);
+    }
+  },
+
   addTab: (path, name) => {
     const { openTabs } = get();
     if (!openTabs.find((t) => t.path === path)) {
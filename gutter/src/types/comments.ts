@@ -5,15 +5,66 @@ export interface CommentMessage {
   body: string;
 }
 
+export type ThreadStatus = "open" | "inProgress" | "wontFix" | "resolved";
+
+export type ThreadPriority = "low" | "normal" | "high" | "urgent";
+
+export interface StatusChange {
+  from: ThreadStatus;
+  to: ThreadStatus;
+  by: string;
+  at: string; // ISO 8601 UTC
+}
+
+// Anchored text and its surroundings as of the last save (maintained by the
+// backend, used to re-anchor a thread whose marker was lost)
+export interface AnchorFingerprint {
+  text: string;
+  prefix: string;
+  suffix: string;
+}
+
+// A suggested position for a thread whose marker was lost. Offsets are
+// UTF-16 offsets (as JS strings count) into the note as stored on disk.
+export interface AnchorCandidate {
+  threadId: string;
+  start: number;
+  end: number;
+  text: string;
+  confidence: number; // 0–1
+}
+
 export interface CommentThread {
   thread: CommentMessage[];
   resolved: boolean;
   resolvedBy?: string;
   resolvedAt?: string; // ISO 8601 UTC
   createdAt: string; // ISO 8601 UTC
+  // Review workflow state (optional; maintained by the backend)
+  status?: ThreadStatus;
+  assignees?: string[];
+  priority?: ThreadPriority;
+  labels?: string[];
+  dueDate?: string; // YYYY-MM-DD
+  statusHistory?: StatusChange[];
+  workflowRevision?: number;
+  fingerprint?: AnchorFingerprint;
+}
+
+export type SuggestionKind = "insert" | "delete" | "replace";
+
+// A tracked change, marked in the document as
+// <del>old</del><ins>new</ins><sup>[sN]</sup>
+export interface Suggestion {
+  kind: SuggestionKind;
+  author: string;
+  timestamp: string; // ISO 8601 UTC
+  deletedText?: string;
+  insertedText?: string;
 }
 
 export interface CommentsFile {
   version: 1;
   comments: Record<string, CommentThread>;
+  suggestions?: Record<string, Suggestion>;
 }
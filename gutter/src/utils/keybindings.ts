@@ -0,0 +1,100 @@
+import { isMac, modLabel } from "./platform";
+
+/*
+ * Shortcuts are written the way the backend normalizes them and the native
+ * menu takes them: modifiers (CmdOrCtrl, Cmd, Ctrl, Alt, Shift) in that
+ * order, then one key, joined with "+" — e.g. "CmdOrCtrl+Shift+E".
+ */
+
+/** Keys written as a character, by `KeyboardEvent.code` */
+const PUNCTUATION: Record<string, string> = {
+  Comma: ",",
+  Period: ".",
+  Slash: "/",
+  Backslash: "\\",
+  Semicolon: ";",
+  Quote: "'",
+  BracketLeft: "[",
+  BracketRight: "]",
+  Minus: "-",
+  Equal: "=",
+  Backquote: "`",
+};
+
+/** Keys whose name differs from their `KeyboardEvent.code` */
+const ARROWS: Record<string, string> = {
+  ArrowUp: "Up",
+  ArrowDown: "Down",
+  ArrowLeft: "Left",
+  ArrowRight: "Right",
+};
+
+const NAMED = new Set([
+  "Enter", "Escape", "Tab", "Space", "Backspace", "Delete", "Insert", "Home", "End", "PageUp", "PageDown",
+]);
+
+type KeyState = Pick<KeyboardEvent, "key" | "code" | "metaKey" | "ctrlKey" | "altKey" | "shiftKey">;
+
+/** The key a physical `code` stands for on a US layout, if it can be bound */
+function codeName(code: string): string | null {
+  if (/^Key[A-Z]$/.test(code)) return code.slice(3);
+  if (/^Digit[0-9]$/.test(code)) return code.slice(5);
+  if (/^F([1-9]|1[0-9]|2[0-4])$/.test(code)) return code;
+  return PUNCTUATION[code] ?? ARROWS[code] ?? (NAMED.has(code) ? code : null);
+}
+
+/** The key an event stands for in a shortcut: the logical key, so Cmd+S is
+ * the key labelled S on any layout. The physical `code` is the fallback
+ * when the logical key isn't one a shortcut can name — punctuation and
+ * digits under Shift, Alt-composed characters, non-Latin layouts. */
+function keyName(e: KeyState): string | null {
+  const key = e.key;
+  if (/^[a-z]$/i.test(key)) return key.toUpperCase();
+  if (/^[0-9]$/.test(key) || Object.values(PUNCTUATION).includes(key)) return key;
+  if (key === " ") return "Space";
+  if (ARROWS[key]) return ARROWS[key];
+  if (NAMED.has(key) || /^F([1-9]|1[0-9]|2[0-4])$/.test(key)) return key;
+  return codeName(e.code);
+}
+
+/** Whether a key press is exactly `shortcut` — no more modifiers, no fewer */
+export function matchesShortcut(e: KeyState, shortcut: string): boolean {
+  const parts = shortcut.split("+");
+  const key = parts.pop();
+  if (!key) return false;
+  const mods = new Set(parts);
+  const meta = mods.has("Cmd") || (mods.has("CmdOrCtrl") && isMac());
+  const ctrl = mods.has("Ctrl") || (mods.has("CmdOrCtrl") && !isMac());
+  return (
+    keyName(e) === key &&
+    e.metaKey === meta &&
+    e.ctrlKey === ctrl &&
+    e.altKey === mods.has("Alt") &&
+    e.shiftKey === mods.has("Shift")
+  );
+}
+
+/** The shortcut a key press makes, for recording one; `null` while only
+ * modifiers are down, or for a key without a modifier (F-keys aside) */
+export function shortcutFromEvent(e: KeyState): string | null {
+  const key = keyName(e);
+  if (!key) return null;
+  const mods: string[] = [];
+  // The platform's main modifier is saved as CmdOrCtrl, so the binding
+  // carries over between macOS and the others
+  const primary = isMac() ? e.metaKey : e.ctrlKey;
+  if (primary) mods.push("CmdOrCtrl");
+  if (isMac() ? e.ctrlKey : e.metaKey) mods.push(isMac() ? "Ctrl" : "Cmd");
+  if (e.altKey) mods.push("Alt");
+  if (e.shiftKey) mods.push("Shift");
+  if (mods.length === 0 && !/^F\d+$/.test(key)) return null;
+  return [...mods, key].join("+");
+}
+
+/** A shortcut as shown to the user: `Cmd+Shift+E` on macOS, `Ctrl+Shift+E` elsewhere */
+export function formatShortcut(shortcut: string): string {
+  return shortcut
+    .split("+")
+    .map((part) => (part === "CmdOrCtrl" ? modLabel() : part === "Cmd" && !isMac() ? "Super" : part))
+    .join("+");
+}
@@ -8,10 +8,22 @@ export function parentDir(p: string): string {
   return idx > 0 ? p.substring(0, idx) : p;
 }
 
+/** Whether `p` lies below `dir`; either separator counts */
+export function isUnder(p: string, dir: string): boolean {
+  if (p.length <= dir.length || !p.startsWith(dir)) return false;
+  return /[/\\]$/.test(dir) || p[dir.length] === "/" || p[dir.length] === "\\";
+}
+
 export function joinPath(...segments: string[]): string {
   return segments.map(s => s.replace(/[/\\]$/, "")).join("/");
 }
 
+/** Absolute path of a link returned by `save_image` / `copy_image` */
+export function resolveAttachment(dirPath: string, link: string): string {
+  if (link.startsWith("/") || /^[a-zA-Z]:[/\\]/.test(link)) return link;
+  return normalizePath(joinPath(dirPath, link));
+}
+
 /** Resolve . and .. components in a forward-slash path */
 export function normalizePath(p: string): string {
   const parts = p.split("/");
@@ -32,15 +44,41 @@ export function normalizePath(p: string): string {
   return resolved.join("/");
 }
 
+/**
+ * Pick the closest of several matches: in a multi-root tree, matches in
+ * the same root as `fromPath` first, then the fewest segments below their
+ * root.
+ */
+function pickClosest(
+  matches: string[],
+  entries: { path: string; is_root?: boolean }[],
+  fromPath?: string,
+): string {
+  const roots = entries.filter((e) => e.is_root).map((e) => e.path);
+  const rootOf = (p: string) =>
+    roots.filter((r) => isUnder(p, r)).sort((a, b) => b.length - a.length)[0] ?? "";
+  const home = fromPath ? rootOf(fromPath) : "";
+  const rank = (p: string) => {
+    const root = rootOf(p);
+    return [root === home ? 0 : 1, p.slice(root.length).split(/[/\\]/).length];
+  };
+  return matches.sort((a, b) => {
+    const [ra, rb] = [rank(a), rank(b)];
+    return ra[0] - rb[0] || ra[1] - rb[1];
+  })[0];
+}
+
 /**
  * Obsidian-style wiki link resolution.
  * Collects all files matching by name (or path suffix), then picks the
  * shortest path — which mirrors Obsidian's "shortest path first" strategy.
- * Supports bare names (`Note`) and path-qualified (`folder/Note`).
+ * Supports bare names (`Note`) and path-qualified (`folder/Note`). Across
+ * the roots of a multi-root workspace, the root holding `fromPath` wins.
  */
 export function resolveWikiLink(
   target: string,
-  entries: { name: string; path: string; is_dir: boolean; children: { name: string; path: string; is_dir: boolean; children: unknown }[] | null }[],
+  entries: { name: string; path: string; is_dir: boolean; is_root?: boolean; children: { name: string; path: string; is_dir: boolean; children: unknown }[] | null }[],
+  fromPath?: string,
 ): string | null {
   const matches: string[] = [];
   const targetWithExt = target.endsWith(".md") ? target : `${target}.md`;
@@ -71,7 +109,7 @@ export function resolveWikiLink(
   if (matches.length === 0) return null;
   if (matches.length === 1) return matches[0];
   // Shortest path wins (fewest segments = closest to root)
-  return matches.sort((a, b) => a.split("/").length - b.split("/").length)[0];
+  return pickClosest(matches, entries, fromPath);
 }
 
 /**
@@ -81,7 +119,8 @@ export function resolveWikiLink(
  */
 export function resolveFileInTree(
   target: string,
-  entries: { name: string; path: string; is_dir: boolean; children: { name: string; path: string; is_dir: boolean; children: unknown }[] | null }[],
+  entries: { name: string; path: string; is_dir: boolean; is_root?: boolean; children: { name: string; path: string; is_dir: boolean; children: unknown }[] | null }[],
+  fromPath?: string,
 ): string | null {
   const matches: string[] = [];
   const hasPath = target.includes("/");
@@ -106,7 +145,7 @@ export function resolveFileInTree(
 
   if (matches.length === 0) return null;
   if (matches.length === 1) return matches[0];
-  return matches.sort((a, b) => a.split("/").length - b.split("/").length)[0];
+  return pickClosest(matches, entries, fromPath);
 }
 
 const IMAGE_EXTENSIONS = new Set(["png", "jpg", "jpeg", "gif", "webp", "svg", "bmp", "ico"]);
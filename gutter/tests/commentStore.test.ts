@@ -106,4 +106,25 @@ createdAt: "2026-02-13T10:00:00Z",
 const threads = useCommentStore.getState().threads;
 expect(threads.c1.thread[0].body).toBe("Loaded comment");
 });
+
+it("keeps suggestions through load and save", () => {
+const store = useCommentStore.getState();
+store.loadComments({
+version: 1,
+comments: {},
+suggestions: {
+s1: {
+kind: "delete",
+author: "Dave",
+timestamp: "2026-02-13T10:00:00Z",
+deletedText: "very",
+},
+},
+});
+const file = useCommentStore.getState().getCommentsFile();
+expect(file.suggestions?.s1.deletedText).toBe("very");
+useCommentStore.getState().clearAll();
+// Sent even when empty, so the backend drops the last one too
+expect(useCommentStore.getState().getCommentsFile().suggestions).toEqual({});
+});
 });
@@ -115,4 +115,36 @@ The strategy <mark>should focus on long-term growth</mark><sup>[c1]</sup> rather
     const output = buildCompanionMarkdown("/path/doc.md", mdContent, commentsFile);
     expect(output).toContain("a".repeat(80) + "...");
   });
+
+  it("lists pending suggestions", () => {
+    const mdContent = `Say <del>hello</del><ins>hi</ins><sup>[s2]</sup> to the <ins>whole </ins><sup>[s1]</sup>world.`;
+
+    const commentsFile: CommentsFile = {
+      version: 1,
+      comments: {},
+      suggestions: {
+        s1: {
+          kind: "insert",
+          author: "Sarah",
+          timestamp: "2026-02-13T10:00:00Z",
+          insertedText: "whole ",
+        },
+        s2: {
+          kind: "replace",
+          author: "Dave",
+          timestamp: "2026-02-13T10:05:00Z",
+          deletedText: "hello",
+          insertedText: "hi",
+        },
+      },
+    };
+
+    const output = buildCompanionMarkdown("/path/doc.md", mdContent, commentsFile);
+    expect(output).toContain("## Suggestions");
+    expect(output).toContain('**[s2]** replace "hello" with "hi"');
+    expect(output).toContain('**[s1]** insert "whole "');
+    // Document order, not id order
+    expect(output.indexOf("[s2]")).toBeLessThan(output.indexOf("[s1]"));
+    expect(output).toContain("0 comments (0 resolved, 0 open), 2 suggestions");
+  });
 });
@@ -0,0 +1,87 @@
+// gutter/tests/keybindings.test.ts
+import { describe, it, expect } from "vitest";
+import { matchesShortcut, shortcutFromEvent, formatShortcut } from "../src/utils/keybindings";
+
+// What each code types on a US layout, unshifted
+const US: Record<string, string> = { Backslash: "\\", Comma: ",", Period: ".", Slash: "/", Space: " " };
+const usKey = (code: string) =>
+  US[code] ?? (/^(Key|Digit).$/.test(code) ? code.slice(-1).toLowerCase() : code);
+
+// jsdom's navigator.platform is not a Mac, so CmdOrCtrl means Ctrl here.
+// `key` defaults to what `code` types on a US layout
+const press = (
+  code: string,
+  mods: { meta?: boolean; ctrl?: boolean; alt?: boolean; shift?: boolean; key?: string } = {},
+) => ({
+  key: mods.key ?? usKey(code),
+  code,
+  metaKey: !!mods.meta,
+  ctrlKey: !!mods.ctrl,
+  altKey: !!mods.alt,
+  shiftKey: !!mods.shift,
+});
+
+describe("matchesShortcut", () => {
+  it("maps CmdOrCtrl to Ctrl off macOS", () => {
+    expect(matchesShortcut(press("KeyS", { ctrl: true }), "CmdOrCtrl+S")).toBe(true);
+    expect(matchesShortcut(press("KeyS", { meta: true }), "CmdOrCtrl+S")).toBe(false);
+  });
+
+  it("requires exactly the shortcut's modifiers", () => {
+    expect(matchesShortcut(press("KeyE", { ctrl: true }), "CmdOrCtrl+Shift+E")).toBe(false);
+    expect(matchesShortcut(press("KeyE", { ctrl: true, shift: true, alt: true }), "CmdOrCtrl+Shift+E")).toBe(false);
+    expect(matchesShortcut(press("KeyE", { ctrl: true, shift: true }), "CmdOrCtrl+Shift+E")).toBe(true);
+  });
+
+  it("matches punctuation, digits, arrows and function keys", () => {
+    expect(matchesShortcut(press("Backslash", { ctrl: true }), "CmdOrCtrl+\\")).toBe(true);
+    expect(matchesShortcut(press("Comma", { ctrl: true }), "CmdOrCtrl+,")).toBe(true);
+    expect(matchesShortcut(press("Digit1", { alt: true }), "Alt+1")).toBe(true);
+    expect(matchesShortcut(press("ArrowUp", { ctrl: true }), "CmdOrCtrl+Up")).toBe(true);
+    expect(matchesShortcut(press("F5"), "F5")).toBe(true);
+  });
+
+  it("matches the logical key on other layouts", () => {
+    // Dvorak: the key labelled S sits where QWERTY has O, and KeyS types O
+    expect(matchesShortcut(press("Semicolon", { ctrl: true, key: "s" }), "CmdOrCtrl+S")).toBe(true);
+    expect(matchesShortcut(press("KeyS", { ctrl: true, key: "o" }), "CmdOrCtrl+S")).toBe(false);
+    expect(matchesShortcut(press("KeyS", { ctrl: true, key: "o" }), "CmdOrCtrl+O")).toBe(true);
+  });
+
+  it("falls back to the physical key for shifted punctuation and other scripts", () => {
+    expect(matchesShortcut(press("Comma", { ctrl: true, shift: true, key: "<" }), "CmdOrCtrl+Shift+,")).toBe(true);
+    expect(matchesShortcut(press("Digit1", { ctrl: true, shift: true, key: "!" }), "CmdOrCtrl+Shift+1")).toBe(true);
+    expect(matchesShortcut(press("KeyS", { ctrl: true, key: "ы" }), "CmdOrCtrl+S")).toBe(true);
+  });
+});
+
+describe("shortcutFromEvent", () => {
+  it("records the primary modifier as CmdOrCtrl", () => {
+    expect(shortcutFromEvent(press("KeyK", { ctrl: true, shift: true }))).toBe("CmdOrCtrl+Shift+K");
+    expect(shortcutFromEvent(press("KeyK", { meta: true }))).toBe("Cmd+K");
+  });
+
+  it("lists modifiers in canonical order", () => {
+    expect(shortcutFromEvent(press("Period", { shift: true, alt: true, ctrl: true }))).toBe("CmdOrCtrl+Alt+Shift+.");
+  });
+
+  it("ignores modifier-only presses and unmodified keys", () => {
+    expect(shortcutFromEvent(press("ShiftLeft", { shift: true }))).toBeNull();
+    expect(shortcutFromEvent(press("ControlLeft", { ctrl: true }))).toBeNull();
+    expect(shortcutFromEvent(press("KeyA"))).toBeNull();
+    expect(shortcutFromEvent(press("F2"))).toBe("F2");
+  });
+
+  it("round-trips through matchesShortcut", () => {
+    const e = press("Slash", { ctrl: true, alt: true });
+    expect(matchesShortcut(e, shortcutFromEvent(e)!)).toBe(true);
+  });
+});
+
+describe("formatShortcut", () => {
+  it("shows platform modifier names", () => {
+    expect(formatShortcut("CmdOrCtrl+Shift+E")).toBe("Ctrl+Shift+E");
+    expect(formatShortcut("Cmd+K")).toBe("Super+K");
+    expect(formatShortcut("F5")).toBe("F5");
+  });
+});
@@ -0,0 +1,78 @@
+// gutter/tests/multiRoot.test.ts
+import { describe, it, expect } from "vitest";
+import { resolveWikiLink } from "../src/utils/path";
+import { rootFor, type FileEntry } from "../src/stores/workspaceStore";
+
+const file = (path: string): FileEntry => ({
+  name: path.split(/[/\\]/).pop()!,
+  path,
+  is_dir: false,
+  children: null,
+});
+
+const root = (path: string, name: string, children: FileEntry[]): FileEntry => ({
+  name,
+  path,
+  is_dir: true,
+  is_root: true,
+  children,
+});
+
+const tree = [
+  root("/home/me/notes", "Notes", [file("/home/me/notes/Plan.md")]),
+  root("/t", "Team", [
+    {
+      name: "deep",
+      path: "/t/deep",
+      is_dir: true,
+      children: [file("/t/deep/Plan.md")],
+    },
+    file("/t/Intro.md"),
+  ]),
+];
+
+describe("resolveWikiLink across roots", () => {
+  it("finds notes in any root", () => {
+    expect(resolveWikiLink("Intro", tree, "/home/me/notes/Plan.md")).toBe("/t/Intro.md");
+  });
+
+  it("prefers the root holding the linking note", () => {
+    expect(resolveWikiLink("Plan", tree, "/t/Intro.md")).toBe("/t/deep/Plan.md");
+    expect(resolveWikiLink("Plan", tree, "/home/me/notes/Other.md")).toBe("/home/me/notes/Plan.md");
+  });
+
+  it("measures depth from each root, not the filesystem", () => {
+    expect(resolveWikiLink("Plan", tree)).toBe("/home/me/notes/Plan.md");
+  });
+
+  it("handles Windows paths", () => {
+    const winTree = [
+      root("C:\\Users\\me\\notes", "Notes", [file("C:\\Users\\me\\notes\\Plan.md")]),
+      root("D:\\t", "Team", [
+        { name: "deep", path: "D:\\t\\deep", is_dir: true, children: [file("D:\\t\\deep\\Plan.md")] },
+      ]),
+    ];
+    expect(resolveWikiLink("Plan", winTree, "D:\\t\\Intro.md")).toBe("D:\\t\\deep\\Plan.md");
+    expect(resolveWikiLink("Plan", winTree)).toBe("C:\\Users\\me\\notes\\Plan.md");
+  });
+});
+
+describe("rootFor", () => {
+  const roots = [
+    { path: "/work", name: "work" },
+    { path: "/work/team", name: "team" },
+  ];
+
+  it("picks the innermost root", () => {
+    expect(rootFor(roots, "/work/team/a.md")?.name).toBe("team");
+    expect(rootFor(roots, "/work/b.md")?.name).toBe("work");
+    expect(rootFor(roots, "/workshop/c.md")).toBeNull();
+    expect(rootFor(null, "/work/b.md")).toBeNull();
+  });
+
+  it("matches Windows paths", () => {
+    const winRoots = [{ path: "C:\\work", name: "work" }];
+    expect(rootFor(winRoots, "C:\\work\\b.md")?.name).toBe("work");
+    expect(rootFor(winRoots, "C:\\workshop\\c.md")).toBeNull();
+  });
+});
@@ -0,0 +1,111 @@
+// gutter/tests/treeDiff.test.ts
+import { describe, it, expect } from "vitest";
+import { applyTreeDiff, findSiblings, setChildren, type FileEntry } from "../src/stores/workspaceStore";
+import { isUnder } from "../src/utils/path";
+
+const file = (path: string): FileEntry => ({
+  name: path.split(/[/\\]/).pop()!,
+  path,
+  is_dir: false,
+  children: null,
+});
+
+const dir = (path: string, children: FileEntry[]): FileEntry => ({
+  name: path.split(/[/\\]/).pop()!,
+  path,
+  is_dir: true,
+  children,
+  child_count: children.length,
+});
+
+const tree = [dir("/ws/notes", [file("/ws/notes/a.md")]), file("/ws/readme.md")];
+
+describe("applyTreeDiff", () => {
+  it("inserts new entries in tree order", () => {
+    const next = applyTreeDiff(tree, "/ws", {
+      root: "/ws",
+      upserted: [file("/ws/notes/B.md"), dir("/ws/drafts", [])],
+      removed: [],
+    });
+    expect(next.map((e) => e.name)).toEqual(["drafts", "notes", "readme.md"]);
+    expect(next[1].children!.map((e) => e.name)).toEqual(["a.md", "B.md"]);
+    expect(next[1].child_count).toBe(2);
+  });
+
+  it("places new entries by the order the backend sent", () => {
+    const next = applyTreeDiff(tree, "/ws", {
+      root: "/ws",
+      upserted: [file("/ws/notes/B.md")],
+      removed: [],
+      order: { "/ws/notes": ["B.md", "a.md"] },
+    });
+    expect(next[0].children!.map((e) => e.name)).toEqual(["B.md", "a.md"]);
+  });
+
+  it("removes entries at any depth", () => {
+    const next = applyTreeDiff(tree, "/ws", {
+      root: "/ws",
+      upserted: [],
+      removed: ["/ws/notes/a.md"],
+    });
+    expect(next[0].children).toEqual([]);
+    expect(next[0].child_count).toBe(0);
+    expect(next[1]).toBe(tree[1]);
+  });
+
+  it("replaces an existing entry instead of duplicating it", () => {
+    const next = applyTreeDiff(tree, "/ws", {
+      root: "/ws",
+      upserted: [file("/ws/readme.md")],
+      removed: [],
+    });
+    expect(next).toHaveLength(2);
+  });
+
+  it("handles Windows paths", () => {
+    const winTree = [dir("C:\\ws\\notes", [file("C:\\ws\\notes\\a.md")])];
+    const next = applyTreeDiff(winTree, "C:\\ws", {
+      root: "C:\\ws",
+      upserted: [file("C:\\ws\\notes\\b.md")],
+      removed: ["C:\\ws\\notes\\a.md"],
+    });
+    expect(next[0].children!.map((e) => e.name)).toEqual(["b.md"]);
+    expect(findSiblings(next, "C:\\ws\\notes\\b.md")).toBe(next[0].children);
+  });
+
+  it("drops entries whose parent isn't loaded", () => {
+    const next = applyTreeDiff(tree, "/ws", {
+      root: "/ws",
+      upserted: [file("/ws/missing/x.md")],
+      removed: [],
+    });
+    expect(next).toBe(tree);
+  });
+});
+
+describe("findSiblings", () => {
+  it("finds the list holding an entry", () => {
+    expect(findSiblings(tree, "/ws/readme.md")).toBe(tree);
+    expect(findSiblings(tree, "/ws/notes/a.md")).toBe(tree[0].children);
+    expect(findSiblings(tree, "/ws/other.md")).toBeNull();
+  });
+});
+
+describe("setChildren", () => {
+  it("fills in a folder loaded on demand", () => {
+    const deep = [dir("/ws/a", [{ ...dir("/ws/a/b", []), children: null, child_count: 1 }])];
+    const next = setChildren(deep, "/ws/a/b", [file("/ws/a/b/c.md")]);
+    expect(next[0].children![0].children!.map((e) => e.name)).toEqual(["c.md"]);
+    expect(setChildren(deep, "/ws/other", [])).toBe(deep);
+  });
+});
+
+describe("isUnder", () => {
+  it("accepts either separator and stops at name boundaries", () => {
+    expect(isUnder("/ws/notes/a.md", "/ws")).toBe(true);
+    expect(isUnder("C:\\ws\\a.md", "C:\\ws")).toBe(true);
+    expect(isUnder("C:\\a.md", "C:\\")).toBe(true);
+    expect(isUnder("/ws-old/a.md", "/ws")).toBe(false);
+    expect(isUnder("/ws", "/ws")).toBe(false);
+  });
+});